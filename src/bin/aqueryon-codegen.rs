@@ -0,0 +1,73 @@
+//! aqueryon-codegen - Generate typed table modules from a schema dump
+//!
+//! ```text
+//! aqueryon-codegen <schema-file> [--format ddl|csv|json] [--database Name] [--output file.rs]
+//! ```
+//!
+
+use aqueryon::codegen::{generate_from_file, SchemaFormat};
+use std::path::PathBuf;
+use std::process::exit;
+
+const USAGE: &str =
+    "usage: aqueryon-codegen <schema-file> [--format ddl|csv|json] [--database Name] [--output file.rs]";
+
+struct Args {
+    input: PathBuf,
+    format: Option<SchemaFormat>,
+    database: String,
+    output: Option<PathBuf>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = std::env::args().skip(1);
+    let mut input = None;
+    let mut format = None;
+    let mut database = "Db".to_string();
+    let mut output = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("{} requires a value", name));
+        match arg.as_str() {
+            "-f" | "--format" => {
+                format = Some(SchemaFormat::from_name(&value(&arg)?).map_err(|e| e.to_string())?)
+            }
+            "-d" | "--database" => database = value(&arg)?,
+            "-o" | "--output" => output = Some(PathBuf::from(value(&arg)?)),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                exit(0);
+            }
+            _ if arg.starts_with('-') => return Err(format!("unknown option: {}", arg)),
+            _ if input.is_none() => input = Some(PathBuf::from(arg)),
+            _ => return Err(format!("unexpected argument: {}", arg)),
+        }
+    }
+
+    Ok(Args {
+        input: input.ok_or("missing <schema-file>")?,
+        format,
+        database,
+        output,
+    })
+}
+
+fn main() {
+    let args = parse_args().unwrap_or_else(|e| {
+        eprintln!("{}\n{}", e, USAGE);
+        exit(2);
+    });
+
+    let result = generate_from_file(&args.input, args.format, &args.database).and_then(|code| {
+        match &args.output {
+            Some(path) => std::fs::write(path, code)?,
+            None => print!("{}", code),
+        }
+        Ok(())
+    });
+
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        exit(1);
+    }
+}
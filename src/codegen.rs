@@ -0,0 +1,299 @@
+//! Code Generator
+//!
+//! Generates Rust modules of typed tables from a schema dump, so that it works offline.
+//! Used by the `aqueryon-codegen` binary and enabled by the `codegen` feature.
+//!
+//! Supported inputs:
+//!
+//! - `CREATE TABLE` DDL (`.sql`)
+//! - `information_schema.COLUMNS` exported as CSV (`.csv`) or a JSON array of objects (`.json`)
+//!
+
+mod ddl;
+mod information_schema;
+
+use std::fmt::Write;
+use std::path::Path;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CodegenError {
+    #[error("CodegenError::IoError: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("CodegenError::ParseError: line {line}: {message}")]
+    ParseError { line: usize, message: String },
+    #[error("CodegenError::UnknownFormat: {0}")]
+    UnknownFormat(String),
+    /// Two names which map to the same Rust identifier, e.g. `user-id` and `user_id`.
+    #[error("CodegenError::NameCollision: {0}")]
+    NameCollision(String),
+}
+
+impl CodegenError {
+    fn parse(line: usize, message: impl Into<String>) -> CodegenError {
+        CodegenError::ParseError {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum SchemaFormat {
+    Ddl,
+    Csv,
+    Json,
+}
+
+impl SchemaFormat {
+    pub fn from_name(name: &str) -> Result<SchemaFormat, CodegenError> {
+        match name.to_ascii_lowercase().as_str() {
+            "ddl" | "sql" => Ok(SchemaFormat::Ddl),
+            "csv" => Ok(SchemaFormat::Csv),
+            "json" => Ok(SchemaFormat::Json),
+            _ => Err(CodegenError::UnknownFormat(name.to_string())),
+        }
+    }
+
+    /// Guess the format from the file extension.
+    pub fn from_path(path: &Path) -> Result<SchemaFormat, CodegenError> {
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        SchemaFormat::from_name(extension)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct Schema {
+    pub tables: Vec<TableSchema>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TableSchema {
+    pub name: String,
+    pub columns: Vec<ColumnSchema>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ColumnSchema {
+    pub name: String,
+    /// Column type as written in the dump, e.g. `int(10) unsigned`.
+    pub column_type: String,
+    pub nullable: bool,
+}
+
+impl ColumnSchema {
+    /// Name of the `SqlType*` marker for the column type.
+    pub fn sql_type_marker(&self) -> &'static str {
+        let column_type = self.column_type.to_ascii_lowercase();
+        let base = column_type
+            .split(|c: char| c == '(' || c.is_whitespace())
+            .next()
+            .unwrap_or("");
+        let unsigned = column_type.contains("unsigned");
+        match base {
+            "tinyint" | "smallint" | "mediumint" | "int" | "integer" | "bigint" | "int2"
+            | "int4" | "int8" | "smallserial" | "serial" | "bigserial" | "year" => {
+                if unsigned {
+                    "SqlTypeUint"
+                } else {
+                    "SqlTypeInt"
+                }
+            }
+            "bool" | "boolean" => "SqlTypeBool",
+            "json" | "jsonb" => "SqlTypeJson",
+            "char" | "varchar" | "character" | "nchar" | "nvarchar" | "tinytext" | "text"
+            | "mediumtext" | "longtext" | "enum" | "set" | "uuid" | "date" | "datetime"
+            | "timestamp" | "timestamptz" | "time" => "SqlTypeString",
+            _ => "SqlTypeAny",
+        }
+    }
+}
+
+impl Schema {
+    pub fn parse(input: &str, format: SchemaFormat) -> Result<Schema, CodegenError> {
+        match format {
+            SchemaFormat::Ddl => ddl::parse(input),
+            SchemaFormat::Csv => information_schema::parse_csv(input),
+            SchemaFormat::Json => information_schema::parse_json(input),
+        }
+    }
+
+    /// Rust source defining the database marker `database` and a module per table.
+    ///
+    /// Fails if two tables, or two columns of a table, map to the same Rust identifier.
+    pub fn generate(&self, database: &str) -> Result<String, CodegenError> {
+        self.check_identifiers()?;
+        let mut out = String::new();
+        // Writing to a String never fails.
+        let _ = self.write_rust(&mut out, database);
+        Ok(out)
+    }
+
+    fn check_identifiers(&self) -> Result<(), CodegenError> {
+        check_unique(
+            "table",
+            self.tables
+                .iter()
+                .map(|table| (table.name.as_str(), rust_ident(&table.name))),
+        )?;
+        for table in &self.tables {
+            let columns = table
+                .columns
+                .iter()
+                .map(|column| (column.name.as_str(), column_ident(&column.name)));
+            check_unique(&format!("column of `{}`", table.name), columns)?;
+        }
+        Ok(())
+    }
+
+    fn write_rust(&self, out: &mut String, database: &str) -> std::fmt::Result {
+        writeln!(out, "// Generated by aqueryon-codegen. Do not edit.")?;
+        writeln!(out)?;
+        writeln!(
+            out,
+            "#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]"
+        )?;
+        writeln!(out, "pub struct {};", database)?;
+        writeln!(out)?;
+        writeln!(out, "::aqueryon::impl_joinable!({});", database)?;
+
+        for table in &self.tables {
+            writeln!(out)?;
+            writeln!(out, "pub mod {} {{", rust_ident(&table.name))?;
+            writeln!(out, "    #![allow(dead_code)]")?;
+            writeln!(out, "    use ::aqueryon::query_builder::*;")?;
+            writeln!(out)?;
+            writeln!(out, "    pub const NAME: &str = {:?};", table.name)?;
+            writeln!(out)?;
+            // A marker per table, so that the columns only accept sources of this table.
+            writeln!(
+                out,
+                "    #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]"
+            )?;
+            writeln!(out, "    pub struct Marker;")?;
+            writeln!(out)?;
+            writeln!(out, "    impl TableMarker for Marker {{")?;
+            writeln!(out, "        type Database = super::{};", database)?;
+            writeln!(out, "        const NAME: &'static str = NAME;")?;
+            writeln!(out, "    }}")?;
+            writeln!(out)?;
+            writeln!(out, "    pub type Table = TypedTable<Marker>;")?;
+            writeln!(out, "    pub type Source = QuerySourceRef<Table>;")?;
+            writeln!(out)?;
+            writeln!(out, "    pub fn table() -> Table {{")?;
+            writeln!(out, "        TypedTable::new()")?;
+            writeln!(out, "    }}")?;
+            for column in &table.columns {
+                let ident = column_ident(&column.name);
+                writeln!(out)?;
+                writeln!(
+                    out,
+                    "    /// `{}` {}{}",
+                    column.name,
+                    column.column_type,
+                    if column.nullable { "" } else { " NOT NULL" }
+                )?;
+                let sql_type = if column.nullable {
+                    format!("Nullable<{}>", column.sql_type_marker())
+                } else {
+                    column.sql_type_marker().to_string()
+                };
+                // Generic over the source so that outer joined tables give nullable columns.
+                writeln!(
+                    out,
+                    "    pub fn {}<S>(source: &QuerySourceRef<S>) -> Column<NullableIf<S, {}>>",
+                    ident, sql_type
+                )?;
+                writeln!(out, "    where")?;
+                writeln!(
+                    out,
+                    "        S: QuerySource<Database = super::{}> + SourceNullability + OfTable<Marker>,",
+                    database
+                )?;
+                writeln!(
+                    out,
+                    "        S::Nullability: ApplyNullability<{}>,",
+                    sql_type
+                )?;
+                writeln!(out, "    {{")?;
+                writeln!(
+                    out,
+                    "        source.typed_column::<{}>({:?})",
+                    sql_type, column.name
+                )?;
+                writeln!(out, "    }}")?;
+            }
+            writeln!(out, "}}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Read a schema dump and generate the Rust source.
+pub fn generate_from_file(
+    path: &Path,
+    format: Option<SchemaFormat>,
+    database: &str,
+) -> Result<String, CodegenError> {
+    let format = match format {
+        Some(format) => format,
+        None => SchemaFormat::from_path(path)?,
+    };
+    let input = std::fs::read_to_string(path)?;
+    Schema::parse(&input, format)?.generate(database)
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where", "while",
+    "abstract", "become", "box", "do", "final", "macro", "override", "priv", "try", "typeof",
+    "unsized", "virtual", "yield",
+];
+
+/// Identifier of the column accessor, leaving `table()` to the constructor of the source.
+fn column_ident(name: &str) -> String {
+    let mut ident = rust_ident(name);
+    if ident == "table" {
+        ident.push('_');
+    }
+    ident
+}
+
+/// Reject `names` of which two have the same Rust identifier.
+fn check_unique<'a>(
+    kind: &str,
+    names: impl Iterator<Item = (&'a str, String)>,
+) -> Result<(), CodegenError> {
+    let mut seen: Vec<(&str, String)> = Vec::new();
+    for (name, ident) in names {
+        if let Some((other, _)) = seen.iter().find(|(_, seen)| *seen == ident) {
+            return Err(CodegenError::NameCollision(format!(
+                "{} `{}` and `{}` are both generated as `{}`",
+                kind, other, name, ident
+            )));
+        }
+        seen.push((name, ident));
+    }
+    Ok(())
+}
+
+/// Turn a SQL name into a snake case Rust identifier.
+fn rust_ident(name: &str) -> String {
+    let mut ident = String::with_capacity(name.len() + 2);
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            ident.push(c.to_ascii_lowercase());
+        } else if !ident.ends_with('_') {
+            ident.push('_');
+        }
+    }
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    match ident.as_str() {
+        "self" | "super" | "crate" | "Self" => ident.push('_'),
+        _ if RUST_KEYWORDS.contains(&ident.as_str()) => ident.insert_str(0, "r#"),
+        _ => {}
+    }
+    ident
+}
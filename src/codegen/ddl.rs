@@ -0,0 +1,404 @@
+//! `CREATE TABLE` DDL parser.
+//!
+//! Reads only what the generator needs: table names, column names, types and nullability.
+//! Other statements are skipped.
+//!
+
+use super::{CodegenError, ColumnSchema, Schema, TableSchema};
+
+/// Items in a column list which are not column definitions.
+const TABLE_CONSTRAINTS: &[&str] = &[
+    "PRIMARY",
+    "KEY",
+    "INDEX",
+    "UNIQUE",
+    "CONSTRAINT",
+    "FOREIGN",
+    "FULLTEXT",
+    "SPATIAL",
+    "CHECK",
+    "EXCLUDE",
+    "PERIOD",
+];
+
+/// Keywords which end the type of a column definition.
+///
+/// `CHARACTER` only does so in `CHARACTER SET`, as it starts types like `character varying(255)`.
+const COLUMN_OPTIONS: &[&str] = &[
+    "NOT",
+    "NULL",
+    "DEFAULT",
+    "PRIMARY",
+    "UNIQUE",
+    "KEY",
+    "AUTO_INCREMENT",
+    "AUTOINCREMENT",
+    "COMMENT",
+    "REFERENCES",
+    "CHECK",
+    "COLLATE",
+    "CHARACTER",
+    "CHARSET",
+    "GENERATED",
+    "AS",
+    "ON",
+    "CONSTRAINT",
+    "VISIBLE",
+    "INVISIBLE",
+];
+
+pub(super) fn parse(input: &str) -> Result<Schema, CodegenError> {
+    let input = strip_comments(input, is_mysql_dump(input));
+    let upper = input.to_ascii_uppercase();
+    let mut schema = Schema::default();
+    let mut pos = 0;
+
+    while let Some(found) = find_create_table(&upper, pos) {
+        let line = line_of(&input, found.start);
+        let mut cursor = skip_keywords(&upper, found.end, &["IF", "NOT", "EXISTS"]);
+        let (name, after_name) = read_name(&input, cursor)
+            .ok_or_else(|| CodegenError::parse(line, "missing table name"))?;
+        cursor = skip_whitespace(&input, after_name);
+        if !input[cursor..].starts_with('(') {
+            // e.g. CREATE TABLE ... AS SELECT / LIKE
+            pos = cursor;
+            continue;
+        }
+        let body_end = matching_paren(&input, cursor)
+            .ok_or_else(|| CodegenError::parse(line, format!("unclosed `(` of {}", name)))?;
+        let body = &input[cursor + 1..body_end];
+        schema.tables.push(parse_table(name, body, line)?);
+        pos = body_end + 1;
+    }
+
+    Ok(schema)
+}
+
+fn parse_table(name: String, body: &str, line: usize) -> Result<TableSchema, CodegenError> {
+    let mut columns = Vec::new();
+    let mut primary_key = Vec::new();
+
+    for item in split_top_level(body, ',') {
+        let tokens = tokenize(item);
+        let first = match tokens.first() {
+            Some(first) => first,
+            None => continue,
+        };
+        let keyword = first.to_ascii_uppercase();
+        if TABLE_CONSTRAINTS.contains(&keyword.as_str()) {
+            if let Some(columns) = primary_key_columns(&tokens) {
+                primary_key.extend(columns);
+            }
+            continue;
+        }
+
+        let type_len = tokens[1..]
+            .iter()
+            .enumerate()
+            .position(|(i, token)| is_column_option(token, tokens.get(i + 2)))
+            .unwrap_or(tokens.len() - 1);
+        let column_type: Vec<&str> = tokens[1..1 + type_len].iter().map(String::as_str).collect();
+        if column_type.is_empty() {
+            return Err(CodegenError::parse(
+                line,
+                format!("missing type of {}.{}", name, first),
+            ));
+        }
+        let options = tokens[1 + column_type.len()..]
+            .join(" ")
+            .to_ascii_uppercase();
+        columns.push(ColumnSchema {
+            name: first.clone(),
+            column_type: column_type.join(" "),
+            nullable: !options.contains("NOT NULL") && !options.contains("PRIMARY KEY"),
+        });
+    }
+
+    for column in &mut columns {
+        if primary_key.contains(&column.name) {
+            column.nullable = false;
+        }
+    }
+
+    Ok(TableSchema { name, columns })
+}
+
+fn is_column_option(token: &str, next: Option<&String>) -> bool {
+    let keyword = token.to_ascii_uppercase();
+    match keyword.as_str() {
+        "CHARACTER" => next.is_some_and(|next| next.eq_ignore_ascii_case("SET")),
+        keyword => COLUMN_OPTIONS.contains(&keyword),
+    }
+}
+
+/// Columns of `PRIMARY KEY (a, b)` or `CONSTRAINT pk PRIMARY KEY (a, b)`.
+fn primary_key_columns(tokens: &[String]) -> Option<Vec<String>> {
+    let position = tokens.windows(2).position(|w| {
+        w[0].eq_ignore_ascii_case("PRIMARY") && w[1].to_ascii_uppercase().starts_with("KEY")
+    })?;
+    let key = &tokens[position + 1];
+    let list = match key.find('(') {
+        Some(start) => &key[start..],
+        None => tokens.get(position + 2)?.as_str(),
+    };
+    let list = list.trim_start_matches('(').trim_end_matches(')');
+    Some(
+        split_top_level(list, ',')
+            .into_iter()
+            .filter_map(|c| tokenize(c).into_iter().next())
+            .collect(),
+    )
+}
+
+struct Found {
+    start: usize,
+    end: usize,
+}
+
+/// Find the first `CREATE [TEMPORARY | TEMP | UNLOGGED] TABLE` from `pos`.
+fn find_create_table(upper: &str, pos: usize) -> Option<Found> {
+    let forms: [&[&str]; 4] = [
+        &["CREATE", "TABLE"],
+        &["CREATE", "TEMPORARY", "TABLE"],
+        &["CREATE", "TEMP", "TABLE"],
+        &["CREATE", "UNLOGGED", "TABLE"],
+    ];
+    forms
+        .iter()
+        .filter_map(|keywords| find_keywords(upper, pos, keywords))
+        .min_by_key(|found| found.start)
+}
+
+/// Find `keywords` separated by whitespace, starting from `pos`.
+fn find_keywords(upper: &str, mut pos: usize, keywords: &[&str]) -> Option<Found> {
+    loop {
+        let start = pos + upper[pos..].find(keywords[0])?;
+        if is_word_boundary(upper, start, keywords[0].len()) {
+            let mut end = start + keywords[0].len();
+            let matched = keywords[1..].iter().all(|keyword| {
+                let next = skip_whitespace(upper, end);
+                let ok = upper[next..].starts_with(keyword)
+                    && next > end
+                    && is_word_boundary(upper, next, keyword.len());
+                if ok {
+                    end = next + keyword.len();
+                }
+                ok
+            });
+            if matched {
+                return Some(Found { start, end });
+            }
+        }
+        pos = start + keywords[0].len();
+    }
+}
+
+fn skip_keywords(upper: &str, pos: usize, keywords: &[&str]) -> usize {
+    let mut end = pos;
+    for keyword in keywords {
+        let next = skip_whitespace(upper, end);
+        if !(upper[next..].starts_with(keyword) && is_word_boundary(upper, next, keyword.len())) {
+            return pos;
+        }
+        end = next + keyword.len();
+    }
+    end
+}
+
+fn is_word_boundary(s: &str, start: usize, len: usize) -> bool {
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let before = !s[..start].chars().next_back().is_some_and(is_word);
+    let after = !s[start + len..].chars().next().is_some_and(is_word);
+    before && after
+}
+
+fn skip_whitespace(s: &str, pos: usize) -> usize {
+    pos + s[pos..].len() - s[pos..].trim_start().len()
+}
+
+fn line_of(s: &str, pos: usize) -> usize {
+    s[..pos].matches('\n').count() + 1
+}
+
+/// Read a possibly qualified and quoted name, returning the last part.
+fn read_name(s: &str, pos: usize) -> Option<(String, usize)> {
+    let mut pos = skip_whitespace(s, pos);
+    let mut name = None;
+    loop {
+        let rest = &s[pos..];
+        let (part, len) = match rest.chars().next()? {
+            q @ '`' | q @ '"' | q @ '[' => {
+                let close = if q == '[' { ']' } else { q };
+                let end = rest[1..].find(close)?;
+                (rest[1..=end].to_string(), end + 2)
+            }
+            _ => {
+                let end = rest
+                    .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+                    .unwrap_or(rest.len());
+                if end == 0 {
+                    return name.map(|name| (name, pos));
+                }
+                (rest[..end].to_string(), end)
+            }
+        };
+        name = Some(part);
+        pos += len;
+        if !s[pos..].starts_with('.') {
+            return name.map(|name| (name, pos));
+        }
+        pos += 1;
+    }
+}
+
+fn matching_paren(s: &str, open: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut quote = None;
+    for (i, c) in s[open..].char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'') | (None, '"') | (None, '`') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(open + i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Split by `separator` outside of parentheses and quotes.
+fn split_top_level(s: &str, separator: char) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut depth = 0;
+    let mut quote = None;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'') | (None, '"') | (None, '`') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => depth -= 1,
+            (None, c) if c == separator && depth == 0 => {
+                items.push(&s[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    items.push(&s[start..]);
+    items
+}
+
+/// Split into words. Quoted identifiers are unquoted and parenthesized groups stick to the previous word.
+fn tokenize(s: &str) -> Vec<String> {
+    let mut tokens: Vec<String> = Vec::new();
+    let mut chars = s.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '`' | '"' | '[' => {
+                let close = if c == '[' { ']' } else { c };
+                let mut ident = String::new();
+                for (_, c) in chars.by_ref() {
+                    if c == close {
+                        break;
+                    }
+                    ident.push(c);
+                }
+                tokens.push(ident);
+            }
+            '\'' => {
+                let mut literal = String::from("'");
+                for (_, c) in chars.by_ref() {
+                    literal.push(c);
+                    if c == '\'' {
+                        break;
+                    }
+                }
+                tokens.push(literal);
+            }
+            '(' => {
+                let end = matching_paren(s, i).unwrap_or(s.len() - 1);
+                let group = &s[i..=end];
+                match tokens.last_mut() {
+                    Some(last) => last.push_str(group),
+                    None => tokens.push(group.to_string()),
+                }
+                while chars.peek().is_some_and(|(j, _)| *j <= end) {
+                    chars.next();
+                }
+            }
+            _ => {
+                let mut word = c.to_string();
+                while let Some((_, c)) = chars.peek() {
+                    if c.is_whitespace() || *c == '(' {
+                        break;
+                    }
+                    word.push(*c);
+                    chars.next();
+                }
+                tokens.push(word);
+            }
+        }
+    }
+    tokens
+}
+
+/// Whether the dump is MySQL's, which quotes names with backticks and sets `ENGINE=`.
+fn is_mysql_dump(input: &str) -> bool {
+    input.contains('`') || input.to_ascii_uppercase().contains("ENGINE=")
+}
+
+/// Replace comments with spaces, keeping line numbers.
+///
+/// `#` starts a comment only in MySQL dumps, as it is an operator in Postgres, e.g. `#>>`.
+fn strip_comments(input: &str, mysql: bool) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    let mut quote = None;
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) => {
+                if c == q {
+                    quote = None;
+                }
+                out.push(c);
+            }
+            (None, '\'') | (None, '"') | (None, '`') => {
+                quote = Some(c);
+                out.push(c);
+            }
+            (None, '-') if chars.peek() == Some(&'-') => skip_line(&mut chars, &mut out),
+            (None, '#') if mysql => skip_line(&mut chars, &mut out),
+            (None, '/') if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = ' ';
+                for c in chars.by_ref() {
+                    out.push(if c == '\n' { '\n' } else { ' ' });
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            (None, c) => out.push(c),
+        }
+    }
+    out
+}
+
+fn skip_line(chars: &mut std::iter::Peekable<std::str::Chars<'_>>, out: &mut String) {
+    for c in chars.by_ref() {
+        if c == '\n' {
+            out.push('\n');
+            break;
+        }
+    }
+}
@@ -0,0 +1,273 @@
+//! `information_schema.COLUMNS` exports.
+//!
+//! e.g. `SELECT TABLE_NAME, COLUMN_NAME, COLUMN_TYPE, IS_NULLABLE, ORDINAL_POSITION FROM information_schema.COLUMNS WHERE TABLE_SCHEMA = 'db'`
+//! saved as CSV with a header line, or as a JSON array of objects keyed by the column names.
+//!
+
+use super::{CodegenError, ColumnSchema, Schema, TableSchema};
+use std::collections::HashMap;
+
+/// A row of `information_schema.COLUMNS`, keyed by the upper case column name.
+type Record = HashMap<String, String>;
+
+pub(super) fn parse_csv(input: &str) -> Result<Schema, CodegenError> {
+    let mut rows = parse_csv_rows(input)?.into_iter();
+    let header: Vec<String> = match rows.next() {
+        Some((_, header)) => header
+            .iter()
+            .map(|h| h.trim().to_ascii_uppercase())
+            .collect(),
+        None => return Ok(Schema::default()),
+    };
+    let records = rows
+        .filter(|(_, fields)| !(fields.len() == 1 && fields[0].is_empty()))
+        .map(|(line, fields)| {
+            if fields.len() != header.len() {
+                return Err(CodegenError::parse(
+                    line,
+                    format!("expected {} fields, found {}", header.len(), fields.len()),
+                ));
+            }
+            Ok((line, header.iter().cloned().zip(fields).collect()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    into_schema(records)
+}
+
+pub(super) fn parse_json(input: &str) -> Result<Schema, CodegenError> {
+    let mut parser = JsonParser { input, pos: 0 };
+    let records = parser.parse_records()?;
+    into_schema(records)
+}
+
+/// Group records by table in order of appearance, ordering columns by `ORDINAL_POSITION` if present.
+fn into_schema(records: Vec<(usize, Record)>) -> Result<Schema, CodegenError> {
+    let mut schema = Schema::default();
+    let mut positions: Vec<Vec<u64>> = Vec::new();
+
+    for (line, record) in records {
+        let get = |key: &str| record.get(key).map(String::as_str);
+        let required = |key: &str| {
+            get(key).ok_or_else(|| CodegenError::parse(line, format!("missing {}", key)))
+        };
+        let table_name = required("TABLE_NAME")?;
+        let column = ColumnSchema {
+            name: required("COLUMN_NAME")?.to_string(),
+            column_type: get("COLUMN_TYPE")
+                .or_else(|| get("DATA_TYPE"))
+                .ok_or_else(|| CodegenError::parse(line, "missing COLUMN_TYPE or DATA_TYPE"))?
+                .to_string(),
+            nullable: !required("IS_NULLABLE")?.eq_ignore_ascii_case("NO"),
+        };
+        let position = match get("ORDINAL_POSITION") {
+            Some(p) => p.trim().parse().map_err(|_| {
+                CodegenError::parse(line, format!("invalid ORDINAL_POSITION: {}", p))
+            })?,
+            None => u64::MAX,
+        };
+
+        let index = match schema.tables.iter().position(|t| t.name == table_name) {
+            Some(index) => index,
+            None => {
+                schema.tables.push(TableSchema {
+                    name: table_name.to_string(),
+                    columns: Vec::new(),
+                });
+                positions.push(Vec::new());
+                schema.tables.len() - 1
+            }
+        };
+        // Stable insertion keeps the input order for equal positions.
+        let at = positions[index].partition_point(|p| *p <= position);
+        positions[index].insert(at, position);
+        schema.tables[index].columns.insert(at, column);
+    }
+
+    Ok(schema)
+}
+
+/// RFC 4180 style CSV. Returns the starting line number and fields of each row.
+fn parse_csv_rows(input: &str) -> Result<Vec<(usize, Vec<String>)>, CodegenError> {
+    let mut rows = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut line = 1;
+    let mut row_line = 1;
+    let mut quoted = false;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, c) => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+            (false, '"') => quoted = true,
+            (false, ',') => fields.push(std::mem::take(&mut field)),
+            (false, '\r') => {}
+            (false, '\n') => {
+                fields.push(std::mem::take(&mut field));
+                rows.push((row_line, std::mem::take(&mut fields)));
+                line += 1;
+                row_line = line;
+            }
+            (false, c) => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(CodegenError::parse(row_line, "unclosed quote"));
+    }
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        rows.push((row_line, fields));
+    }
+    Ok(rows)
+}
+
+/// Just enough JSON to read an array of flat objects.
+struct JsonParser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    fn parse_records(&mut self) -> Result<Vec<(usize, Record)>, CodegenError> {
+        let mut records = Vec::new();
+        self.expect('[')?;
+        if self.eat(']') {
+            return Ok(records);
+        }
+        loop {
+            let line = self.line();
+            records.push((line, self.parse_object()?));
+            if self.eat(']') {
+                return Ok(records);
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<Record, CodegenError> {
+        let mut record = Record::new();
+        self.expect('{')?;
+        if self.eat('}') {
+            return Ok(record);
+        }
+        loop {
+            let key = self.parse_string()?;
+            self.expect(':')?;
+            if let Some(value) = self.parse_scalar()? {
+                record.insert(key.to_ascii_uppercase(), value);
+            }
+            if self.eat('}') {
+                return Ok(record);
+            }
+            self.expect(',')?;
+        }
+    }
+
+    /// Strings, numbers and booleans as text. `null` is `None`.
+    fn parse_scalar(&mut self) -> Result<Option<String>, CodegenError> {
+        self.skip_whitespace();
+        if self.rest().starts_with('"') {
+            return self.parse_string().map(Some);
+        }
+        let end = self
+            .rest()
+            .find(|c: char| c == ',' || c == '}' || c == ']' || c.is_whitespace())
+            .unwrap_or_else(|| self.rest().len());
+        let literal = &self.rest()[..end];
+        match literal {
+            "null" => {
+                self.pos += end;
+                Ok(None)
+            }
+            "true" | "false" => {
+                self.pos += end;
+                Ok(Some(literal.to_string()))
+            }
+            _ if !literal.is_empty() && literal.parse::<f64>().is_ok() => {
+                self.pos += end;
+                Ok(Some(literal.to_string()))
+            }
+            _ => Err(self.error("expected a string, number, boolean or null")),
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, CodegenError> {
+        self.expect('"')?;
+        let mut string = String::new();
+        let mut chars = self.rest().char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos += i + 1;
+                    return Ok(string);
+                }
+                '\\' => {
+                    let escaped = match chars.next() {
+                        Some((_, 'n')) => '\n',
+                        Some((_, 't')) => '\t',
+                        Some((_, 'r')) => '\r',
+                        Some((_, 'b')) => '\u{8}',
+                        Some((_, 'f')) => '\u{c}',
+                        Some((_, 'u')) => {
+                            let hex: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+                            u32::from_str_radix(&hex, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .unwrap_or(char::REPLACEMENT_CHARACTER)
+                        }
+                        Some((_, c)) => c,
+                        None => break,
+                    };
+                    string.push(escaped);
+                }
+                c => string.push(c),
+            }
+        }
+        Err(self.error("unclosed string"))
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), CodegenError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", c)))
+        }
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn line(&self) -> usize {
+        self.input[..self.pos].matches('\n').count() + 1
+    }
+
+    fn error(&self, message: &str) -> CodegenError {
+        CodegenError::parse(self.line(), message)
+    }
+}
@@ -0,0 +1,625 @@
+//! DDL Builder
+//!
+//! `CREATE TABLE`, `CREATE INDEX`, `ALTER TABLE` and `DROP TABLE` rendered per dialect.
+//! Column types come from the same `SqlType*` markers as expressions, and tables from `TableName<DB>`.
+//!
+//! ```ignore
+//! let users = TableName::<Shop>::new("users");
+//! let query = CreateTable::new(users.clone())
+//!     .if_not_exists()
+//!     .column(ColumnDef::new::<SqlTypeUint>("id").not_null().auto_increment().primary_key())
+//!     .column(ColumnDef::new::<SqlTypeString>("name").not_null().default("anonymous"))
+//!     .build_with(BuildOptions::new().dialect(Postgres))?;
+//! ```
+//!
+//! DDL cannot take bound parameters, so defaults are inlined as literals escaped for the dialect.
+//!
+
+use crate::dialect::DialectKind;
+use crate::query_builder::{
+    AnyDatabase, BuildContext, BuildOptions, BuildQuery, BuildSqlError, Joinable, Query,
+    QueryBuildError, SqlTypeBool, SqlTypeInt, SqlTypeJson, SqlTypeString, SqlTypeUint, TableName,
+    Value,
+};
+use std::io::Write;
+
+/// Type of a column in DDL.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum ColumnType<'a> {
+    String,
+    Int,
+    /// `BIGINT UNSIGNED` on MySQL. Postgres and SQLite have no unsigned integers and use their signed type.
+    Uint,
+    Bool,
+    /// `JSON` on MySQL, `JSONB` on Postgres and `TEXT` on SQLite.
+    Json,
+    /// Written as is, for types without a `SqlType*` marker, e.g. `TIMESTAMP`.
+    Raw(&'a str),
+}
+
+impl<'a> ColumnType<'a> {
+    pub fn name(self, dialect: DialectKind) -> &'a str {
+        match (self, dialect) {
+            // MySQL cannot index TEXT without a prefix length.
+            (ColumnType::String, DialectKind::MySql) => "VARCHAR(255)",
+            (ColumnType::String, _) => "TEXT",
+            (ColumnType::Int, DialectKind::Sqlite) | (ColumnType::Uint, DialectKind::Sqlite) => {
+                "INTEGER"
+            }
+            (ColumnType::Int, _) => "BIGINT",
+            (ColumnType::Uint, DialectKind::MySql) => "BIGINT UNSIGNED",
+            (ColumnType::Uint, _) => "BIGINT",
+            (ColumnType::Bool, DialectKind::Sqlite) => "INTEGER",
+            (ColumnType::Bool, _) => "BOOLEAN",
+            (ColumnType::Json, DialectKind::MySql) => "JSON",
+            (ColumnType::Json, DialectKind::Postgres) => "JSONB",
+            (ColumnType::Json, DialectKind::Sqlite) => "TEXT",
+            (ColumnType::Raw(name), _) => name,
+        }
+    }
+}
+
+/// `SqlType*` markers which have a column type.
+///
+/// `SqlTypeAny` has none; use `ColumnDef::raw` for such columns.
+pub trait SqlColumnType {
+    const COLUMN_TYPE: ColumnType<'static>;
+}
+
+impl SqlColumnType for SqlTypeString {
+    const COLUMN_TYPE: ColumnType<'static> = ColumnType::String;
+}
+
+impl SqlColumnType for SqlTypeInt {
+    const COLUMN_TYPE: ColumnType<'static> = ColumnType::Int;
+}
+
+impl SqlColumnType for SqlTypeUint {
+    const COLUMN_TYPE: ColumnType<'static> = ColumnType::Uint;
+}
+
+impl SqlColumnType for SqlTypeBool {
+    const COLUMN_TYPE: ColumnType<'static> = ColumnType::Bool;
+}
+
+impl SqlColumnType for SqlTypeJson {
+    const COLUMN_TYPE: ColumnType<'static> = ColumnType::Json;
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+enum DefaultValue<'a> {
+    Literal(Value),
+    Raw(&'a str),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ColumnDef<'a> {
+    name: &'a str,
+    column_type: ColumnType<'a>,
+    not_null: bool,
+    default: Option<DefaultValue<'a>>,
+    primary_key: bool,
+    unique: bool,
+    auto_increment: bool,
+}
+
+impl<'a> ColumnDef<'a> {
+    /// e.g. `ColumnDef::new::<SqlTypeInt>("id")`
+    pub fn new<ST: SqlColumnType>(name: &'a str) -> ColumnDef<'a> {
+        ColumnDef::with_type(name, ST::COLUMN_TYPE)
+    }
+
+    /// e.g. `ColumnDef::raw("created_at", "TIMESTAMP")`
+    pub fn raw(name: &'a str, column_type: &'a str) -> ColumnDef<'a> {
+        ColumnDef::with_type(name, ColumnType::Raw(column_type))
+    }
+
+    pub fn with_type(name: &'a str, column_type: ColumnType<'a>) -> ColumnDef<'a> {
+        ColumnDef {
+            name,
+            column_type,
+            not_null: false,
+            default: None,
+            primary_key: false,
+            unique: false,
+            auto_increment: false,
+        }
+    }
+
+    pub fn not_null(self) -> Self {
+        ColumnDef {
+            not_null: true,
+            ..self
+        }
+    }
+
+    /// Default value inlined as a literal.
+    pub fn default(self, value: impl Into<Value>) -> Self {
+        ColumnDef {
+            default: Some(DefaultValue::Literal(value.into())),
+            ..self
+        }
+    }
+
+    /// Default expression written as is, e.g. `CURRENT_TIMESTAMP`.
+    pub fn default_raw(self, expr: &'a str) -> Self {
+        ColumnDef {
+            default: Some(DefaultValue::Raw(expr)),
+            ..self
+        }
+    }
+
+    pub fn primary_key(self) -> Self {
+        ColumnDef {
+            primary_key: true,
+            ..self
+        }
+    }
+
+    pub fn unique(self) -> Self {
+        ColumnDef {
+            unique: true,
+            ..self
+        }
+    }
+
+    /// `AUTO_INCREMENT` on MySQL, an identity column on Postgres and `AUTOINCREMENT` on SQLite.
+    ///
+    /// SQLite allows it only on the primary key column.
+    pub fn auto_increment(self) -> Self {
+        ColumnDef {
+            auto_increment: true,
+            ..self
+        }
+    }
+
+    fn write(&self, dialect: DialectKind, buf: &mut Vec<u8>) -> Result<(), BuildSqlError> {
+        write!(buf, "{} {}", self.name, self.column_type.name(dialect))?;
+        if self.not_null {
+            write!(buf, " NOT NULL")?;
+        }
+        match &self.default {
+            Some(DefaultValue::Literal(value)) => {
+                let mut literal = String::new();
+                dialect
+                    .write_literal(&mut literal, value)
+                    .map_err(anyhow::Error::from)?;
+                write!(buf, " DEFAULT {}", literal)?;
+            }
+            Some(DefaultValue::Raw(expr)) => write!(buf, " DEFAULT {}", expr)?,
+            None => {}
+        }
+        if self.auto_increment {
+            match dialect {
+                DialectKind::MySql => write!(buf, " AUTO_INCREMENT")?,
+                DialectKind::Postgres => write!(buf, " GENERATED BY DEFAULT AS IDENTITY")?,
+                DialectKind::Sqlite if !self.primary_key => {
+                    return Err(anyhow::anyhow!(
+                        "SQLite allows AUTOINCREMENT only on the primary key, but `{}` is not",
+                        self.name
+                    )
+                    .into());
+                }
+                DialectKind::Sqlite => {}
+            }
+        }
+        if self.unique {
+            write!(buf, " UNIQUE")?;
+        }
+        if self.primary_key {
+            write!(buf, " PRIMARY KEY")?;
+            if self.auto_increment && dialect == DialectKind::Sqlite {
+                write!(buf, " AUTOINCREMENT")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Action of `ON DELETE` and `ON UPDATE`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum ReferentialAction {
+    Cascade,
+    SetNull,
+    SetDefault,
+    Restrict,
+    NoAction,
+}
+
+impl ReferentialAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            ReferentialAction::Cascade => "CASCADE",
+            ReferentialAction::SetNull => "SET NULL",
+            ReferentialAction::SetDefault => "SET DEFAULT",
+            ReferentialAction::Restrict => "RESTRICT",
+            ReferentialAction::NoAction => "NO ACTION",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ForeignKey<'a, DB> {
+    columns: Vec<&'a str>,
+    table: TableName<'a, DB>,
+    references: Vec<&'a str>,
+    on_delete: Option<ReferentialAction>,
+    on_update: Option<ReferentialAction>,
+}
+
+impl<'a, DB> ForeignKey<'a, DB> {
+    /// `FOREIGN KEY (columns) REFERENCES table (references)`
+    pub fn new(columns: &[&'a str], table: TableName<'a, DB>, references: &[&'a str]) -> Self {
+        ForeignKey {
+            columns: columns.to_vec(),
+            table,
+            references: references.to_vec(),
+            on_delete: None,
+            on_update: None,
+        }
+    }
+
+    pub fn on_delete(self, action: ReferentialAction) -> Self {
+        ForeignKey {
+            on_delete: Some(action),
+            ..self
+        }
+    }
+
+    pub fn on_update(self, action: ReferentialAction) -> Self {
+        ForeignKey {
+            on_update: Some(action),
+            ..self
+        }
+    }
+
+    fn into_any_database(self) -> ForeignKey<'a, AnyDatabase> {
+        ForeignKey {
+            columns: self.columns,
+            table: TableName::new(self.table.name()),
+            references: self.references,
+            on_delete: self.on_delete,
+            on_update: self.on_update,
+        }
+    }
+
+    fn write(&self, buf: &mut Vec<u8>) -> Result<(), BuildSqlError> {
+        write!(
+            buf,
+            "FOREIGN KEY ({}) REFERENCES {} ({})",
+            self.columns.join(", "),
+            self.table.name(),
+            self.references.join(", ")
+        )?;
+        if let Some(action) = self.on_delete {
+            write!(buf, " ON DELETE {}", action.as_str())?;
+        }
+        if let Some(action) = self.on_update {
+            write!(buf, " ON UPDATE {}", action.as_str())?;
+        }
+        Ok(())
+    }
+}
+
+/// DDL statements. Built with `build()` or `build_with(options)` like `SelectBuilder`.
+pub trait DdlStatement {
+    fn write_statement(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), BuildSqlError>;
+
+    fn build(&self) -> Result<Query, QueryBuildError> {
+        self.build_with(BuildOptions::default())
+    }
+
+    fn build_with(&self, options: BuildOptions) -> Result<Query, QueryBuildError> {
+        let mut ctx = BuildContext::new(options);
+        Query::build(|buf, _params| {
+            self.write_statement(&mut ctx, buf)?;
+            write!(buf, ";")?;
+            Ok(())
+        })
+    }
+}
+
+impl<T: DdlStatement> BuildQuery for T {
+    fn build_query(self, options: BuildOptions) -> Result<Query, QueryBuildError> {
+        self.build_with(options)
+    }
+}
+
+/// Write `items` separated by commas inside parentheses, one per line in pretty format.
+fn write_definitions<T, F>(
+    ctx: &mut BuildContext,
+    buf: &mut Vec<u8>,
+    items: &[T],
+    mut f: F,
+) -> Result<(), BuildSqlError>
+where
+    F: FnMut(&T, &mut Vec<u8>) -> Result<(), BuildSqlError>,
+{
+    ctx.write_subquery(buf, |ctx, buf| {
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                write!(buf, ",")?;
+                ctx.write_clause_separator(buf)?;
+            }
+            f(item, buf)?;
+        }
+        Ok(())
+    })
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+enum TableConstraint<'a> {
+    PrimaryKey(Vec<&'a str>),
+    Unique(Option<&'a str>, Vec<&'a str>),
+    ForeignKey(ForeignKey<'a, AnyDatabase>),
+}
+
+impl TableConstraint<'_> {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<(), BuildSqlError> {
+        match self {
+            TableConstraint::PrimaryKey(columns) => {
+                write!(buf, "PRIMARY KEY ({})", columns.join(", "))?
+            }
+            TableConstraint::Unique(Some(name), columns) => {
+                write!(buf, "CONSTRAINT {} UNIQUE ({})", name, columns.join(", "))?
+            }
+            TableConstraint::Unique(None, columns) => {
+                write!(buf, "UNIQUE ({})", columns.join(", "))?
+            }
+            TableConstraint::ForeignKey(foreign_key) => foreign_key.write(buf)?,
+        }
+        Ok(())
+    }
+}
+
+enum TableDefinition<'b, 'a> {
+    Column(&'b ColumnDef<'a>),
+    Constraint(&'b TableConstraint<'a>),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct CreateTable<'a, DB> {
+    table: TableName<'a, DB>,
+    if_not_exists: bool,
+    columns: Vec<ColumnDef<'a>>,
+    constraints: Vec<TableConstraint<'a>>,
+}
+
+impl<'a, DB> CreateTable<'a, DB> {
+    pub fn new(table: TableName<'a, DB>) -> Self {
+        CreateTable {
+            table,
+            if_not_exists: false,
+            columns: Vec::new(),
+            constraints: Vec::new(),
+        }
+    }
+
+    pub fn if_not_exists(self) -> Self {
+        CreateTable {
+            if_not_exists: true,
+            ..self
+        }
+    }
+
+    pub fn column(mut self, column: ColumnDef<'a>) -> Self {
+        self.columns.push(column);
+        self
+    }
+
+    /// Table level primary key, e.g. for a composite key.
+    pub fn primary_key(mut self, columns: &[&'a str]) -> Self {
+        self.constraints
+            .push(TableConstraint::PrimaryKey(columns.to_vec()));
+        self
+    }
+
+    /// Unique index over `columns`. Other indexes are created with `CreateIndex`.
+    pub fn unique(mut self, name: &'a str, columns: &[&'a str]) -> Self {
+        self.constraints
+            .push(TableConstraint::Unique(Some(name), columns.to_vec()));
+        self
+    }
+
+    /// Foreign key to a table of a joinable database.
+    pub fn foreign_key<DB2>(mut self, foreign_key: ForeignKey<'a, DB2>) -> Self
+    where
+        DB: Joinable<DB2>,
+    {
+        self.constraints
+            .push(TableConstraint::ForeignKey(foreign_key.into_any_database()));
+        self
+    }
+}
+
+impl<DB> DdlStatement for CreateTable<'_, DB> {
+    fn write_statement(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), BuildSqlError> {
+        if self.columns.is_empty() {
+            return Err(
+                anyhow::anyhow!("CREATE TABLE {} has no columns", self.table.name()).into(),
+            );
+        }
+        write!(buf, "CREATE TABLE ")?;
+        if self.if_not_exists {
+            write!(buf, "IF NOT EXISTS ")?;
+        }
+        write!(buf, "{} ", self.table.name())?;
+
+        let dialect = ctx.dialect();
+        let definitions: Vec<_> = self
+            .columns
+            .iter()
+            .map(TableDefinition::Column)
+            .chain(self.constraints.iter().map(TableDefinition::Constraint))
+            .collect();
+        write_definitions(ctx, buf, &definitions, |definition, buf| match definition {
+            TableDefinition::Column(column) => column.write(dialect, buf),
+            TableDefinition::Constraint(constraint) => constraint.write(buf),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct CreateIndex<'a, DB> {
+    name: &'a str,
+    table: TableName<'a, DB>,
+    columns: Vec<&'a str>,
+    unique: bool,
+}
+
+impl<'a, DB> CreateIndex<'a, DB> {
+    /// `CREATE INDEX name ON table (columns)`
+    pub fn new(name: &'a str, table: TableName<'a, DB>, columns: &[&'a str]) -> Self {
+        CreateIndex {
+            name,
+            table,
+            columns: columns.to_vec(),
+            unique: false,
+        }
+    }
+
+    pub fn unique(self) -> Self {
+        CreateIndex {
+            unique: true,
+            ..self
+        }
+    }
+}
+
+impl<DB> DdlStatement for CreateIndex<'_, DB> {
+    fn write_statement(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), BuildSqlError> {
+        write!(
+            buf,
+            "CREATE {}INDEX {} ON {} ",
+            if self.unique { "UNIQUE " } else { "" },
+            self.name,
+            self.table.name()
+        )?;
+        write_definitions(ctx, buf, &self.columns, |column, buf| {
+            write!(buf, "{}", column)?;
+            Ok(())
+        })
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+enum AlterAction<'a> {
+    AddColumn(ColumnDef<'a>),
+    DropColumn(&'a str),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct AlterTable<'a, DB> {
+    table: TableName<'a, DB>,
+    actions: Vec<AlterAction<'a>>,
+}
+
+impl<'a, DB> AlterTable<'a, DB> {
+    pub fn new(table: TableName<'a, DB>) -> Self {
+        AlterTable {
+            table,
+            actions: Vec::new(),
+        }
+    }
+
+    pub fn add_column(mut self, column: ColumnDef<'a>) -> Self {
+        self.actions.push(AlterAction::AddColumn(column));
+        self
+    }
+
+    pub fn drop_column(mut self, column: &'a str) -> Self {
+        self.actions.push(AlterAction::DropColumn(column));
+        self
+    }
+}
+
+impl<DB> DdlStatement for AlterTable<'_, DB> {
+    fn write_statement(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), BuildSqlError> {
+        let dialect = ctx.dialect();
+        match self.actions.len() {
+            0 => {
+                return Err(
+                    anyhow::anyhow!("ALTER TABLE {} has no actions", self.table.name()).into(),
+                )
+            }
+            1 => {}
+            _ if dialect == DialectKind::Sqlite => {
+                return Err(anyhow::anyhow!(
+                    "SQLite allows only one action per ALTER TABLE, but {} has {}",
+                    self.table.name(),
+                    self.actions.len()
+                )
+                .into());
+            }
+            _ => {}
+        }
+        write!(buf, "ALTER TABLE {}", self.table.name())?;
+        for (i, action) in self.actions.iter().enumerate() {
+            if i > 0 {
+                write!(buf, ",")?;
+            }
+            ctx.write_clause_separator(buf)?;
+            match action {
+                AlterAction::AddColumn(column) => {
+                    write!(buf, "ADD COLUMN ")?;
+                    column.write(dialect, buf)?;
+                }
+                AlterAction::DropColumn(column) => write!(buf, "DROP COLUMN {}", column)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct DropTable<'a, DB> {
+    table: TableName<'a, DB>,
+    if_exists: bool,
+}
+
+impl<'a, DB> DropTable<'a, DB> {
+    pub fn new(table: TableName<'a, DB>) -> Self {
+        DropTable {
+            table,
+            if_exists: false,
+        }
+    }
+
+    pub fn if_exists(self) -> Self {
+        DropTable {
+            if_exists: true,
+            ..self
+        }
+    }
+}
+
+impl<DB> DdlStatement for DropTable<'_, DB> {
+    fn write_statement(
+        &self,
+        _ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), BuildSqlError> {
+        write!(buf, "DROP TABLE ")?;
+        if self.if_exists {
+            write!(buf, "IF EXISTS ")?;
+        }
+        write!(buf, "{}", self.table.name())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,100 @@
+//! SQL Dialects
+//!
+//! Each database speaks a slightly different SQL.
+//! A dialect is passed where the output depends on the target database.
+//!
+
+use crate::query_builder::Value;
+use std::fmt::Write;
+
+/// Dialect chosen at runtime.
+#[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash, Default)]
+pub enum DialectKind {
+    #[default]
+    MySql,
+    Postgres,
+    Sqlite,
+}
+
+impl DialectKind {
+    pub const ALL: [DialectKind; 3] = [
+        DialectKind::MySql,
+        DialectKind::Postgres,
+        DialectKind::Sqlite,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DialectKind::MySql => "mysql",
+            DialectKind::Postgres => "postgres",
+            DialectKind::Sqlite => "sqlite",
+        }
+    }
+
+    /// Write `value` as a SQL literal escaped for this dialect.
+    pub fn write_literal(self, out: &mut String, value: &Value) -> std::fmt::Result {
+        match value {
+            Value::Null => out.write_str("NULL"),
+            Value::Int(i) => write!(out, "{}", i),
+            Value::Uint(u) => write!(out, "{}", u),
+            Value::Bool(b) => out.write_str(if *b { "TRUE" } else { "FALSE" }),
+            Value::String(s) => {
+                out.write_char('\'')?;
+                for c in s.chars() {
+                    match (self, c) {
+                        (_, '\'') => out.write_str("''")?,
+                        // MySQL treats backslash as an escape character in string literals.
+                        (DialectKind::MySql, '\\') => out.write_str("\\\\")?,
+                        (DialectKind::MySql, '\0') => out.write_str("\\0")?,
+                        (_, c) => out.write_char(c)?,
+                    }
+                }
+                out.write_char('\'')
+            }
+        }
+    }
+}
+
+pub trait Dialect: Copy {
+    fn kind(&self) -> DialectKind;
+}
+
+impl Dialect for DialectKind {
+    fn kind(&self) -> DialectKind {
+        *self
+    }
+}
+
+#[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash, Default)]
+pub struct MySql;
+
+impl Dialect for MySql {
+    fn kind(&self) -> DialectKind {
+        DialectKind::MySql
+    }
+}
+
+#[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash, Default)]
+pub struct Postgres;
+
+impl Dialect for Postgres {
+    fn kind(&self) -> DialectKind {
+        DialectKind::Postgres
+    }
+}
+
+#[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash, Default)]
+pub struct Sqlite;
+
+impl Dialect for Sqlite {
+    fn kind(&self) -> DialectKind {
+        DialectKind::Sqlite
+    }
+}
+
+/// Dialects with `FULL OUTER JOIN`. MySQL has none, so a full outer join for it does not compile.
+pub trait SupportsFullOuterJoin: Dialect {}
+
+impl SupportsFullOuterJoin for Postgres {}
+
+impl SupportsFullOuterJoin for Sqlite {}
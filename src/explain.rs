@@ -0,0 +1,385 @@
+//! EXPLAIN
+//!
+//! Wraps a statement in the `EXPLAIN` of the dialect, and reads the returned plan into [`QueryPlan`].
+//!
+//! ```ignore
+//! let plan = explain(builder).query_plan_sqlite(&mut conn).await?;
+//! assert!(plan.uses_index("idx_users_email"));
+//!
+//! let query = explain_analyze(builder).format_json().build_query(BuildOptions::new().dialect(Postgres))?;
+//! ```
+//!
+//! Reading plans from SQLite needs the `sqlite` feature, and JSON plans the `json` feature.
+//!
+
+use crate::dialect::DialectKind;
+use crate::query_builder::{
+    BuildOptions, BuildQuery, BuildSqlError, Query, QueryBuildError, SizeHint,
+};
+use std::fmt;
+use std::io::Write as _;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ExplainError {
+    #[error("ExplainError::QueryBuildError: {0}")]
+    QueryBuildError(#[from] QueryBuildError),
+    #[cfg(feature = "sqlite")]
+    #[error("ExplainError::OutOfRange: {0}")]
+    OutOfRange(#[from] crate::param::sqlite::OutOfRange),
+    #[cfg(feature = "sqlite")]
+    #[error("ExplainError::SqlxError: {0}")]
+    SqlxError(#[from] sqlx::Error),
+}
+
+/// Output format of `EXPLAIN`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum ExplainFormat {
+    /// The default of the dialect: a table on MySQL, text on Postgres and `EXPLAIN QUERY PLAN` rows on SQLite.
+    Default,
+    /// `FORMAT=JSON` on MySQL and `FORMAT JSON` on Postgres. Not supported by SQLite.
+    ///
+    /// MySQL accepts `EXPLAIN ANALYZE FORMAT=JSON` since 8.3, with `explain_json_format_version = 2`.
+    Json,
+}
+
+/// `EXPLAIN` of a statement.
+#[derive(Debug, Clone)]
+pub struct Explain<Q> {
+    query: Q,
+    analyze: bool,
+    format: ExplainFormat,
+}
+
+/// `EXPLAIN query`, or `EXPLAIN QUERY PLAN query` on SQLite.
+pub fn explain<Q: BuildQuery>(query: Q) -> Explain<Q> {
+    Explain {
+        query,
+        analyze: false,
+        format: ExplainFormat::Default,
+    }
+}
+
+/// `EXPLAIN ANALYZE query`, which runs the query. Not supported by SQLite.
+///
+/// With [`Explain::format_json`], MySQL before 8.3 rejects the statement.
+pub fn explain_analyze<Q: BuildQuery>(query: Q) -> Explain<Q> {
+    Explain {
+        query,
+        analyze: true,
+        format: ExplainFormat::Default,
+    }
+}
+
+impl<Q> Explain<Q> {
+    pub fn format_json(self) -> Explain<Q> {
+        Explain {
+            format: ExplainFormat::Json,
+            ..self
+        }
+    }
+
+    fn prefix(&self, dialect: DialectKind) -> Result<&'static str, anyhow::Error> {
+        let json = self.format == ExplainFormat::Json;
+        Ok(match (dialect, self.analyze, json) {
+            (DialectKind::MySql, false, false) => "EXPLAIN ",
+            (DialectKind::MySql, false, true) => "EXPLAIN FORMAT=JSON ",
+            (DialectKind::MySql, true, false) => "EXPLAIN ANALYZE ",
+            (DialectKind::MySql, true, true) => "EXPLAIN ANALYZE FORMAT=JSON ",
+            (DialectKind::Postgres, false, false) => "EXPLAIN ",
+            (DialectKind::Postgres, false, true) => "EXPLAIN (FORMAT JSON) ",
+            (DialectKind::Postgres, true, false) => "EXPLAIN ANALYZE ",
+            (DialectKind::Postgres, true, true) => "EXPLAIN (ANALYZE, FORMAT JSON) ",
+            (DialectKind::Sqlite, false, false) => "EXPLAIN QUERY PLAN ",
+            (DialectKind::Sqlite, true, _) => {
+                return Err(anyhow::anyhow!(
+                    "EXPLAIN ANALYZE is not supported by sqlite"
+                ))
+            }
+            (DialectKind::Sqlite, _, true) => {
+                return Err(anyhow::anyhow!(
+                    "EXPLAIN FORMAT JSON is not supported by sqlite"
+                ))
+            }
+        })
+    }
+}
+
+impl<Q: BuildQuery> BuildQuery for Explain<Q> {
+    #[track_caller]
+    fn build_query(self, options: BuildOptions) -> Result<Query, QueryBuildError> {
+        let prefix = self.prefix(options.dialect).map_err(BuildSqlError::from)?;
+        let query = self.query.build_query(options)?;
+        let size_hint = SizeHint::new(prefix.len() + query.sql().len(), query.params().len());
+        let explain = Query::build_with_size_hint(size_hint, |buf, params| {
+            write!(buf, "{}{}", prefix, query.sql())?;
+            params.extend_from_slice(query.params());
+            Ok(())
+        })?;
+        Ok(match query.placeholders() {
+            Some(placeholders) => {
+                explain.with_placeholders(placeholders.iter().map(|at| prefix.len() + at).collect())
+            }
+            None => explain,
+        })
+    }
+}
+
+/// Renders the statement with inlined parameters for the dialect of `BuildOptions::default()`.
+impl<Q: BuildQuery + Clone> fmt::Display for Explain<Q> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sql = self
+            .clone()
+            .debug_sql(BuildOptions::default())
+            .map_err(|_| fmt::Error)?;
+        f.write_str(&sql)
+    }
+}
+
+/// A step of a [`QueryPlan`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Default)]
+pub struct PlanNode {
+    /// e.g. `SCAN` or `SEARCH` on SQLite, `Index Scan` on Postgres and `ref` on MySQL.
+    pub operation: String,
+    pub table: Option<String>,
+    /// Alias of `table` in the query, e.g. `t1`.
+    pub alias: Option<String>,
+    pub index: Option<String>,
+    pub children: Vec<PlanNode>,
+}
+
+impl PlanNode {
+    /// Whether the table is read in full without an index:
+    /// `SCAN` on SQLite, `Seq Scan` on Postgres and `ALL` on MySQL.
+    pub fn is_full_scan(&self) -> bool {
+        matches!(self.operation.as_str(), "SCAN" | "Seq Scan" | "ALL") && self.index.is_none()
+    }
+}
+
+/// Plan returned by `EXPLAIN`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Default)]
+pub struct QueryPlan {
+    pub roots: Vec<PlanNode>,
+}
+
+impl QueryPlan {
+    /// All the nodes, parents before their children.
+    pub fn nodes(&self) -> Vec<&PlanNode> {
+        fn push<'a>(node: &'a PlanNode, nodes: &mut Vec<&'a PlanNode>) {
+            nodes.push(node);
+            for child in &node.children {
+                push(child, nodes);
+            }
+        }
+
+        let mut nodes = Vec::new();
+        for root in &self.roots {
+            push(root, &mut nodes);
+        }
+        nodes
+    }
+
+    pub fn uses_index(&self, index: &str) -> bool {
+        self.nodes()
+            .iter()
+            .any(|node| node.index.as_deref() == Some(index))
+    }
+
+    /// Whether `table` is read in full without an index. See [`PlanNode::is_full_scan`].
+    pub fn scans_table(&self, table: &str) -> bool {
+        self.nodes()
+            .iter()
+            .any(|node| node.table.as_deref() == Some(table) && node.is_full_scan())
+    }
+
+    /// Replace the aliases that SQLite 3.36+ and MySQL print as the table of a node
+    /// with the tables of `sources`, e.g. the [`QueryReferences::sources`] of the query.
+    ///
+    /// [`QueryReferences::sources`]: crate::query_builder::QueryReferences::sources
+    pub fn resolve_aliases(&mut self, sources: &[(Option<String>, String)]) {
+        fn resolve(node: &mut PlanNode, sources: &[(Option<String>, String)]) {
+            if node.alias.is_none() {
+                let source = sources
+                    .iter()
+                    .find(|(_, alias)| node.table.as_deref() == Some(alias.as_str()));
+                if let Some((Some(table), alias)) = source {
+                    node.table = Some(table.clone());
+                    node.alias = Some(alias.clone());
+                }
+            }
+            for child in &mut node.children {
+                resolve(child, sources);
+            }
+        }
+
+        for root in &mut self.roots {
+            resolve(root, sources);
+        }
+    }
+
+    /// Read the `(id, parent, detail)` rows of SQLite `EXPLAIN QUERY PLAN`,
+    /// e.g. `SEARCH users USING INDEX idx_users_email (email=?)`.
+    ///
+    /// SQLite 3.36+ prints the alias rather than the table, e.g. `SCAN t1`; see [`QueryPlan::resolve_aliases`].
+    pub fn from_sqlite_rows<I>(rows: I) -> QueryPlan
+    where
+        I: IntoIterator<Item = (i64, i64, String)>,
+    {
+        let rows: Vec<(i64, i64, String)> = rows.into_iter().collect();
+
+        fn children(rows: &[(i64, i64, String)], parent: i64) -> Vec<PlanNode> {
+            rows.iter()
+                .filter(|(_, p, _)| *p == parent)
+                .map(|(id, _, detail)| PlanNode {
+                    children: children(rows, *id),
+                    ..parse_sqlite_detail(detail)
+                })
+                .collect()
+        }
+
+        QueryPlan {
+            roots: children(&rows, 0),
+        }
+    }
+}
+
+fn parse_sqlite_detail(detail: &str) -> PlanNode {
+    let words: Vec<&str> = detail.split_whitespace().collect();
+    let operation = words.first().copied().unwrap_or_default().to_string();
+    let (table, alias) = match operation.as_str() {
+        // Before SQLite 3.36 these read `SCAN TABLE users AS t1`.
+        "SCAN" | "SEARCH" => {
+            let name = match words.get(1) {
+                Some(&"TABLE") => 2,
+                _ => 1,
+            };
+            let alias = match words.get(name + 1) {
+                Some(&"AS") => words.get(name + 2).map(|word| word.to_string()),
+                _ => None,
+            };
+            (words.get(name).map(|word| word.to_string()), alias)
+        }
+        _ => (None, None),
+    };
+    let index = words
+        .windows(2)
+        .find(|pair| pair[0] == "INDEX")
+        .map(|pair| pair[1].to_string());
+    PlanNode {
+        operation,
+        table,
+        alias,
+        index,
+        children: Vec::new(),
+    }
+}
+
+#[cfg(feature = "json")]
+mod json {
+    use super::{PlanNode, QueryPlan};
+    use sqlx::types::JsonValue;
+
+    fn string(value: &JsonValue, key: &str) -> Option<String> {
+        value.get(key).and_then(JsonValue::as_str).map(String::from)
+    }
+
+    fn postgres_node(plan: &JsonValue) -> PlanNode {
+        PlanNode {
+            operation: string(plan, "Node Type").unwrap_or_default(),
+            table: string(plan, "Relation Name"),
+            alias: string(plan, "Alias"),
+            index: string(plan, "Index Name"),
+            children: plan
+                .get("Plans")
+                .and_then(JsonValue::as_array)
+                .map(|plans| plans.iter().map(postgres_node).collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    fn mysql_nodes(value: &JsonValue) -> Vec<PlanNode> {
+        match value {
+            JsonValue::Array(values) => values.iter().flat_map(mysql_nodes).collect(),
+            JsonValue::Object(map) => map
+                .iter()
+                .flat_map(|(key, value)| match key.as_str() {
+                    "table" if value.is_object() => vec![PlanNode {
+                        operation: string(value, "access_type").unwrap_or_default(),
+                        table: string(value, "table_name"),
+                        alias: None,
+                        index: string(value, "key"),
+                        children: mysql_nodes(value),
+                    }],
+                    _ => mysql_nodes(value),
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    impl QueryPlan {
+        /// Read the plan of Postgres `EXPLAIN (FORMAT JSON)`: `[{"Plan": {...}}]`.
+        pub fn from_postgres_json(json: &JsonValue) -> QueryPlan {
+            QueryPlan {
+                roots: json
+                    .as_array()
+                    .map(|plans| {
+                        plans
+                            .iter()
+                            .filter_map(|plan| plan.get("Plan"))
+                            .map(postgres_node)
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            }
+        }
+
+        /// Read the plan of MySQL `EXPLAIN FORMAT=JSON`: `{"query_block": {...}}`.
+        ///
+        /// MySQL prints aliases as `table_name`; see [`QueryPlan::resolve_aliases`].
+        pub fn from_mysql_json(json: &JsonValue) -> QueryPlan {
+            QueryPlan {
+                roots: mysql_nodes(json),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use super::{Explain, ExplainError, QueryPlan};
+    use crate::dialect::Sqlite;
+    use crate::param::sqlite::bind;
+    use crate::query_builder::{BuildOptions, BuildQuery, QueryBuildError, QueryReferences, Visit};
+    use sqlx::sqlite::SqliteConnection;
+    use sqlx::Row as _;
+
+    impl<Q: BuildQuery + Visit> Explain<Q> {
+        /// Run `EXPLAIN QUERY PLAN` on SQLite and read the plan, with the aliases resolved to their tables.
+        pub async fn query_plan_sqlite(
+            self,
+            conn: &mut SqliteConnection,
+        ) -> Result<QueryPlan, ExplainError> {
+            let options = BuildOptions::new().dialect(Sqlite);
+            let mut references = QueryReferences::default();
+            self.query
+                .visit_with(options.clone(), &mut references)
+                .map_err(QueryBuildError::from)?;
+            let query = self.build_query(options)?;
+            let rows = bind(&query)?
+                .fetch_all(&mut *conn)
+                .await?
+                .iter()
+                .map(|row| {
+                    Ok((
+                        row.try_get::<i64, _>(0)?,
+                        row.try_get::<i64, _>(1)?,
+                        row.try_get::<String, _>(3)?,
+                    ))
+                })
+                .collect::<Result<Vec<_>, sqlx::Error>>()?;
+            let mut plan = QueryPlan::from_sqlite_rows(rows);
+            plan.resolve_aliases(&references.sources);
+            Ok(plan)
+        }
+    }
+}
@@ -1,25 +1,22 @@
 //! AQUERYON - A SQL Builder for Rust
 //!
+//! [`explain`] and [`migrate`] build their statements without sqlx. Running them against a database
+//! needs the `sqlite` feature, and reading JSON plans the `json` feature; those APIs are left out without them.
+//!
 
 #![deny(warnings, clippy::all)]
 //#![deny(missing_docs)]
 
+#[cfg(any(test, feature = "codegen"))]
+pub mod codegen;
+pub mod ddl;
+pub mod dialect;
+pub mod explain;
+pub mod migrate;
+pub mod param;
 pub mod query_builder;
-pub mod param {
-    // query_builderのVec<Value>を各クライアントライブラリのparam用の型に変換するためのtraitとその実装
-
-    // pub trait ToMysqlParam {
-    //     fn to_mysql_param(&self);
-    // }
-    //
-    // pub trait ToPostgresParam {
-    //     fn to_postgres_param(&self);
-    // }
-    //
-    // pub trait ToSQLiteParam {{
-    //     fn to_sqlite_param(&self);
-    // }
-}
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,351 @@
+//! Migrations
+//!
+//! Runs versioned migrations in order and records the applied versions in a bookkeeping table.
+//! A migration is either a Rust function returning queries, or a `.sql` file.
+//!
+//! ```ignore
+//! fn create_users(options: BuildOptions) -> Result<Vec<Query>, QueryBuildError> {
+//!     Ok(vec![CreateTable::new(users())
+//!         .column(ColumnDef::new::<SqlTypeInt>("id").not_null().primary_key())
+//!         .build_with(options)?])
+//! }
+//!
+//! let migrator = Migrator::new(BuildOptions::new().dialect(Sqlite))
+//!     .migration(Migration::new(1, "create_users", create_users))?
+//!     .migrations(Migration::from_dir("migrations")?)?;
+//! print!("{}", migrator.dry_run(&[])?);
+//! migrator.run_sqlite(&mut conn).await?;
+//! ```
+//!
+//! Executing needs the `sqlite` feature. Other drivers can execute the queries from `plan()`.
+//!
+
+use crate::ddl::{ColumnDef, CreateTable, DdlStatement};
+use crate::query_builder::{
+    AnyDatabase, BuildOptions, EmptySelectBuilder, Order, Query, QueryBuildError, SelectColumns,
+    SelectOrderBy, SelectSource, SqlTypeInt, SqlTypeString, TableName, Value,
+};
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::Path;
+
+pub type Version = i64;
+
+/// Default name of the bookkeeping table.
+pub const DEFAULT_TABLE: &str = "aqueryon_migrations";
+
+#[derive(Debug, thiserror::Error)]
+pub enum MigrateError {
+    #[error("MigrateError::QueryBuildError: {0}")]
+    QueryBuildError(#[from] QueryBuildError),
+    #[error("MigrateError::IoError: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("MigrateError::InvalidFileName: {0} (expected <version>_<name>.sql)")]
+    InvalidFileName(String),
+    #[error("MigrateError::DuplicateVersion: {0}")]
+    DuplicateVersion(Version),
+    #[cfg(feature = "sqlite")]
+    #[error("MigrateError::OutOfRange: {0}")]
+    OutOfRange(#[from] crate::param::sqlite::OutOfRange),
+    #[cfg(feature = "sqlite")]
+    #[error("MigrateError::SqlxError: {0}")]
+    SqlxError(#[from] sqlx::Error),
+}
+
+/// Queries of a migration, built for the dialect of the migrator.
+pub type MigrationFn = fn(BuildOptions) -> Result<Vec<Query>, QueryBuildError>;
+
+#[derive(Debug, Clone)]
+enum MigrationSource {
+    Rust(MigrationFn),
+    Sql(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Migration {
+    version: Version,
+    name: String,
+    source: MigrationSource,
+}
+
+impl Migration {
+    pub fn new(version: Version, name: impl Into<String>, f: MigrationFn) -> Migration {
+        Migration {
+            version,
+            name: name.into(),
+            source: MigrationSource::Rust(f),
+        }
+    }
+
+    /// Migration from SQL text. Statements are separated by `;`.
+    pub fn sql(version: Version, name: impl Into<String>, sql: impl Into<String>) -> Migration {
+        Migration {
+            version,
+            name: name.into(),
+            source: MigrationSource::Sql(sql.into()),
+        }
+    }
+
+    /// Read the `<version>_<name>.sql` files in `dir`. Other files are ignored.
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Vec<Migration>, MigrateError> {
+        let mut migrations = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("sql") {
+                continue;
+            }
+            let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+            let invalid = || MigrateError::InvalidFileName(path.display().to_string());
+            let (version, name) = stem.split_once('_').ok_or_else(invalid)?;
+            let version = version.parse().map_err(|_| invalid())?;
+            migrations.push(Migration::sql(
+                version,
+                name,
+                std::fs::read_to_string(&path)?,
+            ));
+        }
+        migrations.sort_by_key(|m| m.version);
+        Ok(migrations)
+    }
+
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Queries of this migration, without bookkeeping.
+    pub fn queries(&self, options: BuildOptions) -> Result<Vec<Query>, MigrateError> {
+        match &self.source {
+            MigrationSource::Rust(f) => Ok(f(options)?),
+            MigrationSource::Sql(sql) => split_statements(sql)
+                .into_iter()
+                .map(|statement| {
+                    Query::build(|buf, _params| {
+                        write!(buf, "{};", statement)?;
+                        Ok(())
+                    })
+                    .map_err(From::from)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A pending migration and the queries to apply it, the last one recording its version.
+#[derive(Debug, Clone)]
+pub struct PlannedMigration<'a> {
+    pub migration: &'a Migration,
+    pub queries: Vec<Query>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Migrator {
+    migrations: Vec<Migration>,
+    table: &'static str,
+    options: BuildOptions,
+}
+
+impl Migrator {
+    pub fn new(options: BuildOptions) -> Migrator {
+        Migrator {
+            migrations: Vec::new(),
+            table: DEFAULT_TABLE,
+            options,
+        }
+    }
+
+    /// Use `table` as the bookkeeping table instead of `aqueryon_migrations`.
+    pub fn table(self, table: &'static str) -> Migrator {
+        Migrator { table, ..self }
+    }
+
+    pub fn migration(self, migration: Migration) -> Result<Migrator, MigrateError> {
+        self.migrations(vec![migration])
+    }
+
+    pub fn migrations(
+        mut self,
+        migrations: impl IntoIterator<Item = Migration>,
+    ) -> Result<Migrator, MigrateError> {
+        for migration in migrations {
+            let index = match self
+                .migrations
+                .binary_search_by_key(&migration.version, |m| m.version)
+            {
+                Ok(_) => return Err(MigrateError::DuplicateVersion(migration.version)),
+                Err(index) => index,
+            };
+            self.migrations.insert(index, migration);
+        }
+        Ok(self)
+    }
+
+    /// `CREATE TABLE IF NOT EXISTS` of the bookkeeping table.
+    pub fn setup_query(&self) -> Result<Query, MigrateError> {
+        Ok(CreateTable::new(self.table_name())
+            .if_not_exists()
+            .column(
+                ColumnDef::new::<SqlTypeInt>("version")
+                    .not_null()
+                    .primary_key(),
+            )
+            .column(ColumnDef::new::<SqlTypeString>("name").not_null())
+            .build_with(self.options.clone())?)
+    }
+
+    /// Select the applied versions in ascending order.
+    pub fn applied_versions_query(&self) -> Result<Query, MigrateError> {
+        let (builder, t1) = EmptySelectBuilder::new().source(self.table_name());
+        let version = t1.typed_column::<SqlTypeInt>("version");
+        Ok(builder
+            .select(version.clone())
+            .order_by(Order::Asc(version))
+            .build_with(self.options.clone())?)
+    }
+
+    /// Migrations which are not in `applied`, in version order.
+    pub fn pending<'a>(&'a self, applied: &'a [Version]) -> impl Iterator<Item = &'a Migration> {
+        self.migrations
+            .iter()
+            .filter(move |m| !applied.contains(&m.version))
+    }
+
+    /// Queries to apply the pending migrations, each followed by its bookkeeping insert.
+    pub fn plan(&self, applied: &[Version]) -> Result<Vec<PlannedMigration<'_>>, MigrateError> {
+        self.migrations
+            .iter()
+            .filter(|m| !applied.contains(&m.version))
+            .map(|migration| {
+                let mut queries = migration.queries(self.options.clone())?;
+                queries.push(self.record_query(migration)?);
+                Ok(PlannedMigration { migration, queries })
+            })
+            .collect()
+    }
+
+    /// The SQL which would be executed, for reviewing before running.
+    pub fn dry_run(&self, applied: &[Version]) -> Result<String, MigrateError> {
+        let mut out = String::new();
+        for planned in self.plan(applied)? {
+            // Writing to a String never fails.
+            let _ = writeln!(
+                out,
+                "-- {} {}",
+                planned.migration.version, planned.migration.name
+            );
+            for query in &planned.queries {
+                let _ = writeln!(out, "{}", query.sql());
+                for (i, param) in query.params().iter().enumerate() {
+                    let _ = writeln!(out, "-- ${}: {:?}", i + 1, param);
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    fn record_query(&self, migration: &Migration) -> Result<Query, MigrateError> {
+        Ok(Query::build(|buf, params| {
+            write!(
+                buf,
+                "INSERT INTO {} (version, name) VALUES (?, ?);",
+                self.table
+            )?;
+            params.push(Value::Int(migration.version));
+            params.push(Value::String(migration.name.clone()));
+            Ok(())
+        })?)
+    }
+
+    fn table_name(&self) -> TableName<'static, AnyDatabase> {
+        TableName::new(self.table)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use super::{MigrateError, Migrator, Version};
+    use crate::param::sqlite::bind;
+    use sqlx::sqlite::SqliteConnection;
+    use sqlx::{Connection, Row as _};
+
+    impl Migrator {
+        /// Apply the pending migrations, each in its own transaction. Returns the applied versions.
+        pub async fn run_sqlite(
+            &self,
+            conn: &mut SqliteConnection,
+        ) -> Result<Vec<Version>, MigrateError> {
+            bind(&self.setup_query()?)?.execute(&mut *conn).await?;
+            let applied = bind(&self.applied_versions_query()?)?
+                .fetch_all(&mut *conn)
+                .await?
+                .iter()
+                .map(|row| row.try_get::<i64, _>(0))
+                .collect::<Result<Vec<Version>, _>>()?;
+
+            let mut done = Vec::new();
+            for planned in self.plan(&applied)? {
+                let mut tx = conn.begin().await?;
+                for query in &planned.queries {
+                    bind(query)?.execute(&mut *tx).await?;
+                }
+                tx.commit().await?;
+                done.push(planned.migration.version);
+            }
+            Ok(done)
+        }
+    }
+}
+
+/// Split SQL text into statements at `;` outside of quotes and comments.
+fn split_statements(sql: &str) -> Vec<&str> {
+    let mut statements = Vec::new();
+    let mut start = 0;
+    let mut has_code = false;
+    let mut chars = sql.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\'' | '"' | '`' => {
+                has_code = true;
+                for (_, q) in chars.by_ref() {
+                    if q == c {
+                        break;
+                    }
+                }
+            }
+            '-' if chars.peek().map(|(_, c)| *c) == Some('-') => {
+                for (_, c) in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek().map(|(_, c)| *c) == Some('*') => {
+                chars.next();
+                let mut prev = ' ';
+                for (_, c) in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            ';' => {
+                if has_code {
+                    statements.push(sql[start..i].trim());
+                }
+                start = i + 1;
+                has_code = false;
+            }
+            c if !c.is_whitespace() => has_code = true,
+            _ => {}
+        }
+    }
+    if has_code {
+        statements.push(sql[start..].trim());
+    }
+    statements
+}
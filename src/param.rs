@@ -0,0 +1,47 @@
+//! Parameters
+//!
+//! Binding the [`Value`](crate::query_builder::Value)s of a query to client libraries.
+//!
+
+// query_builderのVec<Value>を各クライアントライブラリのparam用の型に変換するためのtraitとその実装
+
+// pub trait ToMysqlParam {
+//     fn to_mysql_param(&self);
+// }
+//
+// pub trait ToPostgresParam {
+//     fn to_postgres_param(&self);
+// }
+//
+// pub trait ToSQLiteParam {{
+//     fn to_sqlite_param(&self);
+// }
+
+#[cfg(feature = "sqlite")]
+pub mod sqlite {
+    use crate::query_builder::{Query, Value};
+    use sqlx::sqlite::{Sqlite, SqliteArguments};
+    use std::convert::TryFrom;
+
+    pub type SqliteQuery<'q> = sqlx::query::Query<'q, Sqlite, SqliteArguments<'q>>;
+
+    /// An unsigned parameter too large for a signed 64 bits integer of SQLite.
+    #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, thiserror::Error)]
+    #[error("{0} is out of the range of SQLite integers")]
+    pub struct OutOfRange(pub u64);
+
+    /// Prepare `query` for sqlx with its parameters bound.
+    pub fn bind(query: &Query) -> Result<SqliteQuery<'_>, OutOfRange> {
+        let mut bound = sqlx::query(query.sql());
+        for param in query.params() {
+            bound = match param {
+                Value::Null => bound.bind(None::<i64>),
+                Value::String(s) => bound.bind(s.as_str()),
+                Value::Int(i) => bound.bind(*i),
+                Value::Bool(b) => bound.bind(*b),
+                Value::Uint(u) => bound.bind(i64::try_from(*u).map_err(|_| OutOfRange(*u))?),
+            };
+        }
+        Ok(bound)
+    }
+}
@@ -33,8 +33,13 @@ pub mod synonym {
     pub type RightOuterJoin<L, R, EXP> =
         qb::Join<<L as qb::QuerySource>::NullableSelf, IntoQuerySourceRef<R>, EXP>;
 
+    pub type FullOuterJoin<L, R, EXP> =
+        qb::Join<<L as qb::QuerySource>::NullableSelf, IntoNullableQuerySourceRef<R>, EXP>;
+
     pub type CrossJoin<L, R> = qb::Join<L, IntoQuerySourceRef<R>, qb::BlankBoolExpression>;
 
+    pub type NaturalJoin<L, R> = qb::Join<L, IntoQuerySourceRef<R>, qb::BlankBoolExpression>;
+
     pub type IntoQuerySource<QS> = <QS as qb::IntoQuerySource>::QuerySource;
 
     pub type IntoNullableQuerySource<QS> =
@@ -45,13 +50,18 @@ pub mod synonym {
     pub type IntoNullableQuerySourceRef<QS> = qb::QuerySourceRef<IntoNullableQuerySource<QS>>;
 }
 
+use crate::dialect::{Dialect, DialectKind, SupportsFullOuterJoin};
 use std::borrow::{Borrow, Cow};
 use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::convert::TryInto;
+use std::fmt;
 use std::io::Write;
 use std::marker::PhantomData;
+use std::panic::Location;
 use std::rc::Rc;
 use std::string::FromUtf8Error;
+use std::sync::Arc;
 pub use synonym::EmptySelectBuilder;
 
 // とりあえず名前は適当に。
@@ -85,15 +95,20 @@ where
     type Next = synonym::SourceUpdatedBuilder<synonym::IntoQuerySourceRef<T>>;
     fn source(self, source: T) -> (Self::Next, synonym::IntoQuerySourceRef<T>) {
         let sources_num = self.sources_num + 1;
+        let explicit_alias = source.explicit_alias();
         let src_ref = QuerySourceRef::new(
             source.into_query_source(),
-            SourceAlias::new(self.sources_alias_name.clone(), sources_num),
+            SourceAlias::new(self.sources_alias_name.clone(), sources_num)
+                .with_explicit(explicit_alias),
         );
         let ret_src_ref = src_ref.clone();
         let new_builder = SelectBuilder {
             sources: FromClause::new(src_ref),
             sources_num,
             sources_alias_name: self.sources_alias_name,
+            hints: self.hints,
+            tags: self.tags,
+            scopes: self.scopes,
             filter: self.filter,
             columns: (),
             group_by: self.group_by,
@@ -133,15 +148,17 @@ where
     QS2::QuerySource: QuerySource + Clone,
     QS2::Database: Joinable<QS::Database>,
     ON: FnMut(QuerySourceRef<QS2::QuerySource>) -> EXP,
-    EXP: Expression<SqlType = SqlTypeBool>,
+    EXP: JoinConstraint,
 {
     type Next = synonym::SourceUpdatedBuilder<synonym::Join<QS, QS2, EXP>>;
 
     fn inner_join(self, source: QS2, mut on: ON) -> (Self::Next, synonym::IntoQuerySourceRef<QS2>) {
         let sources_num = self.sources_num + 1;
+        let explicit_alias = source.explicit_alias();
         let src_ref = QuerySourceRef::new(
             source.into_query_source(),
-            SourceAlias::new(self.sources_alias_name.clone(), sources_num),
+            SourceAlias::new(self.sources_alias_name.clone(), sources_num)
+                .with_explicit(explicit_alias),
         );
         let ret_src_ref = src_ref.clone();
         let on_expr = on(src_ref.clone());
@@ -149,6 +166,9 @@ where
             sources: FromClause::new(Join::Inner(self.sources.unwrap(), src_ref, on_expr)),
             sources_num,
             sources_alias_name: self.sources_alias_name,
+            hints: self.hints,
+            tags: self.tags,
+            scopes: self.scopes,
             filter: self.filter,
             columns: (),
             group_by: self.group_by,
@@ -182,7 +202,7 @@ where
     QS2::Database: Joinable<QS::Database>,
     <QS2::QuerySource as QuerySource>::NullableSelf: Clone,
     ON: FnMut(QuerySourceRef<<QS2::QuerySource as QuerySource>::NullableSelf>) -> EXP,
-    EXP: Expression<SqlType = SqlTypeBool>,
+    EXP: JoinConstraint,
 {
     type Next = synonym::SourceUpdatedBuilder<synonym::LeftOuterJoin<QS, QS2, EXP>>;
 
@@ -192,9 +212,11 @@ where
         mut on: ON,
     ) -> (Self::Next, synonym::IntoNullableQuerySourceRef<QS2>) {
         let sources_num = self.sources_num + 1;
+        let explicit_alias = source.explicit_alias();
         let src_ref = QuerySourceRef::new(
             source.into_query_source().nullable(),
-            SourceAlias::new(self.sources_alias_name.clone(), sources_num),
+            SourceAlias::new(self.sources_alias_name.clone(), sources_num)
+                .with_explicit(explicit_alias),
         );
         let ret_src_ref = src_ref.clone();
         let on_expr = on(src_ref.clone());
@@ -202,6 +224,9 @@ where
             sources: FromClause::new(Join::LeftOuter(self.sources.unwrap(), src_ref, on_expr)),
             sources_num,
             sources_alias_name: self.sources_alias_name,
+            hints: self.hints,
+            tags: self.tags,
+            scopes: self.scopes,
             filter: self.filter,
             columns: (),
             group_by: self.group_by,
@@ -244,15 +269,20 @@ where
 
     fn cross_join(self, source: QS2) -> (Self::Next, synonym::IntoQuerySourceRef<QS2>) {
         let sources_num = self.sources_num + 1;
+        let explicit_alias = source.explicit_alias();
         let src_ref = QuerySourceRef::new(
             source.into_query_source(),
-            SourceAlias::new(self.sources_alias_name.clone(), sources_num),
+            SourceAlias::new(self.sources_alias_name.clone(), sources_num)
+                .with_explicit(explicit_alias),
         );
         let ret_src_ref = src_ref.clone();
         let new_builder = SelectBuilder {
             sources: FromClause::new(Join::Cross(self.sources.unwrap(), src_ref)),
             sources_num,
             sources_alias_name: self.sources_alias_name,
+            hints: self.hints,
+            tags: self.tags,
+            scopes: self.scopes,
             filter: self.filter,
             columns: (),
             group_by: self.group_by,
@@ -302,6 +332,9 @@ where
             sources: self.sources,
             sources_num: self.sources_num,
             sources_alias_name: self.sources_alias_name,
+            hints: self.hints,
+            tags: self.tags,
+            scopes: self.scopes,
             filter: self.filter,
             columns,
             group_by: self.group_by,
@@ -350,6 +383,9 @@ where
             sources: self.sources,
             sources_num: self.sources_num,
             sources_alias_name: self.sources_alias_name,
+            hints: self.hints,
+            tags: self.tags,
+            scopes: self.scopes,
             filter: WhereClause::new(expr),
             columns: self.columns,
             group_by: self.group_by,
@@ -398,6 +434,9 @@ where
             sources: self.sources,
             sources_num: self.sources_num,
             sources_alias_name: self.sources_alias_name,
+            hints: self.hints,
+            tags: self.tags,
+            scopes: self.scopes,
             filter: self.filter,
             columns: self.columns,
             group_by: GroupByClause::new(group),
@@ -448,6 +487,9 @@ where
             sources: self.sources,
             sources_num: self.sources_num,
             sources_alias_name: self.sources_alias_name,
+            hints: self.hints,
+            tags: self.tags,
+            scopes: self.scopes,
             filter: self.filter,
             columns: self.columns,
             group_by: self.group_by,
@@ -497,6 +539,9 @@ where
             sources: self.sources,
             sources_num: self.sources_num,
             sources_alias_name: self.sources_alias_name,
+            hints: self.hints,
+            tags: self.tags,
+            scopes: self.scopes,
             filter: self.filter,
             columns: self.columns,
             group_by: self.group_by,
@@ -529,6 +574,9 @@ where
             sources: self.sources,
             sources_num: self.sources_num,
             sources_alias_name: self.sources_alias_name,
+            hints: self.hints,
+            tags: self.tags,
+            scopes: self.scopes,
             filter: self.filter,
             columns: self.columns,
             group_by: self.group_by,
@@ -576,7 +624,7 @@ where
 // こうすすること
 
 macro_rules! define_select_clause {
-    ( $type_name:ident, $empty_type:tt, $clause:expr ) => {
+    ( $type_name:ident, $empty_type:tt, $clause:expr $(, $body_start:ident)? ) => {
         #[derive(Debug, Clone, Default)]
         pub struct $empty_type;
 
@@ -604,8 +652,9 @@ macro_rules! define_select_clause {
         }
 
         impl BuildSql for $empty_type {
-            fn build_sql(
+            fn build_sql_with(
                 &self,
+                _ctx: &mut BuildContext,
                 _buf: &mut Vec<u8>,
                 _params: &mut Vec<Value>,
             ) -> Result<(), BuildSqlError> {
@@ -617,30 +666,48 @@ macro_rules! define_select_clause {
         where
             T: BuildSql,
         {
-            fn build_sql(
+            fn build_sql_with(
                 &self,
+                ctx: &mut BuildContext,
                 buf: &mut Vec<u8>,
                 params: &mut Vec<Value>,
             ) -> Result<(), BuildSqlError> {
-                write!(buf, $clause)?;
-                self.inner_ref().build_sql(buf, params)
+                let start = buf.len();
+                ctx.write_clause_separator(buf)?;
+                write!(buf, "{} ", $clause)?;
+                let body = buf.len();
+                self.inner_ref().build_sql_with(ctx, buf, params)?;
+                // Omit the clause if it has no items, e.g. an empty `DynOrders`.
+                if buf.len() == body {
+                    buf.truncate(start);
+                    return Ok(());
+                }
+                $(ctx.$body_start = Some(body);)?
+                Ok(())
+            }
+
+            fn size_hint(&self) -> SizeHint {
+                self.inner_ref().size_hint() + " ".len() + $clause.len() + " ".len()
             }
         }
     };
 }
 
-define_select_clause!(FromClause, EmptyFromClause, " FROM ");
-define_select_clause!(WhereClause, EmptyWhereClause, " WHERE ");
-define_select_clause!(GroupByClause, EmptyGroupByClause, " GROUP BY ");
-define_select_clause!(HavingClause, EmptyHavingClause, " HAVING ");
-define_select_clause!(OrderByClause, EmptyOrderByClause, " ORDER BY ");
-define_select_clause!(LimitClause, EmptyLimitClause, " LIMIT ");
+define_select_clause!(FromClause, EmptyFromClause, "FROM");
+define_select_clause!(WhereClause, EmptyWhereClause, "WHERE", where_condition);
+define_select_clause!(GroupByClause, EmptyGroupByClause, "GROUP BY");
+define_select_clause!(HavingClause, EmptyHavingClause, "HAVING");
+define_select_clause!(OrderByClause, EmptyOrderByClause, "ORDER BY");
+define_select_clause!(LimitClause, EmptyLimitClause, "LIMIT");
 
 #[derive(Clone)]
 pub struct SelectBuilder<QS, W, C, G, H, O, L, LM> {
     sources: QS,
-    sources_num: u8,
+    sources_num: u32,
     sources_alias_name: SourceAliasName,
+    hints: Vec<OptimizerHint>,
+    tags: Vec<(String, String)>,
+    scopes: Vec<Rc<dyn SourceScope>>,
     filter: W,
     columns: C,
     group_by: G,
@@ -654,13 +721,36 @@ impl<QS, W, C, G, H, O, L, LM> SelectBuilder<QS, W, C, G, H, O, L, LM> {
     pub fn change_sources_alias_name(&mut self, new_name: &'static str) {
         self.sources_alias_name.set(new_name)
     }
+
+    /// Add an optimizer hint, written as `SELECT /*+ hint */ ...`.
+    pub fn optimizer_hint(mut self, hint: OptimizerHint) -> Self {
+        self.hints.push(hint);
+        self
+    }
+
+    /// Add a sqlcommenter tag, written as `SELECT ... /* key='value' */;`.
+    pub fn tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.push((key.into(), value.into()));
+        self
+    }
+
+    /// Add a predicate for every table of the query and of its subqueries, e.g. `TenantScope(id)`.
+    pub fn with_scope(mut self, scope: impl SourceScope + 'static) -> Self {
+        self.scopes.push(Rc::new(scope));
+        self
+    }
 }
 
 #[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash, Default)]
 pub struct LockModeDefaultBehavior;
 
 impl BuildSql for LockModeDefaultBehavior {
-    fn build_sql(&self, _buf: &mut Vec<u8>, _params: &mut Vec<Value>) -> Result<(), BuildSqlError> {
+    fn build_sql_with(
+        &self,
+        _ctx: &mut BuildContext,
+        _buf: &mut Vec<u8>,
+        _params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
         Ok(())
     }
 }
@@ -669,8 +759,18 @@ impl BuildSql for LockModeDefaultBehavior {
 pub struct ForUpdate;
 
 impl BuildSql for ForUpdate {
-    fn build_sql(&self, buf: &mut Vec<u8>, _params: &mut Vec<Value>) -> Result<(), BuildSqlError> {
-        Ok(write!(buf, " FOR UPDATE")?)
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        _params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        ctx.write_clause_separator(buf)?;
+        Ok(write!(buf, "FOR UPDATE")?)
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::bytes(" FOR UPDATE".len())
     }
 }
 
@@ -678,8 +778,18 @@ impl BuildSql for ForUpdate {
 pub struct LockInShareMode;
 
 impl BuildSql for LockInShareMode {
-    fn build_sql(&self, buf: &mut Vec<u8>, _params: &mut Vec<Value>) -> Result<(), BuildSqlError> {
-        Ok(write!(buf, " LOCK IN SHARE MODE")?)
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        _params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        ctx.write_clause_separator(buf)?;
+        Ok(write!(buf, "LOCK IN SHARE MODE")?)
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::bytes(" LOCK IN SHARE MODE".len())
     }
 }
 
@@ -689,6 +799,9 @@ impl Default for synonym::EmptySelectBuilder {
             sources: EmptyFromClause,
             sources_num: 0,
             sources_alias_name: SourceAliasName::default(),
+            hints: Vec::new(),
+            tags: Vec::new(),
+            scopes: Vec::new(),
             filter: EmptyWhereClause,
             columns: (),
             group_by: EmptyGroupByClause,
@@ -720,6 +833,8 @@ impl<QS>
 where
     QS: QuerySource,
 {
+    /// `RIGHT OUTER JOIN`, making the sources joined before nullable.
+    /// Their refs keep non-null column types; re-type them with `t1.nullable()`.
     pub fn right_outer_join<QS2, ON, EXP>(
         self,
         source: QS2,
@@ -733,12 +848,14 @@ where
         QS2::Database: Joinable<QS::Database>,
         QS2::QuerySource: QuerySource + Clone,
         ON: FnMut(QuerySourceRef<QS2::QuerySource>) -> EXP,
-        EXP: Expression<SqlType = SqlTypeBool>,
+        EXP: JoinConstraint,
     {
         let sources_num = self.sources_num + 1;
+        let explicit_alias = source.explicit_alias();
         let src_ref = QuerySourceRef::new(
             source.into_query_source(),
-            SourceAlias::new(self.sources_alias_name.clone(), sources_num),
+            SourceAlias::new(self.sources_alias_name.clone(), sources_num)
+                .with_explicit(explicit_alias),
         );
         let ret_src_ref = src_ref.clone();
         let on_expr = on(src_ref.clone());
@@ -750,6 +867,119 @@ where
             )),
             sources_num,
             sources_alias_name: self.sources_alias_name,
+            hints: self.hints,
+            tags: self.tags,
+            scopes: self.scopes,
+            filter: self.filter,
+            columns: (),
+            group_by: self.group_by,
+            having: self.having,
+            order_by: self.order_by,
+            limit: self.limit,
+            lock_mode: self.lock_mode,
+        };
+        (new_builder, ret_src_ref)
+    }
+
+    /// `FULL OUTER JOIN`, making both sides nullable. `dialect` must support it, which MySQL does not;
+    /// building it for MySQL anyway fails.
+    ///
+    /// Returns the sources joined before re-typed as nullable, e.g. the ref of a single source,
+    /// along with the ref of `source`.
+    ///
+    /// ```ignore
+    /// let (builder, t1) = EmptySelectBuilder::new().source("users");
+    /// let (builder, t1, t2) = builder.full_outer_join(Postgres, "accounts", |t2| {
+    ///     t2.column("user_id").eq(t1.column("id"))
+    /// });
+    /// let name: Column<Nullable<SqlTypeString>> = t1.typed_column::<SqlTypeString>("name");
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use aqueryon::dialect::MySql;
+    /// use aqueryon::query_builder::*;
+    ///
+    /// let (builder, _t1) = EmptySelectBuilder::new().source("users");
+    /// let (builder, _t1, _t2) = builder.full_outer_join(MySql, "accounts", |_| using(["id"]));
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn full_outer_join<D, QS2, ON, EXP>(
+        self,
+        _dialect: D,
+        source: QS2,
+        mut on: ON,
+    ) -> (
+        synonym::SourceUpdatedBuilder<synonym::FullOuterJoin<QS, QS2, EXP>>,
+        QS::NullableSelf,
+        synonym::IntoNullableQuerySourceRef<QS2>,
+    )
+    where
+        D: SupportsFullOuterJoin,
+        QS::NullableSelf: Clone,
+        QS2: IntoQuerySource,
+        QS2::Database: Joinable<QS::Database>,
+        QS2::QuerySource: QuerySource + Clone,
+        <QS2::QuerySource as QuerySource>::NullableSelf: Clone,
+        ON: FnMut(QuerySourceRef<<QS2::QuerySource as QuerySource>::NullableSelf>) -> EXP,
+        EXP: JoinConstraint,
+    {
+        let sources_num = self.sources_num + 1;
+        let explicit_alias = source.explicit_alias();
+        let src_ref = QuerySourceRef::new(
+            source.into_query_source().nullable(),
+            SourceAlias::new(self.sources_alias_name.clone(), sources_num)
+                .with_explicit(explicit_alias),
+        );
+        let ret_src_ref = src_ref.clone();
+        let on_expr = on(src_ref.clone());
+        let left = self.sources.unwrap().nullable();
+        let ret_left = left.clone();
+        let new_builder = SelectBuilder {
+            sources: FromClause::new(Join::FullOuter(left, src_ref, on_expr)),
+            sources_num,
+            sources_alias_name: self.sources_alias_name,
+            hints: self.hints,
+            tags: self.tags,
+            scopes: self.scopes,
+            filter: self.filter,
+            columns: (),
+            group_by: self.group_by,
+            having: self.having,
+            order_by: self.order_by,
+            limit: self.limit,
+            lock_mode: self.lock_mode,
+        };
+        (new_builder, ret_left, ret_src_ref)
+    }
+
+    /// `NATURAL JOIN` on the columns of the same name.
+    pub fn natural_join<QS2>(
+        self,
+        source: QS2,
+    ) -> (
+        synonym::SourceUpdatedBuilder<synonym::NaturalJoin<QS, QS2>>,
+        synonym::IntoQuerySourceRef<QS2>,
+    )
+    where
+        QS2: IntoQuerySource,
+        QS2::Database: Joinable<QS::Database>,
+        QS2::QuerySource: QuerySource + Clone,
+    {
+        let sources_num = self.sources_num + 1;
+        let explicit_alias = source.explicit_alias();
+        let src_ref = QuerySourceRef::new(
+            source.into_query_source(),
+            SourceAlias::new(self.sources_alias_name.clone(), sources_num)
+                .with_explicit(explicit_alias),
+        );
+        let ret_src_ref = src_ref.clone();
+        let new_builder = SelectBuilder {
+            sources: FromClause::new(Join::Natural(self.sources.unwrap(), src_ref)),
+            sources_num,
+            sources_alias_name: self.sources_alias_name,
+            hints: self.hints,
+            tags: self.tags,
+            scopes: self.scopes,
             filter: self.filter,
             columns: (),
             group_by: self.group_by,
@@ -774,6 +1004,9 @@ where
             sources: self.sources,
             sources_num: self.sources_num,
             sources_alias_name: self.sources_alias_name,
+            hints: self.hints,
+            tags: self.tags,
+            scopes: self.scopes,
             filter: self.filter,
             columns: self.columns,
             group_by: self.group_by,
@@ -788,7 +1021,7 @@ where
 impl<QS, C, W, G, H, O, L, LM> SelectBuilder<QS, W, C, G, H, O, L, LM>
 where
     QS: BuildSql,
-    C: BuildSql,
+    C: BuildSql + Columns,
     W: BuildSql,
     G: BuildSql,
     H: BuildSql,
@@ -796,27 +1029,306 @@ where
     L: BuildSql,
     LM: BuildSql,
 {
+    #[track_caller]
     pub fn build(self) -> Result<Query, QueryBuildError> {
-        Query::build(|buf, params| {
-            write!(buf, "SELECT ")?;
-            self.columns.build_sql(buf, params)?;
-            self.sources.build_sql(buf, params)?;
-            self.filter.build_sql(buf, params)?;
-            self.group_by.build_sql(buf, params)?;
-            self.having.build_sql(buf, params)?;
-            self.order_by.build_sql(buf, params)?;
-            self.limit.build_sql(buf, params)?;
-            self.lock_mode.build_sql(buf, params)?;
-            write!(buf, ";")?;
+        self.build_with(BuildOptions::default())
+    }
+
+    /// Build the statement with `options`, e.g. `BuildOptions::new().pretty()`.
+    #[track_caller]
+    pub fn build_with(self, options: BuildOptions) -> Result<Query, QueryBuildError> {
+        let mut sql = String::new();
+        let mut params = Vec::new();
+        let placeholders = self.build_into_with(&options, &mut sql, &mut params)?;
+        Ok(Query {
+            sql,
+            params,
+            placeholders: Some(placeholders),
+        })
+    }
+
+    /// Build the statement into buffers owned by the caller.
+    ///
+    /// The buffers are cleared first, so they can be reused across builds.
+    #[track_caller]
+    pub fn build_into(
+        &self,
+        sql: &mut String,
+        params: &mut Vec<Value>,
+    ) -> Result<(), QueryBuildError> {
+        self.build_into_with(&BuildOptions::default(), sql, params)
+            .map(|_| ())
+    }
+
+    /// Build the statement with `options` into buffers owned by the caller,
+    /// returning the byte offsets of its placeholders. See [`Query::placeholders`].
+    #[track_caller]
+    pub fn build_into_with(
+        &self,
+        options: &BuildOptions,
+        sql: &mut String,
+        params: &mut Vec<Value>,
+    ) -> Result<Vec<usize>, QueryBuildError> {
+        let mut ctx = BuildContext::new(options.clone());
+        ctx.fill_caller_tags();
+        Query::build_into(sql, params, self.statement_size_hint(), |buf, params| {
+            self.build_statement(&mut ctx, buf, params)
+        })?;
+        Ok(ctx.placeholders)
+    }
+
+    fn build_statement(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        write!(buf, "SELECT ")?;
+        self.build_select_body(ctx, buf, params)?;
+        write_tags(ctx.tags.iter().chain(&self.tags), buf)?;
+        write!(buf, ";")?;
+        Ok(())
+    }
+
+    fn statement_size_hint(&self) -> SizeHint {
+        let tags_len = self
+            .tags
+            .iter()
+            .map(|(key, value)| key.len() + value.len() + "='',".len())
+            .sum::<usize>();
+        self.select_body_size_hint() + "SELECT  /*  */;".len() + tags_len
+    }
+
+    fn build_select_body(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        ctx.alias_scope(|ctx| {
+            write_optimizer_hints(&self.hints, buf)?;
+            // The scopes also apply to the subqueries of every clause.
+            let outer_scopes_len = ctx.scopes.len();
+            ctx.scopes.extend(self.scopes.iter().cloned());
+            let outer_distinct_on = ctx.distinct_on.take();
+            let columns =
+                ctx.collect_grouping(|ctx| self.columns.build_sql_with(ctx, buf, params))?;
+            let distinct_on = std::mem::replace(&mut ctx.distinct_on, outer_distinct_on);
+            let outer_scoped_sources = std::mem::take(&mut ctx.scoped_sources);
+            self.sources.build_sql_with(ctx, buf, params)?;
+            ctx.where_condition = None;
+            self.filter.build_sql_with(ctx, buf, params)?;
+            let condition_start = ctx.where_condition.take();
+            let scoped_sources = std::mem::replace(&mut ctx.scoped_sources, outer_scoped_sources);
+            ctx.write_scoped_where(&scoped_sources, condition_start, buf, params)?;
+            let group_by_start = buf.len();
+            let group_by =
+                ctx.collect_grouping(|ctx| self.group_by.build_sql_with(ctx, buf, params))?;
+            let grouped = buf.len() != group_by_start;
+            let having =
+                ctx.collect_grouping(|ctx| self.having.build_sql_with(ctx, buf, params))?;
+            let outer_select_list = ctx.select_list.replace(self.columns.output_columns());
+            let order_by =
+                ctx.collect_grouping(|ctx| self.order_by.build_sql_with(ctx, buf, params))?;
+            ctx.select_list = outer_select_list;
+            self.limit.build_sql_with(ctx, buf, params)?;
+            self.lock_mode.build_sql_with(ctx, buf, params)?;
+            ctx.scopes.truncate(outer_scopes_len);
+
+            if ctx.visiting {
+                return Ok(());
+            }
+            if let Some(keys) = distinct_on {
+                check_distinct_on(&keys, &order_by)?;
+            }
+            if grouped {
+                check_rolled_up(&group_by, &columns)?;
+            }
+            if !ctx.options.allow_partial_group_by {
+                check_full_group_by(grouped, &group_by, &[columns, having, order_by])?;
+            }
             Ok(())
         })
     }
+
+    fn select_body_size_hint(&self) -> SizeHint {
+        optimizer_hints_size_hint(&self.hints)
+            + self.columns.size_hint()
+            + self.sources.size_hint()
+            + self.filter.size_hint()
+            + self.group_by.size_hint()
+            + self.having.size_hint()
+            + self.order_by.size_hint()
+            + self.limit.size_hint()
+            + self.lock_mode.size_hint()
+    }
+}
+
+/// Reject columns which are neither grouped nor aggregated in a grouped query,
+/// as MySQL does under `ONLY_FULL_GROUP_BY` and Postgres always does.
+///
+/// Columns are matched by source and name while building, not by the type system:
+/// a `Column<ST>` carries its name only at runtime, so `group_by(...).select(...)`
+/// can't tell grouped columns apart at compile time either.
+/// Functional dependencies are unknown, so a column determined by a grouped key
+/// (e.g. `GROUP BY t1.id`, `SELECT t1.name`) is rejected although MySQL accepts it:
+/// group by it too, or use [`BuildOptions::allow_partial_group_by`].
+/// A column counts as grouped when any GROUP BY expression uses it, so
+/// `GROUP BY date(t1.c2)` with `SELECT t1.c2` is accepted, although the databases reject it.
+fn check_full_group_by(
+    grouped: bool,
+    group_by: &GroupingScope,
+    clauses: &[GroupingScope],
+) -> Result<(), BuildSqlError> {
+    if !grouped {
+        return Ok(());
+    }
+    let ungrouped = clauses
+        .iter()
+        .flat_map(|clause| clause.columns.iter())
+        .find(|column| !group_by.columns.contains(column));
+    if let Some(column) = ungrouped {
+        return Err(anyhow::anyhow!(
+            "`{}` is neither in GROUP BY nor aggregated (ONLY_FULL_GROUP_BY)",
+            column.text
+        )
+        .into());
+    }
+    let ungrouped = clauses
+        .iter()
+        .flat_map(|clause| clause.grouping_args.iter())
+        .find(|column| !group_by.columns.contains(column));
+    match ungrouped {
+        Some(column) => Err(anyhow::anyhow!(
+            "`{}` is an argument of grouping() but not in GROUP BY",
+            column.text
+        )
+        .into()),
+        None => Ok(()),
+    }
+}
+
+/// Reject selected columns of a non-null type which `ROLLUP`, `CUBE` or `GROUPING SETS`
+/// make NULL in subtotal rows, as they would fail to decode. Every column of
+/// `GROUPING SETS` counts as rolled up, even if it is in all of the sets.
+fn check_rolled_up(group_by: &GroupingScope, columns: &GroupingScope) -> Result<(), BuildSqlError> {
+    let rolled_up = |column: &ColumnRef| {
+        let mut grouped = group_by.columns.iter().filter(|grouped| *grouped == column);
+        grouped.clone().any(|grouped| grouped.may_be_null)
+            && grouped.all(|grouped| grouped.may_be_null)
+    };
+    match columns
+        .columns
+        .iter()
+        .find(|column| !column.may_be_null && rolled_up(column))
+    {
+        Some(column) => Err(anyhow::anyhow!(
+            "`{}` is rolled up into NULL in subtotal rows; select it with a Nullable type",
+            column.text
+        )
+        .into()),
+        None => Ok(()),
+    }
+}
+
+/// Postgres requires `ORDER BY` to start with the `DISTINCT ON` expressions.
+fn check_distinct_on(keys: &[ColumnRef], order_by: &GroupingScope) -> Result<(), BuildSqlError> {
+    if order_by.columns.is_empty() {
+        return Ok(());
+    }
+    let leading = order_by.columns.iter().take(keys.len());
+    if order_by.columns.len() < keys.len() || leading.clone().any(|column| !keys.contains(column)) {
+        return Err(anyhow::anyhow!(
+            "ORDER BY must start with the DISTINCT ON expressions ({})",
+            keys.iter()
+                .map(|key| key.text.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Statements which can be built into a `Query`.
+pub trait BuildQuery {
+    fn build_query(self, options: BuildOptions) -> Result<Query, QueryBuildError>;
+
+    /// Build the statement with inlined parameters for the dialect of `options`. See [`Query::to_debug_sql`].
+    #[track_caller]
+    fn debug_sql(self, options: BuildOptions) -> Result<String, QueryBuildError>
+    where
+        Self: Sized,
+    {
+        let dialect = options.dialect;
+        Ok(self.build_query(options)?.to_debug_sql(dialect))
+    }
+}
+
+impl BuildQuery for Query {
+    /// A query is already built, so `options` are ignored.
+    fn build_query(self, _options: BuildOptions) -> Result<Query, QueryBuildError> {
+        Ok(self)
+    }
+}
+
+impl<QS, C, W, G, H, O, L, LM> BuildQuery for SelectBuilder<QS, W, C, G, H, O, L, LM>
+where
+    QS: BuildSql,
+    C: BuildSql + Columns,
+    W: BuildSql,
+    G: BuildSql,
+    H: BuildSql,
+    O: BuildSql,
+    L: BuildSql,
+    LM: BuildSql,
+{
+    #[track_caller]
+    fn build_query(self, options: BuildOptions) -> Result<Query, QueryBuildError> {
+        self.build_with(options)
+    }
+}
+
+impl<QS, W, C, G, H, O, L, LM> SelectBuilder<QS, W, C, G, H, O, L, LM>
+where
+    C: Columns,
+    C::SqlType: DecodeRow,
+{
+    /// Decode a result row of this query. Nullable columns become `Option`.
+    pub fn decode_row(&self, values: Vec<Value>) -> Result<RowOf<C>, DecodeError> {
+        C::SqlType::decode_row(values)
+    }
+}
+
+/// Renders the statement with inlined parameters for the dialect of `BuildOptions::default()`, MySQL.
+/// Use [`BuildQuery::debug_sql`] for other dialects.
+impl<QS, C, W, G, H, O, L, LM> fmt::Display for SelectBuilder<QS, W, C, G, H, O, L, LM>
+where
+    QS: BuildSql,
+    C: BuildSql + Columns,
+    W: BuildSql,
+    G: BuildSql,
+    H: BuildSql,
+    O: BuildSql,
+    L: BuildSql,
+    LM: BuildSql,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let options = BuildOptions::default();
+        let mut ctx = BuildContext::new(options.clone());
+        let query = Query::build_with_size_hint(self.statement_size_hint(), |buf, params| {
+            self.build_statement(&mut ctx, buf, params)
+        })
+        .map_err(|_| fmt::Error)?
+        .with_placeholders(ctx.placeholders);
+        f.write_str(&query.to_debug_sql(options.dialect))
+    }
 }
 
 impl<QS, C, W, G, H, O, L, LM> BuildSql for SelectBuilder<QS, W, C, G, H, O, L, LM>
 where
     QS: BuildSql,
-    C: BuildSql,
+    C: BuildSql + Columns,
     W: BuildSql,
     G: BuildSql,
     H: BuildSql,
@@ -824,18 +1336,20 @@ where
     L: BuildSql,
     LM: BuildSql,
 {
-    fn build_sql(&self, buf: &mut Vec<u8>, params: &mut Vec<Value>) -> Result<(), BuildSqlError> {
-        write!(buf, "(SELECT ")?;
-        self.columns.build_sql(buf, params)?;
-        self.sources.build_sql(buf, params)?;
-        self.filter.build_sql(buf, params)?;
-        self.group_by.build_sql(buf, params)?;
-        self.having.build_sql(buf, params)?;
-        self.order_by.build_sql(buf, params)?;
-        self.limit.build_sql(buf, params)?;
-        self.lock_mode.build_sql(buf, params)?;
-        write!(buf, ")")?;
-        Ok(())
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        ctx.write_subquery(buf, |ctx, buf| {
+            write!(buf, "SELECT ")?;
+            self.build_select_body(ctx, buf, params)
+        })
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.select_body_size_hint() + "(SELECT )".len()
     }
 }
 
@@ -851,10 +1365,10 @@ where
 
 impl<W, C, G, H, O, L, LM> QuerySource for SelectBuilder<EmptyFromClause, W, C, G, H, O, L, LM> {
     type Database = AnyDatabase;
-    type NullableSelf = Self;
+    type NullableSelf = NullableSource<Self>;
 
     fn nullable(self) -> Self::NullableSelf {
-        self
+        NullableSource(self)
     }
 }
 
@@ -864,13 +1378,17 @@ where
     QS: QuerySource<Database = DB>,
 {
     type Database = DB;
-    type NullableSelf = Self;
+    type NullableSelf = NullableSource<Self>;
 
     fn nullable(self) -> Self::NullableSelf {
-        self
+        NullableSource(self)
     }
 }
 
+impl<QS, W, C, G, H, O, L, LM> SourceNullability for SelectBuilder<QS, W, C, G, H, O, L, LM> {
+    type Nullability = NotNull;
+}
+
 impl<W, C, G, H, O, L, LM> IntoQuerySource
     for SelectBuilder<EmptyFromClause, W, C, G, H, O, L, LM>
 {
@@ -900,6 +1418,7 @@ where
 pub struct Query {
     sql: String,
     params: Vec<Value>,
+    placeholders: Option<Vec<usize>>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -911,22 +1430,69 @@ pub enum QueryBuildError {
 }
 
 impl Query {
-    pub fn build<F>(mut f: F) -> Result<Query, QueryBuildError>
+    /// Size hint used when the size of the query is unknown.
+    pub const DEFAULT_SIZE_HINT: SizeHint = SizeHint::new(128, 32);
+
+    pub fn build<F>(f: F) -> Result<Query, QueryBuildError>
     where
         F: FnMut(&mut Vec<u8>, &mut Vec<Value>) -> Result<(), BuildSqlError>,
     {
-        let num_of_params = 32;
-        let mut buf: Vec<u8> = Vec::with_capacity(128);
-        let mut params: Vec<Value> = Vec::with_capacity(num_of_params); // TODO: メモリの再アロケートを繰り返さない為にパラメータの数だけ最初から確保したほうがいい
+        Query::build_with_size_hint(Query::DEFAULT_SIZE_HINT, f)
+    }
+
+    /// Build a query allocating the buffers from `size_hint`.
+    pub fn build_with_size_hint<F>(size_hint: SizeHint, mut f: F) -> Result<Query, QueryBuildError>
+    where
+        F: FnMut(&mut Vec<u8>, &mut Vec<Value>) -> Result<(), BuildSqlError>,
+    {
+        let mut buf: Vec<u8> = Vec::with_capacity(size_hint.bytes);
+        let mut params: Vec<Value> = Vec::with_capacity(size_hint.params);
 
         f(&mut buf, &mut params)?;
 
         Ok(Query {
             sql: String::from_utf8(buf)?,
             params,
+            placeholders: None,
         })
     }
 
+    /// Build a query into buffers owned by the caller.
+    ///
+    /// `sql` and `params` are cleared before building, and their allocations are reused.
+    /// On error both are left empty, keeping their allocations.
+    pub fn build_into<F>(
+        sql: &mut String,
+        params: &mut Vec<Value>,
+        size_hint: SizeHint,
+        mut f: F,
+    ) -> Result<(), QueryBuildError>
+    where
+        F: FnMut(&mut Vec<u8>, &mut Vec<Value>) -> Result<(), BuildSqlError>,
+    {
+        let mut buf = std::mem::take(sql).into_bytes();
+        buf.clear();
+        buf.reserve(size_hint.bytes);
+        params.clear();
+        params.reserve(size_hint.params);
+
+        let result = f(&mut buf, params);
+        if result.is_err() {
+            buf.clear();
+        }
+        match String::from_utf8(buf) {
+            Ok(built) => *sql = built,
+            Err(e) => {
+                params.clear();
+                return Err(e.into());
+            }
+        }
+        if result.is_err() {
+            params.clear();
+        }
+        Ok(result?)
+    }
+
     pub fn sql(&self) -> &str {
         self.sql.as_str()
     }
@@ -934,6 +1500,47 @@ impl Query {
     pub fn params(&self) -> &[Value] {
         &self.params
     }
+
+    /// Byte offsets of the placeholders of `params()` in `sql()`, if they were tracked while building.
+    pub fn placeholders(&self) -> Option<&[usize]> {
+        self.placeholders.as_deref()
+    }
+
+    /// Set the byte offsets of the placeholders, e.g. from [`BuildContext::placeholders`].
+    pub fn with_placeholders(self, placeholders: Vec<usize>) -> Query {
+        Query {
+            placeholders: Some(placeholders),
+            ..self
+        }
+    }
+
+    /// SQL with every parameter inlined as a literal, for logging and debugging.
+    ///
+    /// Only the placeholders tracked while building are replaced, so a `?` in a literal or an operator is kept.
+    /// Without tracked placeholders, e.g. for [`Query::build`], every `?` is taken as one.
+    ///
+    /// **Do not execute the returned SQL.** Always execute `sql()` with `params()` bound.
+    pub fn to_debug_sql(&self, dialect: impl Dialect) -> String {
+        let dialect = dialect.kind();
+        let scanned: Vec<usize>;
+        let placeholders = match &self.placeholders {
+            Some(placeholders) => placeholders.as_slice(),
+            None => {
+                scanned = self.sql.match_indices('?').map(|(at, _)| at).collect();
+                &scanned
+            }
+        };
+        let mut sql = String::with_capacity(self.sql.len() + self.params.len() * 8);
+        let mut written = 0;
+        for (&at, value) in placeholders.iter().zip(&self.params) {
+            sql.push_str(&self.sql[written..at]);
+            // Writing to a String never fails.
+            let _ = dialect.write_literal(&mut sql, value);
+            written = at + "?".len();
+        }
+        sql.push_str(&self.sql[written..]);
+        sql
+    }
 }
 
 #[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
@@ -942,6 +1549,7 @@ pub enum Value {
     String(String),
     Int(i64),
     Uint(u64),
+    Bool(bool),
 }
 
 impl Expression for Value {
@@ -957,6 +1565,12 @@ impl From<&str> for Value {
     }
 }
 
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
 impl From<i64> for Value {
     fn from(value: i64) -> Self {
         Value::Int(value)
@@ -969,6 +1583,18 @@ impl From<u64> for Value {
     }
 }
 
+impl From<i32> for Value {
+    fn from(value: i32) -> Self {
+        Value::Int(value.into())
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
 impl<T> From<Option<T>> for Value
 where
     T: Into<Value>,
@@ -1000,10 +1626,17 @@ impl<T> BuildSql for T
 where
     T: Into<Value> + Clone,
 {
-    fn build_sql(&self, buf: &mut Vec<u8>, params: &mut Vec<Value>) -> Result<(), BuildSqlError> {
-        write!(buf, "?")?;
-        params.push((*self).clone().into());
-        Ok(())
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        ctx.write_param(buf, params, (*self).clone().into())
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::new(1, 1)
     }
 }
 
@@ -1090,6 +1723,155 @@ impl Expression for SqlUint {
     type Aggregation = NonAggregate;
 }
 
+/// Raw SQL fragment with bound parameters.
+///
+/// Only accepts `&'static str` so that values can not be formatted into the SQL.
+/// Every `?` is a placeholder, and their number must match the parameters.
+/// Use [`raw_sql!`](crate::raw_sql) to build one.
+#[derive(Debug, Clone)]
+pub struct RawSql<ST = SqlTypeAny> {
+    sql: &'static str,
+    params: Vec<Value>,
+    sql_type: PhantomData<ST>,
+}
+
+impl RawSql {
+    pub fn new(sql: &'static str, params: Vec<Value>) -> RawSql {
+        RawSql {
+            sql,
+            params,
+            sql_type: PhantomData,
+        }
+    }
+}
+
+impl<ST> RawSql<ST> {
+    /// Give the fragment a SQL type, e.g. `SqlTypeBool` to use it in `filter()`.
+    pub fn typed<T>(self) -> RawSql<T> {
+        RawSql {
+            sql: self.sql,
+            params: self.params,
+            sql_type: PhantomData,
+        }
+    }
+}
+
+impl<ST> Expression for RawSql<ST> {
+    type SqlType = ST;
+    type Term = Polynomial;
+    // The content is unknown, so treat it like `OR` to be parenthesized in `AND`.
+    type BoolOperation = BoolOr;
+    type Aggregation = NonAggregate;
+}
+
+impl<ST> AndOperatorMethod for RawSql<ST> where ST: Comparable<SqlTypeBool> {}
+
+impl<ST> OrOperatorMethod for RawSql<ST> where ST: Comparable<SqlTypeBool> {}
+
+impl<ST> NotOperatorMethod for RawSql<ST> where ST: Comparable<SqlTypeBool> {}
+
+impl<ST> RawSql<ST> {
+    /// Byte offsets of the `?` placeholders in the fragment,
+    /// leaving out those in quoted literals and identifiers and in comments.
+    fn placeholders(&self, dialect: DialectKind) -> Vec<usize> {
+        let mysql = dialect == DialectKind::MySql;
+        let mut placeholders = Vec::new();
+        let mut chars = self.sql.char_indices().peekable();
+        while let Some((at, c)) = chars.next() {
+            let next = chars.peek().map(|(_, next)| *next);
+            match (c, next) {
+                ('\'', _) | ('"', _) | ('`', _) => {
+                    while let Some((_, inner)) = chars.next() {
+                        if mysql && c != '`' && inner == '\\' {
+                            chars.next();
+                        } else if inner == c {
+                            // A doubled quote closes and reopens the literal.
+                            break;
+                        }
+                    }
+                }
+                // MySQL needs whitespace after `--`, as `1--1` is a subtraction.
+                ('-', Some('-'))
+                    if !mysql
+                        || self.sql[at + 2..]
+                            .chars()
+                            .next()
+                            .is_none_or(char::is_whitespace) =>
+                {
+                    chars.by_ref().find(|(_, c)| *c == '\n');
+                }
+                ('#', _) if mysql => {
+                    chars.by_ref().find(|(_, c)| *c == '\n');
+                }
+                ('/', Some('*')) => {
+                    chars.next();
+                    while let Some((_, inner)) = chars.next() {
+                        if inner == '*' && chars.peek().map(|(_, c)| *c) == Some('/') {
+                            chars.next();
+                            break;
+                        }
+                    }
+                }
+                ('?', _) => placeholders.push(at),
+                _ => {}
+            }
+        }
+        placeholders
+    }
+}
+
+impl<ST> BuildSql for RawSql<ST> {
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        let placeholders = self.placeholders(ctx.dialect());
+        if placeholders.len() != self.params.len() {
+            return Err(anyhow::anyhow!(
+                "raw SQL `{}` has {} placeholders but {} parameters",
+                self.sql,
+                placeholders.len(),
+                self.params.len()
+            )
+            .into());
+        }
+        let start = buf.len();
+        ctx.placeholders
+            .extend(placeholders.iter().map(|at| start + at));
+        write!(buf, "{}", self.sql)?;
+        for index in params.len()..params.len() + self.params.len() {
+            ctx.record_visit(|| VisitEvent::Param { index });
+        }
+        params.extend(self.params.iter().cloned());
+        Ok(())
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::new(self.sql.len(), self.params.len())
+    }
+}
+
+/// Build a [`RawSql`](crate::query_builder::RawSql) fragment from a string literal and bound values.
+///
+/// ```ignore
+/// raw_sql!("t1.created_at > now() - interval ? day", 7i64)
+/// raw_sql!(SqlTypeBool; "t1.flags & ? != 0", 4i64)
+/// ```
+#[macro_export]
+macro_rules! raw_sql {
+    ( $sql:literal $(, $param:expr )* $(,)* ) => {
+        $crate::query_builder::RawSql::new(
+            $sql,
+            vec![ $( $crate::query_builder::Value::from($param), )* ],
+        )
+    };
+    ( $sql_type:ty; $sql:literal $(, $param:expr )* $(,)* ) => {
+        $crate::raw_sql!($sql $(, $param )*).typed::<$sql_type>()
+    };
+}
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Hash)]
 pub struct Dual;
 
@@ -1101,2093 +1883,6967 @@ pub trait QuerySource {
     type NullableSelf: QuerySource<Database = Self::Database>;
 
     fn nullable(self) -> Self::NullableSelf;
-}
 
-pub trait IntoQuerySource {
-    type Database;
-    type QuerySource: QuerySource<Database = Self::Database>;
+    /// Column names written after the alias, e.g. `as t1(c1, c2)`.
+    fn column_aliases(&self) -> &[&'static str] {
+        &[]
+    }
 
-    fn into_query_source(self) -> Self::QuerySource;
-}
-
-impl<'a> IntoQuerySource for &'a str {
-    type Database = AnyDatabase;
-    type QuerySource = TableName<'a, AnyDatabase>;
-
-    fn into_query_source(self) -> Self::QuerySource {
-        TableName::new(self)
+    /// Index hints written after the alias. See [`IndexHintMethod`].
+    fn index_hints(&self) -> &[IndexHint] {
+        &[]
     }
-}
-
-#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Default, Hash, derive_more::Deref)]
-pub struct TableName<'a, DB> {
-    #[deref]
-    name: &'a str,
-    db: PhantomData<DB>,
-}
 
-impl<DB> TableName<'_, DB> {
-    pub fn new(name: &str) -> TableName<'_, DB> {
-        TableName {
-            name,
-            db: PhantomData,
-        }
+    /// Name of the table, if the source is one. See [`Visitor::visit_source`].
+    fn table_name(&self) -> Option<&str> {
+        None
     }
-}
 
-impl<'a, DB> From<&'a str> for TableName<'a, DB> {
-    fn from(value: &'a str) -> Self {
-        TableName::new(value)
+    /// Whether [`SourceScope`]s apply to the source. See [`unscoped`].
+    fn is_scoped(&self) -> bool {
+        true
     }
-}
 
-pub trait Joinable<DB> {
-    type Database;
+    /// The deletion timestamp column and the rows to read, if the table is soft-deleted. See [`SoftDelete`].
+    fn soft_delete(&self) -> Option<(&'static str, DeletedRows)> {
+        None
+    }
 }
 
-impl Joinable<AnyDatabase> for AnyDatabase {
-    type Database = AnyDatabase;
+/// Whether the columns of a source may be NULL regardless of the schema.
+pub trait SourceNullability {
+    type Nullability;
 }
 
-#[macro_export]
-macro_rules! impl_joinable {
-    ($ty:ty) => {
-        impl $crate::query_builder::Joinable<$ty> for $ty {
-            type Database = $ty;
-        }
-
-        impl $crate::query_builder::Joinable<$crate::query_builder::AnyDatabase> for $ty {
-            type Database = $ty;
-        }
-
-        impl $crate::query_builder::Joinable<$ty> for $crate::query_builder::AnyDatabase {
-            type Database = $ty;
-        }
-    };
-}
+/// A source on the nullable side of an outer join.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, derive_more::Deref)]
+pub struct NullableSource<QS>(QS);
 
-// 個別のテーブル型ではNullableSelfにOption<カラムの型>を返すメソッドを生やす。
-// left_outer_join等が呼ばれたらbuilderがnullableメソッドをコールする。
-impl<DB> QuerySource for TableName<'_, DB> {
-    type Database = DB;
+impl<QS> QuerySource for NullableSource<QS>
+where
+    QS: QuerySource,
+{
+    type Database = QS::Database;
     type NullableSelf = Self;
 
     fn nullable(self) -> Self::NullableSelf {
         self
     }
-}
 
-impl<DB> IntoQuerySource for TableName<'_, DB>
-where
-    DB: Clone,
-{
-    type Database = DB;
-    type QuerySource = Self;
+    fn column_aliases(&self) -> &[&'static str] {
+        self.0.column_aliases()
+    }
 
-    fn into_query_source(self) -> Self::QuerySource {
-        self
+    fn index_hints(&self) -> &[IndexHint] {
+        self.0.index_hints()
     }
-}
 
-impl<DB> BuildSql for TableName<'_, DB> {
-    fn build_sql(&self, buf: &mut Vec<u8>, _params: &mut Vec<Value>) -> Result<(), BuildSqlError> {
-        write!(buf, "{}", self.name)?;
-        Ok(())
+    fn table_name(&self) -> Option<&str> {
+        self.0.table_name()
     }
-}
 
-#[derive(Clone)]
-pub struct Column<ST = SqlTypeAny> {
-    table_name: SourceAlias,
-    column_name: String,
-    sql_type: ST,
-}
+    fn is_scoped(&self) -> bool {
+        self.0.is_scoped()
+    }
 
-impl<ST> Column<ST>
-where
-    ST: Default,
-{
-    pub fn new<T>(table_name: SourceAlias, column_name: T) -> Column<ST>
-    where
-        T: AsColumnName + Sized,
-    {
-        Column {
-            table_name,
-            column_name: column_name.as_column_name().to_string(),
-            sql_type: Default::default(),
-        }
+    fn soft_delete(&self) -> Option<(&'static str, DeletedRows)> {
+        self.0.soft_delete()
     }
 }
 
-impl<ST> Expression for Column<ST> {
-    type SqlType = ST;
-    type Term = Monomial;
-    type BoolOperation = NonBool;
-    type Aggregation = NonAggregate;
+impl<QS> SourceNullability for NullableSource<QS> {
+    type Nullability = MaybeNull;
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
-pub struct ColumnAlias<'a, T> {
-    value: T,
-    alias: Cow<'a, str>,
-}
+impl<QS> BuildSql for NullableSource<QS>
+where
+    QS: BuildSql,
+{
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        self.0.build_sql_with(ctx, buf, params)
+    }
 
-impl<'a, T> ColumnAlias<'a, T> {
-    pub fn new(value: T, alias: &'a str) -> ColumnAlias<'a, T> {
-        ColumnAlias {
-            value,
-            alias: Cow::from(alias),
-        }
+    fn size_hint(&self) -> SizeHint {
+        self.0.size_hint()
     }
 }
 
-impl<'a, T> Columns for ColumnAlias<'a, T>
+/// `LATERAL (subquery)`, a subquery source which may reference the sources joined before it.
+#[derive(Debug, Clone, derive_more::Deref)]
+pub struct Lateral<SQ>(SQ);
+
+/// Build a [`Lateral`] subquery. The builder passed to `subquery` names its sources `l1`, `l2`, ...
+/// so that they do not shadow the outer `t1`, `t2`, ...
+///
+/// ```ignore
+/// let (builder, t2) = builder.inner_join(
+///     lateral(|b| {
+///         let (b, l1) = b.source("posts");
+///         b.filter(l1.column("user_id").eq(t1.column("id")))
+///             .select(l1.column("title"))
+///             .order_by(Order::Desc(l1.column("created_at")))
+///             .limit(3)
+///     }),
+///     |_| True,
+/// );
+/// ```
+pub fn lateral<F, SQ>(subquery: F) -> Lateral<SQ>
 where
-    T: Expression,
+    F: FnOnce(synonym::EmptySelectBuilder) -> SQ,
+    SQ: QuerySource,
 {
-    type SqlType = T::SqlType;
-    type Aggregation = T::Aggregation;
+    let mut builder = synonym::EmptySelectBuilder::new();
+    builder.change_sources_alias_name("l");
+    Lateral(subquery(builder))
 }
 
-impl<T> BuildSql for ColumnAlias<'_, T>
+impl<SQ> QuerySource for Lateral<SQ>
 where
-    T: BuildSql,
+    SQ: QuerySource,
 {
-    fn build_sql(&self, buf: &mut Vec<u8>, params: &mut Vec<Value>) -> Result<(), BuildSqlError> {
-        self.value.build_sql(buf, params)?;
-        write!(buf, " as {}", self.alias)?;
-        Ok(())
+    type Database = SQ::Database;
+    type NullableSelf = NullableSource<Self>;
+
+    fn nullable(self) -> Self::NullableSelf {
+        NullableSource(self)
     }
 }
 
-#[derive(Clone)]
-pub struct SourceAliasName {
-    name: Rc<RefCell<&'static str>>,
+impl<SQ> SourceNullability for Lateral<SQ> {
+    type Nullability = NotNull;
 }
 
-impl SourceAliasName {
-    pub fn new(name: &'static str) -> Self {
-        SourceAliasName {
-            name: Rc::new(RefCell::new(name)),
-        }
+impl<SQ> IntoQuerySource for Lateral<SQ>
+where
+    SQ: QuerySource,
+{
+    type Database = SQ::Database;
+    type QuerySource = Self;
+
+    fn into_query_source(self) -> Self::QuerySource {
+        self
     }
+}
 
-    pub fn set(&mut self, new_name: &'static str) {
-        self.name.replace(new_name);
+impl<SQ> BuildSql for Lateral<SQ>
+where
+    SQ: BuildSql,
+{
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        if ctx.dialect() == DialectKind::Sqlite {
+            ctx.reject(anyhow::anyhow!("LATERAL is not supported by sqlite"))?;
+        }
+        write!(buf, "LATERAL ")?;
+        self.0.build_sql_with(ctx, buf, params)
     }
 
-    pub fn as_str(&self) -> &'static str {
-        RefCell::borrow(&self.name).borrow()
+    fn size_hint(&self) -> SizeHint {
+        self.0.size_hint() + "LATERAL ".len()
     }
 }
 
-impl Default for SourceAliasName {
-    fn default() -> Self {
-        Self::new("t")
-    }
+/// A table-valued function as a source, e.g. `generate_series(?, ?)`.
+///
+/// ```ignore
+/// table_function("generate_series", (Value::Int(1), Value::Int(10)))
+/// table_function("json_table", raw_sql!("?, '$[*]' COLUMNS (id INT PATH '$.id')", json))
+/// ```
+#[derive(Debug, Clone)]
+pub struct TableFunction<A> {
+    name: &'static str,
+    args: A,
 }
 
-#[derive(Clone)]
-pub struct SourceAlias {
-    name: SourceAliasName,
-    suffix_number: u8,
+pub fn table_function<A>(name: &'static str, args: A) -> TableFunction<A>
+where
+    A: BuildSql,
+{
+    TableFunction { name, args }
 }
 
-impl SourceAlias {
-    pub fn new(name: SourceAliasName, suffix_number: u8) -> Self {
-        SourceAlias {
-            name,
-            suffix_number,
-        }
-    }
+impl<A> QuerySource for TableFunction<A> {
+    type Database = AnyDatabase;
+    type NullableSelf = NullableSource<Self>;
 
-    pub fn change_name(&mut self, new_name: &'static str) {
-        self.name.set(new_name);
+    fn nullable(self) -> Self::NullableSelf {
+        NullableSource(self)
     }
 }
 
-impl ToString for SourceAlias {
-    fn to_string(&self) -> String {
-        format!("{}{}", self.name.as_str(), self.suffix_number)
-    }
+impl<A> SourceNullability for TableFunction<A> {
+    type Nullability = NotNull;
 }
 
-#[derive(Clone, derive_more::Deref)]
-pub struct QuerySourceRef<QS> {
-    #[deref]
-    source: QS,
-    alias: SourceAlias,
+impl<A> IntoQuerySource for TableFunction<A> {
+    type Database = AnyDatabase;
+    type QuerySource = Self;
+
+    fn into_query_source(self) -> Self::QuerySource {
+        self
+    }
 }
 
-impl<QS> QuerySourceRef<QS>
+impl<A> BuildSql for TableFunction<A>
 where
-    QS: QuerySource,
+    A: BuildSql,
 {
-    pub fn new(source: QS, alias: SourceAlias) -> QuerySourceRef<QS> {
-        QuerySourceRef { source, alias }
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        write!(buf, "{}(", self.name)?;
+        self.args.build_sql_with(ctx, buf, params)?;
+        write!(buf, ")")?;
+        Ok(())
     }
 
-    pub fn column<T>(&self, column_name: T) -> Column
-    where
-        T: AsColumnName + Sized,
-    {
-        Column::new(self.alias.clone(), column_name)
+    fn size_hint(&self) -> SizeHint {
+        self.args.size_hint() + self.name.len() + "()".len()
     }
+}
 
-    pub fn typed_column<T: Default>(&self, column_name: impl AsColumnName + Sized) -> Column<T> {
-        Column::new(self.alias.clone(), column_name)
-    }
+/// A Rust value bound as a parameter of the SQL type `SqlType`.
+pub trait IntoSqlValue {
+    type SqlType;
 
-    pub fn alias(&self) -> String {
-        self.alias.to_string()
-    }
+    fn into_value(self) -> Value;
 }
 
-impl<QS> QuerySource for QuerySourceRef<QS>
-where
-    QS: QuerySource,
-{
-    type Database = QS::Database;
-    type NullableSelf = QuerySourceRef<QS::NullableSelf>;
+macro_rules! impl_into_sql_value {
+    ( $( $ty:ty => $sql_type:ty ),* $(,)* ) => {
+        $(
+            impl IntoSqlValue for $ty {
+                type SqlType = $sql_type;
 
-    fn nullable(self) -> Self::NullableSelf {
-        QuerySourceRef {
-            source: self.source.nullable(),
-            alias: self.alias.clone(),
-        }
-    }
+                fn into_value(self) -> Value {
+                    Value::from(self)
+                }
+            }
+        )*
+    };
 }
 
-impl<QS> BuildSql for QuerySourceRef<QS>
+impl_into_sql_value!(
+    i64 => SqlTypeInt,
+    i32 => SqlTypeInt,
+    u64 => SqlTypeUint,
+    bool => SqlTypeBool,
+    &str => SqlTypeString,
+    String => SqlTypeString,
+);
+
+impl<T> IntoSqlValue for Option<T>
 where
-    QS: QuerySource + BuildSql,
+    T: IntoSqlValue,
 {
-    fn build_sql(&self, buf: &mut Vec<u8>, params: &mut Vec<Value>) -> Result<(), BuildSqlError> {
-        self.source
-            .build_sql(buf, params)
-            .map_err(anyhow::Error::from)?;
-        write!(buf, " as {}", self.alias.to_string()).map_err(anyhow::Error::from)?;
-        Ok(())
+    type SqlType = Nullable<T::SqlType>;
+
+    fn into_value(self) -> Value {
+        self.map_or(Value::Null, T::into_value)
     }
 }
 
-pub trait AsColumnName {
-    fn as_column_name(&self) -> &str;
-}
+/// A row of [`values_source`]: a value or a tuple of values.
+pub trait ValuesRow {
+    type SqlType;
 
-impl AsColumnName for &str {
-    fn as_column_name(&self) -> &str {
-        self
-    }
-}
+    const LEN: usize;
 
-impl<T> AsColumnName for &T
-where
-    T: AsColumnName,
-{
-    fn as_column_name(&self) -> &str {
-        (**self).as_column_name()
-    }
+    fn into_values(self) -> Vec<Value>;
 }
 
-impl<T> AsColumnName for &mut T
+impl<T> ValuesRow for T
 where
-    T: AsColumnName,
+    T: IntoSqlValue,
 {
-    fn as_column_name(&self) -> &str {
-        (**self).as_column_name()
+    type SqlType = T::SqlType;
+
+    const LEN: usize = 1;
+
+    fn into_values(self) -> Vec<Value> {
+        vec![self.into_value()]
     }
 }
 
-#[derive(Debug, Clone)]
-pub enum Join<L, R, ON> {
-    Inner(L, R, ON),
-    LeftOuter(L, R, ON),
-    RightOuter(L, R, ON),
-    Cross(L, R),
+/// SQL type of the `I`th column of a row type.
+pub trait ColumnAt<const I: usize> {
+    type SqlType;
 }
 
-impl<L, R, E> QuerySource for Join<L, R, E>
-where
-    L: QuerySource,
-    R: QuerySource,
-    L::Database: Joinable<R::Database>,
-    L::NullableSelf: QuerySource,
-    R::NullableSelf: QuerySource,
-    E: Expression<SqlType = SqlTypeBool> + Clone,
-{
-    type Database = <L::Database as Joinable<R::Database>>::Database;
-    type NullableSelf = Join<L::NullableSelf, R::NullableSelf, E>;
+macro_rules! impl_values_row_for_tuple {
+    ( $all:tt; $( $type_param:ident $field:tt ),+ ) => {
+        impl_values_row_for_tuple!(@row $all; $( $type_param $field ),+);
+        $( impl_values_row_for_tuple!(@column_at $all $type_param $field); )+
+    };
+    ( @row [ $( $all:ident ),+ ]; $( $type_param:ident $field:tt ),+ ) => {
+        impl<$( $all ),+> ValuesRow for ( $( $all, )+ )
+        where
+            $( $all: IntoSqlValue, )+
+        {
+            type SqlType = ( $( $all::SqlType, )+ );
 
-    fn nullable(self) -> Self::NullableSelf {
-        match self {
-            Join::Inner(l, r, on) => Join::Inner(l.nullable(), r.nullable(), on),
-            Join::LeftOuter(l, r, on) => Join::LeftOuter(l.nullable(), r.nullable(), on),
-            Join::RightOuter(l, r, on) => Join::RightOuter(l.nullable(), r.nullable(), on),
-            Join::Cross(l, r) => Join::Cross(l.nullable(), r.nullable()),
-        }
-    }
-}
+            const LEN: usize = [ $( stringify!($all) ),+ ].len();
 
-impl<L, R, E> BuildSql for Join<L, R, E>
-where
-    L: QuerySource + BuildSql,
-    R: QuerySource + BuildSql,
-    E: Expression<SqlType = SqlTypeBool> + BuildSql,
-{
-    fn build_sql(&self, buf: &mut Vec<u8>, params: &mut Vec<Value>) -> Result<(), BuildSqlError> {
-        match self {
-            Join::Inner(l, r, on) => (|| -> Result<(), anyhow::Error> {
-                l.build_sql(buf, params)?;
-                write!(buf, " JOIN ")?;
-                r.build_sql(buf, params)?;
-                write!(buf, " ON ")?;
-                on.build_sql(buf, params)?;
-                Ok(())
-            })()
-            .map_err(From::from),
-            Join::LeftOuter(l, r, on) => (|| -> Result<(), anyhow::Error> {
-                l.build_sql(buf, params)?;
-                write!(buf, " LEFT OUTER JOIN ")?;
-                r.build_sql(buf, params)?;
-                write!(buf, " ON ")?;
-                on.build_sql(buf, params)?;
-                Ok(())
-            })()
-            .map_err(From::from),
-            Join::RightOuter(l, r, on) => (|| -> Result<(), anyhow::Error> {
-                l.build_sql(buf, params)?;
-                write!(buf, " RIGHT OUTER JOIN ")?;
-                r.build_sql(buf, params)?;
-                write!(buf, " ON ")?;
-                on.build_sql(buf, params)?;
-                Ok(())
-            })()
-            .map_err(From::from),
-            Join::Cross(l, r) => (|| -> Result<(), anyhow::Error> {
-                l.build_sql(buf, params)?;
-                write!(buf, " CROSS JOIN ")?;
-                r.build_sql(buf, params)?;
-                Ok(())
-            })()
-            .map_err(From::from),
+            fn into_values(self) -> Vec<Value> {
+                vec![ $( self.$field.into_value() ),+ ]
+            }
         }
-    }
+    };
+    ( @column_at [ $( $all:ident ),+ ] $type_param:ident $field:tt ) => {
+        impl<$( $all ),+> ColumnAt<$field> for ( $( $all, )+ ) {
+            type SqlType = $type_param;
+        }
+    };
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
-pub struct Monomial;
-
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
-pub struct Polynomial;
-
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Hash)]
-pub struct BoolAnd;
+impl_values_row_for_tuple!([A]; A 0);
+impl_values_row_for_tuple!([A, B]; A 0, B 1);
+impl_values_row_for_tuple!([A, B, C]; A 0, B 1, C 2);
+impl_values_row_for_tuple!([A, B, C, D]; A 0, B 1, C 2, D 3);
+impl_values_row_for_tuple!([A, B, C, D, E]; A 0, B 1, C 2, D 3, E 4);
+impl_values_row_for_tuple!([A, B, C, D, E, F]; A 0, B 1, C 2, D 3, E 4, F 5);
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Hash)]
-pub struct BoolOr;
+macro_rules! impl_column_at_for_sql_type {
+    ( $( $ty:ty ),* $(,)* ) => {
+        $(
+            impl ColumnAt<0> for $ty {
+                type SqlType = $ty;
+            }
+        )*
+    };
+}
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Hash)]
-pub struct BoolMono;
+impl_column_at_for_sql_type!(
+    SqlTypeAny,
+    SqlTypeString,
+    SqlTypeInt,
+    SqlTypeUint,
+    SqlTypeBool,
+    SqlTypeJson
+);
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Hash)]
-pub struct NonBool;
+impl<ST> ColumnAt<0> for Nullable<ST> {
+    type SqlType = Nullable<ST>;
+}
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Hash)]
-pub struct Aggregate;
+/// An in-memory list of rows as a source: `(VALUES (?, ?), (?, ?)) as t1(c1, c2)`.
+#[derive(Debug, Clone)]
+pub struct ValuesSource<ST> {
+    columns: Vec<&'static str>,
+    rows: Vec<Vec<Value>>,
+    sql_type: PhantomData<ST>,
+}
+
+/// Build a [`ValuesSource`] of `rows` with the column names `columns`, one for each value of a row.
+///
+/// ```ignore
+/// let (builder, t2) = builder.inner_join(
+///     values_source(["id", "label"], vec![(1i64, "a"), (2, "b")]),
+///     |t2| t2.value_column::<0>().eq(t1.typed_column::<SqlTypeInt>("id")),
+/// );
+/// ```
+///
+/// ```compile_fail
+/// use aqueryon::query_builder::*;
+///
+/// let (builder, t1) = EmptySelectBuilder::new().source(values_source(["id"], vec![(1i64, "a")]));
+/// ```
+pub fn values_source<R, I, const N: usize>(
+    columns: [&'static str; N],
+    rows: I,
+) -> ValuesSource<R::SqlType>
+where
+    R: ValuesRow,
+    I: IntoIterator<Item = R>,
+{
+    #[allow(clippy::let_unit_value)]
+    let () = ColumnCount::<R, N>::MATCHES;
+    ValuesSource {
+        columns: columns.to_vec(),
+        rows: rows.into_iter().map(R::into_values).collect(),
+        sql_type: PhantomData,
+    }
+}
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Hash)]
-pub struct NonAggregate;
+/// Fails to compile unless a row of `R` has `N` values.
+struct ColumnCount<R, const N: usize>(PhantomData<R>);
 
-pub trait Aggregation<T> {
-    type Output;
+impl<R: ValuesRow, const N: usize> ColumnCount<R, N> {
+    const MATCHES: () = assert!(
+        N == R::LEN,
+        "values_source needs a column name for each value"
+    );
 }
 
-impl Aggregation<Aggregate> for NonAggregate {
-    type Output = Aggregate;
-}
+impl<ST> QuerySource for ValuesSource<ST> {
+    type Database = AnyDatabase;
+    type NullableSelf = NullableSource<Self>;
 
-impl Aggregation<NonAggregate> for Aggregate {
-    type Output = Aggregate;
-}
+    fn nullable(self) -> Self::NullableSelf {
+        NullableSource(self)
+    }
 
-impl Aggregation<Aggregate> for Aggregate {
-    type Output = Aggregate;
+    fn column_aliases(&self) -> &[&'static str] {
+        &self.columns
+    }
 }
 
-impl Aggregation<NonAggregate> for NonAggregate {
-    type Output = NonAggregate;
+impl<ST> SourceNullability for ValuesSource<ST> {
+    type Nullability = NotNull;
 }
 
-impl Aggregation<()> for Aggregate {
-    type Output = Aggregate;
-}
+impl<ST> IntoQuerySource for ValuesSource<ST> {
+    type Database = AnyDatabase;
+    type QuerySource = Self;
 
-impl Aggregation<()> for NonAggregate {
-    type Output = NonAggregate;
+    fn into_query_source(self) -> Self::QuerySource {
+        self
+    }
 }
 
-impl<A, B, C> Aggregation<(B, C)> for A
-where
-    A: Aggregation<B>,
-    A::Output: Aggregation<C>,
-{
-    type Output = <A::Output as Aggregation<C>>::Output;
+impl<ST> BuildSql for ValuesSource<ST> {
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        if self.rows.is_empty() {
+            return Err(anyhow::anyhow!("values_source needs at least one row").into());
+        }
+        let dialect = ctx.dialect();
+        ctx.write_subquery(buf, |ctx, buf| {
+            for (i, row) in self.rows.iter().enumerate() {
+                let (open, close) = match (dialect, i) {
+                    // SQLite cannot name the columns of VALUES, so name them in a compound SELECT.
+                    (DialectKind::Sqlite, 0) => {
+                        write!(buf, "SELECT ")?;
+                        for (j, (column, value)) in self.columns.iter().zip(row).enumerate() {
+                            let separator = if j == 0 { "" } else { ", " };
+                            write!(buf, "{}", separator)?;
+                            ctx.write_param(buf, params, value.clone())?;
+                            write!(buf, " AS {}", column)?;
+                        }
+                        continue;
+                    }
+                    (DialectKind::Sqlite, _) => (" UNION ALL SELECT ", ""),
+                    (DialectKind::MySql, 0) => ("VALUES ROW(", ")"),
+                    (DialectKind::MySql, _) => (", ROW(", ")"),
+                    (DialectKind::Postgres, 0) => ("VALUES (", ")"),
+                    (DialectKind::Postgres, _) => (", (", ")"),
+                };
+                write!(buf, "{}", open)?;
+                for (j, value) in row.iter().enumerate() {
+                    if j > 0 {
+                        write!(buf, ", ")?;
+                    }
+                    ctx.write_param(buf, params, value.clone())?;
+                }
+                write!(buf, "{}", close)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        let values: usize = self.rows.iter().map(Vec::len).sum();
+        SizeHint::new(
+            "(VALUES )".len() + values * ", ".len() + self.rows.len() * "ROW(), ".len(),
+            values,
+        )
+    }
 }
 
-pub trait Expression {
+/// Sources with the column list of a [`ValuesSource`].
+pub trait ValuesColumns {
     type SqlType;
-    type Term;
-    type BoolOperation;
-    type Aggregation;
-}
 
-/// Only for cross join.
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Hash)]
-pub struct BlankBoolExpression;
+    fn column_name(&self, index: usize) -> &'static str;
 
-impl Expression for BlankBoolExpression {
-    type SqlType = SqlTypeBool;
-    type Term = Monomial;
-    type BoolOperation = ();
-    type Aggregation = NonAggregate;
+    fn column_index(&self, name: &str) -> Option<usize>;
 }
 
-impl BuildSql for BlankBoolExpression {
-    fn build_sql(&self, _buf: &mut Vec<u8>, _params: &mut Vec<Value>) -> Result<(), BuildSqlError> {
-        Ok(())
+impl<ST> ValuesColumns for ValuesSource<ST> {
+    type SqlType = ST;
+
+    fn column_name(&self, index: usize) -> &'static str {
+        self.columns[index]
     }
-}
 
-#[derive(Debug, Clone)]
-pub enum Order<E> {
-    Asc(E),
-    Desc(E),
+    fn column_index(&self, name: &str) -> Option<usize> {
+        self.columns.iter().position(|column| *column == name)
+    }
 }
 
-impl<E> BuildSql for Order<E>
+impl<QS> ValuesColumns for NullableSource<QS>
 where
-    E: BuildSql,
+    QS: ValuesColumns,
 {
-    fn build_sql(&self, buf: &mut Vec<u8>, params: &mut Vec<Value>) -> Result<(), BuildSqlError> {
-        match self {
-            Order::Asc(expr) => {
-                expr.build_sql(buf, params)?;
-                write!(buf, " ASC")?;
-            }
-            Order::Desc(expr) => {
-                expr.build_sql(buf, params)?;
-                write!(buf, " DESC")?;
-            }
-        }
-        Ok(())
+    type SqlType = QS::SqlType;
+
+    fn column_name(&self, index: usize) -> &'static str {
+        self.0.column_name(index)
+    }
+
+    fn column_index(&self, name: &str) -> Option<usize> {
+        self.0.column_index(name)
     }
 }
 
-pub trait Orders {}
+pub trait IntoQuerySource {
+    type Database;
+    type QuerySource: QuerySource<Database = Self::Database>;
 
-impl<E> Orders for Order<E> where E: Expression {}
+    fn into_query_source(self) -> Self::QuerySource;
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
-pub struct Limit {
-    pub offset: Option<usize>,
-    pub row_count: usize,
+    /// Alias given by [`source_as`] instead of a generated one.
+    fn explicit_alias(&self) -> Option<&'static str> {
+        None
+    }
 }
 
-impl From<usize> for Limit {
-    fn from(row_count: usize) -> Self {
-        Limit {
-            offset: None,
-            row_count,
-        }
+impl<'a> IntoQuerySource for &'a str {
+    type Database = AnyDatabase;
+    type QuerySource = TableName<'a, AnyDatabase>;
+
+    fn into_query_source(self) -> Self::QuerySource {
+        TableName::new(self)
     }
 }
 
-impl From<(usize, usize)> for Limit {
-    fn from((offset, row_count): (usize, usize)) -> Self {
-        Limit {
-            offset: Some(offset),
-            row_count,
-        }
-    }
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Default, Hash, derive_more::Deref)]
+pub struct TableName<'a, DB> {
+    #[deref]
+    name: &'a str,
+    db: PhantomData<DB>,
 }
 
-impl BuildSql for Limit {
-    fn build_sql(&self, buf: &mut Vec<u8>, params: &mut Vec<Value>) -> Result<(), BuildSqlError> {
-        if let Some(offset) = self.offset {
-            write!(buf, "?, ")?;
-            params.push((offset as i64).into());
+impl<DB> TableName<'_, DB> {
+    pub fn new(name: &str) -> TableName<'_, DB> {
+        TableName {
+            name,
+            db: PhantomData,
         }
+    }
+}
 
-        write!(buf, "?")?;
-        params.push((self.row_count as i64).into());
+impl<'a, DB> TableName<'a, DB> {
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+}
 
-        Ok(())
+impl<'a, DB> From<&'a str> for TableName<'a, DB> {
+    fn from(value: &'a str) -> Self {
+        TableName::new(value)
     }
 }
 
-pub trait Columns {
-    type SqlType;
-    type Aggregation;
+pub trait Joinable<DB> {
+    type Database;
 }
 
-impl<E: Expression> Columns for E {
-    type SqlType = E::SqlType;
-    type Aggregation = E::Aggregation;
+impl Joinable<AnyDatabase> for AnyDatabase {
+    type Database = AnyDatabase;
 }
 
-#[derive(Debug, Clone)]
-pub struct Distinct<T>(T);
+#[macro_export]
+macro_rules! impl_joinable {
+    ($ty:ty) => {
+        impl $crate::query_builder::Joinable<$ty> for $ty {
+            type Database = $ty;
+        }
 
-impl<T> Distinct<T> {
-    pub fn new(x: T) -> Distinct<T> {
-        Distinct(x)
-    }
-}
+        impl $crate::query_builder::Joinable<$crate::query_builder::AnyDatabase> for $ty {
+            type Database = $ty;
+        }
 
-impl<T> Columns for Distinct<T>
-where
-    T: Columns<Aggregation = NonAggregate>,
-{
-    type SqlType = T::SqlType;
-    type Aggregation = T::Aggregation;
+        impl $crate::query_builder::Joinable<$ty> for $crate::query_builder::AnyDatabase {
+            type Database = $ty;
+        }
+    };
 }
 
-impl<T: BuildSql> BuildSql for Distinct<T> {
-    fn build_sql(&self, buf: &mut Vec<u8>, params: &mut Vec<Value>) -> Result<(), BuildSqlError> {
-        write!(buf, "DISTINCT ")?;
-        self.0.build_sql(buf, params)
+// left_outer_join等が呼ばれたらbuilderがnullableメソッドをコールし、
+// NullableSourceで包まれたソースのtyped_columnはNullable<カラムの型>を返す。
+impl<'a, DB> QuerySource for TableName<'a, DB> {
+    type Database = DB;
+    type NullableSelf = NullableSource<TableName<'a, DB>>;
+
+    fn nullable(self) -> Self::NullableSelf {
+        NullableSource(self)
+    }
+
+    fn table_name(&self) -> Option<&str> {
+        Some(self.name)
     }
 }
 
-/// Build SQL string as a part of SQL.
-pub trait BuildSql {
-    fn build_sql(&self, buf: &mut Vec<u8>, params: &mut Vec<Value>) -> Result<(), BuildSqlError>;
+impl<DB> SourceNullability for TableName<'_, DB> {
+    type Nullability = NotNull;
 }
 
-#[derive(Debug, thiserror::Error)]
-pub enum BuildSqlError {
-    #[error("Failed to build sql: {0}")]
-    IoError(#[from] std::io::Error),
-    #[error("Failed to build sql: {0}")]
-    AnyError(#[from] anyhow::Error),
+impl<DB> IntoQuerySource for TableName<'_, DB>
+where
+    DB: Clone,
+{
+    type Database = DB;
+    type QuerySource = Self;
+
+    fn into_query_source(self) -> Self::QuerySource {
+        self
+    }
 }
 
-impl<ST> BuildSql for Column<ST> {
-    fn build_sql(&self, buf: &mut Vec<u8>, _params: &mut Vec<Value>) -> Result<(), BuildSqlError> {
-        write!(buf, "{}.{}", self.table_name.to_string(), self.column_name)?;
+impl<DB> BuildSql for TableName<'_, DB> {
+    fn build_sql_with(
+        &self,
+        _ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        _params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        write!(buf, "{}", self.name)?;
         Ok(())
     }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::bytes(self.name.len())
+    }
 }
 
-#[derive(Debug, Clone)]
-pub struct Record<T> {
-    columns: T,
+/// A table known at compile time, e.g. one generated by [`codegen`](crate::codegen).
+pub trait TableMarker {
+    type Database;
+    const NAME: &'static str;
+}
+
+/// Source of the table `T`, which column accessors of other tables reject. See [`OfTable`].
+///
+/// ```compile_fail
+/// use aqueryon::query_builder::*;
+///
+/// #[derive(Debug, Clone, Copy, Default)]
+/// struct Users;
+/// impl TableMarker for Users {
+///     type Database = AnyDatabase;
+///     const NAME: &'static str = "users";
+/// }
+///
+/// #[derive(Debug, Clone, Copy, Default)]
+/// struct Posts;
+/// impl TableMarker for Posts {
+///     type Database = AnyDatabase;
+///     const NAME: &'static str = "posts";
+/// }
+///
+/// fn title<S: QuerySource + OfTable<Posts>>(source: &QuerySourceRef<S>) -> Column {
+///     source.column("title")
+/// }
+///
+/// let (_, t1) = EmptySelectBuilder::new().source(TypedTable::<Users>::new());
+/// title(&t1);
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub struct TypedTable<T>(PhantomData<T>);
+
+impl<T> TypedTable<T> {
+    pub fn new() -> TypedTable<T> {
+        TypedTable(PhantomData)
+    }
 }
 
-impl<T> Record<T> {
-    pub fn new(columns: T) -> Record<T> {
-        Record { columns }
+impl<T: TableMarker> QuerySource for TypedTable<T> {
+    type Database = T::Database;
+    type NullableSelf = NullableSource<TypedTable<T>>;
+
+    fn nullable(self) -> Self::NullableSelf {
+        NullableSource(self)
+    }
+
+    fn table_name(&self) -> Option<&str> {
+        Some(T::NAME)
     }
 }
 
-impl<A> Columns for (A,)
+impl<T> SourceNullability for TypedTable<T> {
+    type Nullability = NotNull;
+}
+
+impl<T> IntoQuerySource for TypedTable<T>
 where
-    A: Expression,
+    T: TableMarker,
 {
-    type SqlType = (A::SqlType,);
-    type Aggregation = A::Aggregation;
+    type Database = T::Database;
+    type QuerySource = Self;
+
+    fn into_query_source(self) -> Self::QuerySource {
+        self
+    }
 }
 
-impl<A: Expression> Expression for Record<(A,)> {
-    type SqlType = A::SqlType;
-    type Term = A::Term;
-    type BoolOperation = A::BoolOperation;
-    type Aggregation = A::Aggregation;
+impl<T: TableMarker> BuildSql for TypedTable<T> {
+    fn build_sql_with(
+        &self,
+        _ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        _params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        write!(buf, "{}", T::NAME)?;
+        Ok(())
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::bytes(T::NAME.len())
+    }
 }
 
-impl<A> Orders for (Order<A>,) where A: Expression {}
+/// Sources reading the table `T`, also on the nullable side of an outer join
+/// and wrapped in [`soft_delete`], [`unscoped`] or index hints.
+pub trait OfTable<T> {}
 
-impl<A> BuildSql for (A,)
+impl<T> OfTable<T> for TypedTable<T> {}
+
+impl<T, QS: OfTable<T>> OfTable<T> for NullableSource<QS> {}
+
+impl<T, QS: OfTable<T>> OfTable<T> for IndexHinted<QS> {}
+
+impl<T, QS: OfTable<T>> OfTable<T> for Unscoped<QS> {}
+
+impl<T, QS: OfTable<T>> OfTable<T> for SoftDelete<QS> {}
+
+#[derive(Clone)]
+pub struct Column<ST = SqlTypeAny> {
+    table_name: SourceAlias,
+    column_name: String,
+    sql_type: ST,
+}
+
+impl<ST> Column<ST>
 where
-    A: BuildSql,
+    ST: Default,
 {
-    fn build_sql(&self, buf: &mut Vec<u8>, params: &mut Vec<Value>) -> Result<(), BuildSqlError> {
-        self.0.build_sql(buf, params)
+    pub fn new<T>(table_name: SourceAlias, column_name: T) -> Column<ST>
+    where
+        T: AsColumnName + Sized,
+    {
+        Column {
+            table_name,
+            column_name: column_name.as_column_name().to_string(),
+            sql_type: Default::default(),
+        }
     }
 }
 
-macro_rules! recursive_aggregation {
-    ( $first_ty:ty, $( $ty:ty ),* $(,)* ) => {
-        ($first_ty, recursive_aggregation!( $($ty,)* ))
-    };
+impl<ST> Expression for Column<ST> {
+    type SqlType = ST;
+    type Term = Monomial;
+    type BoolOperation = NonBool;
+    type Aggregation = NonAggregate;
+}
 
-    () => {
-        ()
-    };
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ColumnAlias<'a, T> {
+    value: T,
+    alias: Cow<'a, str>,
 }
 
-macro_rules! impl_traits_for_tuple {
-    ($type_paramA:ident, $field0:tt $(,$type_param:ident, $field:tt)*) => {
-        impl<$type_paramA $(, $type_param)*> Columns for ($type_paramA $(, $type_param)*)
-        where
-            $type_paramA: Columns,
-            $($type_param: Columns,)*
-            <$type_paramA>::Aggregation: Aggregation<recursive_aggregation!( $( <$type_param>::Aggregation, )* )>,
-        {
-            type SqlType = ( $type_paramA::SqlType, $( $type_param::SqlType, )* );
-            type Aggregation = <<$type_paramA>::Aggregation as Aggregation<recursive_aggregation!( $( <$type_param>::Aggregation, )* )>>::Output;
+impl<'a, T> ColumnAlias<'a, T> {
+    pub fn new(value: T, alias: &'a str) -> ColumnAlias<'a, T> {
+        ColumnAlias {
+            value,
+            alias: Cow::from(alias),
         }
+    }
 
-        impl<$type_paramA $(, $type_param)*> Expression for Record<($type_paramA $(, $type_param)*)>
-        where
-            $type_paramA: Expression,
-            $($type_param: Expression,)*
-            <$type_paramA>::Aggregation: Aggregation<recursive_aggregation!( $( <$type_param>::Aggregation, )* )>,
-        {
-            type SqlType = ( $type_paramA::SqlType, $( $type_param::SqlType, )* );
-            type Term = Polynomial;
-            type BoolOperation = BoolMono;
-            type Aggregation = <<$type_paramA>::Aggregation as Aggregation<recursive_aggregation!( $( <$type_param>::Aggregation, )* )>>::Output;
+    /// Refer to this column by its alias, e.g. `Order::Desc(total.alias_ref())`.
+    pub fn alias_ref(&self) -> AliasRef {
+        AliasRef(self.alias.to_string())
+    }
+}
+
+impl<'a, T> Columns for ColumnAlias<'a, T>
+where
+    T: Expression,
+{
+    type SqlType = T::SqlType;
+    type Aggregation = T::Aggregation;
+
+    fn output_columns(&self) -> Vec<Option<String>> {
+        vec![Some(self.alias.to_string())]
+    }
+}
+
+impl<T> BuildSql for ColumnAlias<'_, T>
+where
+    T: BuildSql,
+{
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        self.value.build_sql_with(ctx, buf, params)?;
+        write!(buf, " as {}", self.alias)?;
+        Ok(())
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.value.size_hint() + " as ".len() + self.alias.len()
+    }
+}
+
+#[derive(Clone)]
+pub struct SourceAliasName {
+    name: Rc<RefCell<&'static str>>,
+}
+
+impl SourceAliasName {
+    pub fn new(name: &'static str) -> Self {
+        SourceAliasName {
+            name: Rc::new(RefCell::new(name)),
         }
+    }
 
-        impl<$type_paramA $(, $type_param)*> Orders for (Order<$type_paramA> $(, Order<$type_param>)*)
-        where
-            $type_paramA: Expression,
-            $($type_param: Expression,)*
-        {}
+    pub fn set(&mut self, new_name: &'static str) {
+        self.name.replace(new_name);
+    }
 
-        impl<$type_paramA $(, $type_param)*> BuildSql for ($type_paramA $(, $type_param)*)
-        where
-            $type_paramA: BuildSql,
-            $($type_param: BuildSql,)*
-        {
-            fn build_sql(&self, buf: &mut Vec<u8>, params: &mut Vec<Value>) -> Result<(), BuildSqlError> {
-                self.$field0.build_sql(buf, params)?;
+    pub fn as_str(&self) -> &'static str {
+        RefCell::borrow(&self.name).borrow()
+    }
+}
 
-                $(
-                    write!(buf, ", ")?;
-                    self.$field.build_sql(buf, params)?;
-                )*
+impl Default for SourceAliasName {
+    fn default() -> Self {
+        Self::new("t")
+    }
+}
 
-                Ok(())
-            }
+#[derive(Clone)]
+pub struct SourceAlias {
+    name: SourceAliasName,
+    suffix_number: u32,
+    explicit: Option<&'static str>,
+}
+
+impl SourceAlias {
+    pub fn new(name: SourceAliasName, suffix_number: u32) -> Self {
+        SourceAlias {
+            name,
+            suffix_number,
+            explicit: None,
         }
+    }
 
-        impl<$type_paramA $(, $type_param)*> BuildSql for Record<($type_paramA $(, $type_param)*)>
-        where
-            $type_paramA: BuildSql,
-            $($type_param: BuildSql,)*
-        {
-            fn build_sql(&self, buf: &mut Vec<u8>, params: &mut Vec<Value>) -> Result<(), BuildSqlError> {
-                write!(buf, "(")?; // Rowって付けた方がいい？
-                self.columns.$field0.build_sql(buf, params)?;
-                $(
-                    write!(buf, ", ")?;
-                    self.columns.$field.build_sql(buf, params)?;
-                )*
-                write!(buf, ")")?;
-                Ok(())
+    /// Use `explicit` instead of the generated name if given.
+    pub fn with_explicit(self, explicit: Option<&'static str>) -> Self {
+        SourceAlias { explicit, ..self }
+    }
+
+    pub fn change_name(&mut self, new_name: &'static str) {
+        self.name.set(new_name);
+    }
+
+    /// Byte length of the alias.
+    fn len(&self) -> usize {
+        match self.explicit {
+            Some(explicit) => explicit.len(),
+            None => {
+                let digits = self.suffix_number.checked_ilog10().unwrap_or(0) as usize + 1;
+                self.name.as_str().len() + digits
             }
         }
     }
+
+    /// Identifies the source regardless of how the alias is rendered.
+    fn id(&self) -> AliasId {
+        (Rc::as_ptr(&self.name.name) as usize, self.suffix_number)
+    }
 }
 
-// Increase if needed.
-impl_traits_for_tuple!(A, 0, B, 1);
-impl_traits_for_tuple!(A, 0, B, 1, C, 2);
-impl_traits_for_tuple!(A, 0, B, 1, C, 2, D, 3);
-impl_traits_for_tuple!(A, 0, B, 1, C, 2, D, 3, E, 4);
-impl_traits_for_tuple!(A, 0, B, 1, C, 2, D, 3, E, 4, F, 5);
-impl_traits_for_tuple!(A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6);
-impl_traits_for_tuple!(A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7);
-impl_traits_for_tuple!(A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8);
-impl_traits_for_tuple!(A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9);
-impl_traits_for_tuple!(A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10);
-impl_traits_for_tuple!(A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
-    AC, 28
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
-    AC, 28, AD, 29
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
-    AC, 28, AD, 29, AE, 30
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
-    AC, 28, AD, 29, AE, 30, AF, 31
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
-    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
-    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
-    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
-    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
-    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
-    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
-    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37, AM, 38
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
-    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37, AM, 38, AN, 39
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
-    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37, AM, 38, AN, 39,
-    AO, 40
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
-    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37, AM, 38, AN, 39,
-    AO, 40, AP, 41
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
-    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37, AM, 38, AN, 39,
-    AO, 40, AP, 41, AQ, 42
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
-    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37, AM, 38, AN, 39,
-    AO, 40, AP, 41, AQ, 42, AR, 43
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
-    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37, AM, 38, AN, 39,
-    AO, 40, AP, 41, AQ, 42, AR, 43, AS, 44
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
-    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37, AM, 38, AN, 39,
-    AO, 40, AP, 41, AQ, 42, AR, 43, AS, 44, AT, 45
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
-    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37, AM, 38, AN, 39,
-    AO, 40, AP, 41, AQ, 42, AR, 43, AS, 44, AT, 45, AU, 46
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
-    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37, AM, 38, AN, 39,
-    AO, 40, AP, 41, AQ, 42, AR, 43, AS, 44, AT, 45, AU, 46, AV, 47
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
-    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37, AM, 38, AN, 39,
-    AO, 40, AP, 41, AQ, 42, AR, 43, AS, 44, AT, 45, AU, 46, AV, 47, AW, 48
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
-    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37, AM, 38, AN, 39,
-    AO, 40, AP, 41, AQ, 42, AR, 43, AS, 44, AT, 45, AU, 46, AV, 47, AW, 48, AX, 49
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
-    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37, AM, 38, AN, 39,
-    AO, 40, AP, 41, AQ, 42, AR, 43, AS, 44, AT, 45, AU, 46, AV, 47, AW, 48, AX, 49, AY, 50
-);
-impl_traits_for_tuple!(
-    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
-    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
-    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37, AM, 38, AN, 39,
-    AO, 40, AP, 41, AQ, 42, AR, 43, AS, 44, AT, 45, AU, 46, AV, 47, AW, 48, AX, 49, AY, 50, AZ, 51
-);
+impl ToString for SourceAlias {
+    fn to_string(&self) -> String {
+        match self.explicit {
+            Some(explicit) => explicit.to_string(),
+            None => format!("{}{}", self.name.as_str(), self.suffix_number),
+        }
+    }
+}
+
+type AliasId = (usize, u32);
 
+/// A source with an explicit alias instead of a generated `t1`, `t2`, ...
 #[derive(Debug, Clone)]
-pub struct Row<T> {
-    columns: T,
+pub struct AliasedSource<T> {
+    source: T,
+    alias: &'static str,
 }
 
-impl<T: Columns> Row<T> {
-    pub fn new(columns: T) -> Row<T> {
-        Row { columns }
-    }
+/// Alias `source` as `alias`, e.g. `builder.source(source_as("user", "u"))`.
+pub fn source_as<T>(source: T, alias: &'static str) -> AliasedSource<T>
+where
+    T: IntoQuerySource,
+{
+    AliasedSource { source, alias }
 }
 
-/// 事前にコードレベルでSQL上での型が確定できない場合に使用する。
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
-pub struct SqlTypeAny;
+impl<T> IntoQuerySource for AliasedSource<T>
+where
+    T: IntoQuerySource,
+{
+    type Database = T::Database;
+    type QuerySource = T::QuerySource;
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
-pub struct SqlTypeString;
+    fn into_query_source(self) -> Self::QuerySource {
+        self.source.into_query_source()
+    }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
-pub struct SqlTypeInt;
+    fn explicit_alias(&self) -> Option<&'static str> {
+        Some(self.alias)
+    }
+}
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
-pub struct SqlTypeUint;
+/// `USE INDEX`, `FORCE INDEX` or `IGNORE INDEX` of MySQL.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum IndexHintKind {
+    Use,
+    Force,
+    Ignore,
+}
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
-pub struct SqlTypeBool;
+/// A MySQL identifier in backticks, e.g. an index name.
+fn quote_identifier(name: &str) -> String {
+    format!("`{}`", name.replace('`', "``"))
+}
 
-pub trait Comparable<T> {}
+/// An index hint written after the alias of a source, e.g. ``users as t1 FORCE INDEX (`idx_email`)``.
+///
+/// `USE INDEX ()` uses no index, but `FORCE` and `IGNORE` need at least one.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct IndexHint {
+    kind: IndexHintKind,
+    indexes: Vec<String>,
+}
+
+impl BuildSql for IndexHint {
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        _params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        if ctx.dialect() != DialectKind::MySql {
+            ctx.reject(anyhow::anyhow!(
+                "index hints are not supported by {}",
+                ctx.dialect().name()
+            ))?;
+        }
+        let keyword = match self.kind {
+            IndexHintKind::Use => "USE",
+            IndexHintKind::Force => "FORCE",
+            IndexHintKind::Ignore => "IGNORE",
+        };
+        if self.indexes.is_empty() && self.kind != IndexHintKind::Use {
+            return Err(anyhow::anyhow!("{} INDEX needs at least one index", keyword).into());
+        }
+        let indexes: Vec<String> = self.indexes.iter().map(|i| quote_identifier(i)).collect();
+        write!(buf, " {} INDEX ({})", keyword, indexes.join(", "))?;
+        Ok(())
+    }
 
-impl Comparable<SqlTypeString> for SqlTypeAny {}
+    fn size_hint(&self) -> SizeHint {
+        let indexes_len = self
+            .indexes
+            .iter()
+            .map(|i| i.len() + "``, ".len())
+            .sum::<usize>();
+        SizeHint::bytes(" IGNORE INDEX ()".len() + indexes_len)
+    }
+}
 
-impl Comparable<SqlTypeInt> for SqlTypeAny {}
+/// A source with index hints. See [`IndexHintMethod`].
+#[derive(Debug, Clone, derive_more::Deref)]
+pub struct IndexHinted<QS> {
+    #[deref]
+    source: QS,
+    hints: Vec<IndexHint>,
+}
 
-impl Comparable<SqlTypeUint> for SqlTypeAny {}
+impl<QS> IndexHinted<QS> {
+    fn hint(mut self, kind: IndexHintKind, indexes: &[&str]) -> IndexHinted<QS> {
+        self.hints.push(IndexHint {
+            kind,
+            indexes: indexes.iter().map(|i| i.to_string()).collect(),
+        });
+        self
+    }
 
-impl Comparable<SqlTypeBool> for SqlTypeAny {}
+    pub fn use_index(self, indexes: &[&str]) -> IndexHinted<QS> {
+        self.hint(IndexHintKind::Use, indexes)
+    }
 
-impl Comparable<SqlTypeAny> for SqlTypeString {}
+    pub fn force_index(self, indexes: &[&str]) -> IndexHinted<QS> {
+        self.hint(IndexHintKind::Force, indexes)
+    }
 
-impl Comparable<SqlTypeAny> for SqlTypeInt {}
+    pub fn ignore_index(self, indexes: &[&str]) -> IndexHinted<QS> {
+        self.hint(IndexHintKind::Ignore, indexes)
+    }
+}
 
-impl Comparable<SqlTypeAny> for SqlTypeUint {}
+/// Index hints on a source, e.g. `builder.source("users".force_index(&["idx_email"]))`. MySQL only.
+pub trait IndexHintMethod: IntoQuerySource + Sized {
+    fn use_index(self, indexes: &[&str]) -> IndexHinted<Self> {
+        IndexHinted {
+            source: self,
+            hints: Vec::new(),
+        }
+        .use_index(indexes)
+    }
 
-impl Comparable<SqlTypeAny> for SqlTypeBool {}
+    fn force_index(self, indexes: &[&str]) -> IndexHinted<Self> {
+        IndexHinted {
+            source: self,
+            hints: Vec::new(),
+        }
+        .force_index(indexes)
+    }
 
-impl Comparable<SqlTypeInt> for SqlTypeUint {}
+    fn ignore_index(self, indexes: &[&str]) -> IndexHinted<Self> {
+        IndexHinted {
+            source: self,
+            hints: Vec::new(),
+        }
+        .ignore_index(indexes)
+    }
+}
 
-impl Comparable<SqlTypeUint> for SqlTypeInt {}
+impl<T: IntoQuerySource> IndexHintMethod for T {}
 
-impl<T> Comparable<T> for T {}
+impl<T> IntoQuerySource for IndexHinted<T>
+where
+    T: IntoQuerySource,
+{
+    type Database = T::Database;
+    type QuerySource = IndexHinted<T::QuerySource>;
 
-macro_rules! impl_bool_binary_operators {
-    ( $( ( $ty:ident, $op:expr ) ),* $(,)* ) => {
-        $(
-            #[derive(Debug, Clone)]
-            pub struct $ty<L, R>
-            where
-                L: Expression,
-                R: Expression,
-                L::SqlType: Comparable<R::SqlType>,
-            {
-                lhs: L,
-                rhs: R,
-            }
+    fn into_query_source(self) -> Self::QuerySource {
+        IndexHinted {
+            source: self.source.into_query_source(),
+            hints: self.hints,
+        }
+    }
 
-            impl<L, R> Expression for $ty<L, R>
-            where
-                L: Expression,
-                R: Expression,
-                L::SqlType: Comparable<R::SqlType>,
-                L::Aggregation: Aggregation<R::Aggregation>,
-            {
-                type SqlType = SqlTypeBool;
-                type Term = Polynomial;
-                type BoolOperation = BoolMono;
-                type Aggregation = <L::Aggregation as Aggregation<R::Aggregation>>::Output;
-            }
+    fn explicit_alias(&self) -> Option<&'static str> {
+        self.source.explicit_alias()
+    }
+}
 
-            impl<L, R> AndOperatorMethod for $ty<L, R>
-            where
-                L: Expression,
-                R: Expression,
-                L::SqlType: Comparable<R::SqlType>,
-                Self: Expression<SqlType = SqlTypeBool>,
-            {}
+impl<QS> QuerySource for IndexHinted<QS>
+where
+    QS: QuerySource,
+{
+    type Database = QS::Database;
+    type NullableSelf = IndexHinted<QS::NullableSelf>;
 
-            impl<L,R> OrOperatorMethod for $ty<L,R>
-            where
-                L: Expression,
-                R: Expression,
-                L::SqlType: Comparable<R::SqlType>,
-                Self: Expression<SqlType = SqlTypeBool>,
-            {}
+    fn nullable(self) -> Self::NullableSelf {
+        IndexHinted {
+            source: self.source.nullable(),
+            hints: self.hints,
+        }
+    }
 
-            impl<L,R> NotOperatorMethod for $ty<L,R>
-            where
-                L: Expression,
-                R: Expression,
-                L::SqlType: Comparable<R::SqlType>,
-                Self: Expression<SqlType = SqlTypeBool>,
-            {}
+    fn column_aliases(&self) -> &[&'static str] {
+        self.source.column_aliases()
+    }
 
-            impl<L, R> BuildSql for $ty<L, R>
-            where
-                L: Expression + BuildSql,
-                R: Expression + BuildSql,
-                L::SqlType: Comparable<R::SqlType>,
-            {
-                fn build_sql(
-                    &self,
-                    buf: &mut Vec<u8>,
-                    params: &mut Vec<Value>,
-                ) -> Result<(), BuildSqlError> {
-                    (|| -> Result<(), anyhow::Error> {
-                        self.lhs.build_sql(buf, params)?;
-                        write!(buf, $op)?;
-                        self.rhs.build_sql(buf, params)?;
-                        Ok(())
-                    })()
-                    .map_err(From::from)
+    fn index_hints(&self) -> &[IndexHint] {
+        &self.hints
+    }
+
+    fn table_name(&self) -> Option<&str> {
+        self.source.table_name()
+    }
+
+    fn is_scoped(&self) -> bool {
+        self.source.is_scoped()
+    }
+
+    fn soft_delete(&self) -> Option<(&'static str, DeletedRows)> {
+        self.source.soft_delete()
+    }
+}
+
+impl<QS> SourceNullability for IndexHinted<QS>
+where
+    QS: SourceNullability,
+{
+    type Nullability = QS::Nullability;
+}
+
+impl<QS> BuildSql for IndexHinted<QS>
+where
+    QS: BuildSql,
+{
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        self.source.build_sql_with(ctx, buf, params)
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.source.size_hint()
+    }
+}
+
+/// A MySQL optimizer hint, written in `SELECT /*+ ... */`.
+///
+/// Hints naming a source take its [`QuerySourceRef`], so that they follow `change_sources_alias_name`.
+#[derive(Clone)]
+pub struct OptimizerHint(OptimizerHintKind);
+
+#[derive(Clone)]
+enum OptimizerHintKind {
+    Call {
+        name: &'static str,
+        source: Option<SourceAlias>,
+        args: Vec<String>,
+    },
+    Raw(String),
+}
+
+impl OptimizerHint {
+    /// `MAX_EXECUTION_TIME(ms)`.
+    pub fn max_execution_time(milliseconds: u64) -> OptimizerHint {
+        OptimizerHint(OptimizerHintKind::Call {
+            name: "MAX_EXECUTION_TIME",
+            source: None,
+            args: vec![milliseconds.to_string()],
+        })
+    }
+
+    /// ``INDEX(t1 `idx`, ...)``.
+    pub fn index<QS>(source: &QuerySourceRef<QS>, indexes: &[&str]) -> OptimizerHint {
+        OptimizerHint::source_hint("INDEX", source, indexes)
+    }
+
+    /// ``NO_INDEX(t1 `idx`, ...)``.
+    pub fn no_index<QS>(source: &QuerySourceRef<QS>, indexes: &[&str]) -> OptimizerHint {
+        OptimizerHint::source_hint("NO_INDEX", source, indexes)
+    }
+
+    /// A hint written as is, e.g. `SET_VAR(sort_buffer_size = 16M)`.
+    pub fn raw(hint: impl Into<String>) -> OptimizerHint {
+        OptimizerHint(OptimizerHintKind::Raw(hint.into()))
+    }
+
+    fn source_hint<QS>(
+        name: &'static str,
+        source: &QuerySourceRef<QS>,
+        indexes: &[&str],
+    ) -> OptimizerHint {
+        OptimizerHint(OptimizerHintKind::Call {
+            name,
+            source: Some(source.alias.clone()),
+            args: indexes.iter().map(|i| quote_identifier(i)).collect(),
+        })
+    }
+
+    fn to_sql(&self) -> String {
+        match &self.0 {
+            OptimizerHintKind::Call { name, source, args } => {
+                let mut sql = format!("{}(", name);
+                if let Some(source) = source {
+                    sql.push_str(&source.to_string());
+                    if !args.is_empty() {
+                        sql.push(' ');
+                    }
                 }
+                sql.push_str(&args.join(", "));
+                sql.push(')');
+                sql
+            }
+            OptimizerHintKind::Raw(hint) => hint.clone(),
+        }
+    }
+}
+
+fn write_optimizer_hints(hints: &[OptimizerHint], buf: &mut Vec<u8>) -> Result<(), BuildSqlError> {
+    if hints.is_empty() {
+        return Ok(());
+    }
+    let hints: Vec<String> = hints.iter().map(OptimizerHint::to_sql).collect();
+    if let Some(hint) = hints.iter().find(|h| h.contains("*/")) {
+        return Err(anyhow::anyhow!("optimizer hint `{}` must not contain `*/`", hint).into());
+    }
+    write!(buf, "/*+ {} */ ", hints.join(" "))?;
+    Ok(())
+}
+
+fn optimizer_hints_size_hint(hints: &[OptimizerHint]) -> SizeHint {
+    if hints.is_empty() {
+        return SizeHint::default();
+    }
+    SizeHint::bytes("/*+  */ ".len() + hints.len() * 32)
+}
+
+/// A predicate added for every table of a query and of its subqueries. See [`SelectBuilder::with_scope`].
+///
+/// It goes to `WHERE`, or to `ON` for the nullable side of an outer join.
+pub trait SourceScope: fmt::Debug {
+    /// Whether `table` is scoped. Sources wrapped in [`unscoped`] never are.
+    fn applies_to(&self, _table: &str) -> bool {
+        true
+    }
+
+    /// Write the predicate on the source aliased `alias`, e.g. `t1.tenant_id = ?`.
+    fn write_predicate(
+        &self,
+        ctx: &mut BuildContext,
+        alias: &str,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError>;
+}
+
+/// Scope every table to the tenant: `tN.tenant_id = ?`.
+#[derive(Debug, Clone)]
+pub struct TenantScope<T>(pub T);
+
+impl<T> SourceScope for TenantScope<T>
+where
+    T: IntoSqlValue + Clone + fmt::Debug,
+{
+    fn write_predicate(
+        &self,
+        ctx: &mut BuildContext,
+        alias: &str,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        write!(buf, "{}.tenant_id = ", alias)?;
+        ctx.write_param(buf, params, self.0.clone().into_value())
+    }
+}
+
+/// A table source added to a query, to be scoped by the scopes of the query
+/// or to filter out its soft-deleted rows.
+#[derive(Debug, Clone)]
+struct ScopedSource {
+    table: String,
+    alias: String,
+    scoped: bool,
+    soft_delete: Option<(&'static str, DeletedRows)>,
+}
+
+/// A source left out of [`SourceScope`]s, e.g. a table shared by all tenants.
+#[derive(Debug, Clone, derive_more::Deref)]
+pub struct Unscoped<T>(T);
+
+pub fn unscoped<T>(source: T) -> Unscoped<T>
+where
+    T: IntoQuerySource,
+{
+    Unscoped(source)
+}
+
+impl<T> IntoQuerySource for Unscoped<T>
+where
+    T: IntoQuerySource,
+{
+    type Database = T::Database;
+    type QuerySource = Unscoped<T::QuerySource>;
+
+    fn into_query_source(self) -> Self::QuerySource {
+        Unscoped(self.0.into_query_source())
+    }
+
+    fn explicit_alias(&self) -> Option<&'static str> {
+        self.0.explicit_alias()
+    }
+}
+
+impl<QS> QuerySource for Unscoped<QS>
+where
+    QS: QuerySource,
+{
+    type Database = QS::Database;
+    type NullableSelf = Unscoped<QS::NullableSelf>;
+
+    fn nullable(self) -> Self::NullableSelf {
+        Unscoped(self.0.nullable())
+    }
+
+    fn column_aliases(&self) -> &[&'static str] {
+        self.0.column_aliases()
+    }
+
+    fn index_hints(&self) -> &[IndexHint] {
+        self.0.index_hints()
+    }
+
+    fn table_name(&self) -> Option<&str> {
+        self.0.table_name()
+    }
+
+    fn is_scoped(&self) -> bool {
+        false
+    }
+
+    fn soft_delete(&self) -> Option<(&'static str, DeletedRows)> {
+        self.0.soft_delete()
+    }
+}
+
+impl<QS> SourceNullability for Unscoped<QS>
+where
+    QS: SourceNullability,
+{
+    type Nullability = QS::Nullability;
+}
+
+impl<QS> BuildSql for Unscoped<QS>
+where
+    QS: BuildSql,
+{
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        self.0.build_sql_with(ctx, buf, params)
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.0.size_hint()
+    }
+}
+
+/// Which rows of a [`SoftDelete`] table a query reads.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub enum DeletedRows {
+    /// Rows not deleted yet: `deleted_at IS NULL`.
+    #[default]
+    Exclude,
+    /// All the rows.
+    Include,
+    /// Deleted rows only: `deleted_at IS NOT NULL`.
+    Only,
+}
+
+/// A table whose rows are deleted by setting `deleted_at`, e.g. `soft_delete(users::table())`.
+///
+/// Queries read its rows not deleted yet, and [`DeleteBuilder`] updates `deleted_at` instead of deleting them.
+#[derive(Debug, Clone, derive_more::Deref)]
+pub struct SoftDelete<T> {
+    #[deref]
+    source: T,
+    column: &'static str,
+    rows: DeletedRows,
+}
+
+pub fn soft_delete<T>(source: T) -> SoftDelete<T>
+where
+    T: IntoQuerySource,
+{
+    SoftDelete::new(source)
+}
+
+impl<T> SoftDelete<T> {
+    pub fn new(source: T) -> SoftDelete<T> {
+        SoftDelete {
+            source,
+            column: "deleted_at",
+            rows: DeletedRows::Exclude,
+        }
+    }
+
+    /// The deletion timestamp column instead of `deleted_at`.
+    pub fn column(self, column: &'static str) -> SoftDelete<T> {
+        SoftDelete { column, ..self }
+    }
+
+    /// Read the deleted rows too.
+    pub fn with_deleted(self) -> SoftDelete<T> {
+        SoftDelete {
+            rows: DeletedRows::Include,
+            ..self
+        }
+    }
+
+    /// Read the deleted rows only.
+    pub fn only_deleted(self) -> SoftDelete<T> {
+        SoftDelete {
+            rows: DeletedRows::Only,
+            ..self
+        }
+    }
+}
+
+impl<T> IntoQuerySource for SoftDelete<T>
+where
+    T: IntoQuerySource,
+{
+    type Database = T::Database;
+    type QuerySource = SoftDelete<T::QuerySource>;
+
+    fn into_query_source(self) -> Self::QuerySource {
+        SoftDelete {
+            source: self.source.into_query_source(),
+            column: self.column,
+            rows: self.rows,
+        }
+    }
+
+    fn explicit_alias(&self) -> Option<&'static str> {
+        self.source.explicit_alias()
+    }
+}
+
+impl<QS> QuerySource for SoftDelete<QS>
+where
+    QS: QuerySource,
+{
+    type Database = QS::Database;
+    type NullableSelf = SoftDelete<QS::NullableSelf>;
+
+    fn nullable(self) -> Self::NullableSelf {
+        SoftDelete {
+            source: self.source.nullable(),
+            column: self.column,
+            rows: self.rows,
+        }
+    }
+
+    fn column_aliases(&self) -> &[&'static str] {
+        self.source.column_aliases()
+    }
+
+    fn index_hints(&self) -> &[IndexHint] {
+        self.source.index_hints()
+    }
+
+    fn table_name(&self) -> Option<&str> {
+        self.source.table_name()
+    }
+
+    fn is_scoped(&self) -> bool {
+        self.source.is_scoped()
+    }
+
+    fn soft_delete(&self) -> Option<(&'static str, DeletedRows)> {
+        Some((self.column, self.rows))
+    }
+}
+
+impl<QS> SourceNullability for SoftDelete<QS>
+where
+    QS: SourceNullability,
+{
+    type Nullability = QS::Nullability;
+}
+
+impl<QS> BuildSql for SoftDelete<QS>
+where
+    QS: BuildSql,
+{
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        self.source.build_sql_with(ctx, buf, params)
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.source.size_hint()
+    }
+}
+
+/// `DELETE FROM table WHERE table.id = ?;`
+///
+/// The table has no alias, which MySQL before 8.0.16 rejects in a single-table `DELETE`:
+/// its columns are qualified with the table name instead.
+///
+/// A [`SoftDelete`] table is updated with `SET deleted_at = now()` instead, unless [`DeleteBuilder::force_delete`] is called.
+#[derive(Clone)]
+pub struct DeleteBuilder<QS, W> {
+    source: QuerySourceRef<QS>,
+    tags: Vec<(String, String)>,
+    scopes: Vec<Rc<dyn SourceScope>>,
+    filter: W,
+    force: bool,
+}
+
+impl<QS> DeleteBuilder<QS, EmptyWhereClause>
+where
+    QS: QuerySource + Clone,
+{
+    pub fn new<T>(source: T) -> (DeleteBuilder<QS, EmptyWhereClause>, QuerySourceRef<QS>)
+    where
+        T: IntoQuerySource<QuerySource = QS>,
+    {
+        let explicit_alias = source.explicit_alias();
+        let src_ref = QuerySourceRef::new(
+            source.into_query_source(),
+            SourceAlias::new(SourceAliasName::default(), 1).with_explicit(explicit_alias),
+        );
+        let builder = DeleteBuilder {
+            source: src_ref.clone(),
+            tags: Vec::new(),
+            scopes: Vec::new(),
+            filter: EmptyWhereClause,
+            force: false,
+        };
+        (builder, src_ref)
+    }
+
+    pub fn filter<W>(self, expr: W) -> DeleteBuilder<QS, WhereClause<W>>
+    where
+        W: Expression<SqlType = SqlTypeBool, Aggregation = NonAggregate>,
+    {
+        DeleteBuilder {
+            source: self.source,
+            tags: self.tags,
+            scopes: self.scopes,
+            filter: WhereClause::new(expr),
+            force: self.force,
+        }
+    }
+}
+
+impl<QS, W> DeleteBuilder<QS, W>
+where
+    QS: QuerySource + BuildSql,
+    W: BuildSql,
+{
+    /// `DELETE` the rows even if the table is soft-deleted, including the rows already soft-deleted.
+    pub fn force_delete(self) -> DeleteBuilder<QS, W> {
+        DeleteBuilder {
+            force: true,
+            ..self
+        }
+    }
+
+    /// Add a sqlcommenter tag, written as `DELETE ... /* key='value' */;`.
+    pub fn tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.push((key.into(), value.into()));
+        self
+    }
+
+    /// Add a predicate for the table and the tables of the subqueries, e.g. `TenantScope(id)`.
+    pub fn with_scope(mut self, scope: impl SourceScope + 'static) -> Self {
+        self.scopes.push(Rc::new(scope));
+        self
+    }
+
+    #[track_caller]
+    pub fn build(self) -> Result<Query, QueryBuildError> {
+        self.build_with(BuildOptions::default())
+    }
+
+    #[track_caller]
+    pub fn build_with(self, options: BuildOptions) -> Result<Query, QueryBuildError> {
+        let mut ctx = BuildContext::new(options);
+        ctx.fill_caller_tags();
+        let size_hint = self.source.size_hint()
+            + self.filter.size_hint()
+            + "UPDATE  SET  = CURRENT_TIMESTAMP;".len()
+            + "deleted_at".len();
+        let query = Query::build_with_size_hint(size_hint, |buf, params| {
+            self.build_statement(&mut ctx, buf, params)
+        })?;
+        Ok(query.with_placeholders(ctx.placeholders))
+    }
+
+    fn build_statement(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        if self.source.source.table_name().is_none() {
+            return Err(anyhow::anyhow!("DELETE needs a table source").into());
+        }
+        let soft_delete = self.source.source.soft_delete().filter(|_| !self.force);
+        ctx.alias_scope(|ctx| {
+            let outer_scopes_len = ctx.scopes.len();
+            ctx.scopes.extend(self.scopes.iter().cloned());
+            let outer_scoped_sources = std::mem::take(&mut ctx.scoped_sources);
+            match soft_delete {
+                Some((column, _)) => {
+                    write!(buf, "UPDATE ")?;
+                    self.write_source(ctx, buf, params)?;
+                    let now = match ctx.dialect() {
+                        DialectKind::Sqlite => "CURRENT_TIMESTAMP",
+                        _ => "now()",
+                    };
+                    write!(buf, " SET {} = {}", column, now)?;
+                }
+                None => {
+                    write!(buf, "DELETE FROM ")?;
+                    self.write_source(ctx, buf, params)?;
+                }
+            }
+            let mut scoped_sources =
+                std::mem::replace(&mut ctx.scoped_sources, outer_scoped_sources);
+            if self.force {
+                for source in &mut scoped_sources {
+                    source.soft_delete = None;
+                }
+                scoped_sources.retain(|source| source.scoped);
+            }
+            ctx.where_condition = None;
+            self.filter.build_sql_with(ctx, buf, params)?;
+            let condition_start = ctx.where_condition.take();
+            ctx.write_scoped_where(&scoped_sources, condition_start, buf, params)?;
+            ctx.scopes.truncate(outer_scopes_len);
+            Ok(())
+        })?;
+        write_tags(ctx.tags.iter().chain(&self.tags), buf)?;
+        write!(buf, ";")?;
+        Ok(())
+    }
+
+    /// Write the table without alias, qualifying its columns with the table name.
+    fn write_source(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        let start = buf.len();
+        self.source.source.build_sql_with(ctx, buf, params)?;
+        let name = String::from_utf8_lossy(&buf[start..]).into_owned();
+        let source = &self.source.source;
+        if let Some(table) = source.table_name() {
+            ctx.record_scoped_source(table, &name, source.is_scoped(), source.soft_delete());
+        }
+        ctx.unaliased_source = Some((self.source.alias.id(), name));
+        Ok(())
+    }
+}
+
+impl<QS, W> BuildQuery for DeleteBuilder<QS, W>
+where
+    QS: QuerySource + BuildSql,
+    W: BuildSql,
+{
+    #[track_caller]
+    fn build_query(self, options: BuildOptions) -> Result<Query, QueryBuildError> {
+        self.build_with(options)
+    }
+}
+
+/// Renders the statement with inlined parameters like [`SelectBuilder`] does.
+impl<QS, W> fmt::Display for DeleteBuilder<QS, W>
+where
+    QS: QuerySource + BuildSql + Clone,
+    W: BuildSql + Clone,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sql = self
+            .clone()
+            .debug_sql(BuildOptions::default())
+            .map_err(|_| fmt::Error)?;
+        f.write_str(&sql)
+    }
+}
+
+#[derive(Clone, derive_more::Deref)]
+pub struct QuerySourceRef<QS> {
+    #[deref]
+    source: QS,
+    alias: SourceAlias,
+}
+
+impl<QS> QuerySourceRef<QS>
+where
+    QS: QuerySource,
+{
+    pub fn new(source: QS, alias: SourceAlias) -> QuerySourceRef<QS> {
+        QuerySourceRef { source, alias }
+    }
+
+    pub fn column<T>(&self, column_name: T) -> Column
+    where
+        T: AsColumnName + Sized,
+    {
+        Column::new(self.alias.clone(), column_name)
+    }
+
+    /// Column of the SQL type `T`, made `Nullable<T>` on the nullable side of an outer join.
+    pub fn typed_column<T>(
+        &self,
+        column_name: impl AsColumnName + Sized,
+    ) -> Column<NullableIf<QS, T>>
+    where
+        QS: SourceNullability,
+        QS::Nullability: ApplyNullability<T>,
+    {
+        Column::new(self.alias.clone(), column_name)
+    }
+
+    pub fn alias(&self) -> String {
+        self.alias.to_string()
+    }
+
+    /// The `I`th declared column of a [`ValuesSource`], typed by its rows.
+    pub fn value_column<const I: usize>(
+        &self,
+    ) -> Column<NullableIf<QS, <QS::SqlType as ColumnAt<I>>::SqlType>>
+    where
+        QS: ValuesColumns + SourceNullability,
+        QS::SqlType: ColumnAt<I>,
+        QS::Nullability: ApplyNullability<<QS::SqlType as ColumnAt<I>>::SqlType>,
+    {
+        Column::new(self.alias.clone(), self.source.column_name(I))
+    }
+
+    /// The declared column `name` of a [`ValuesSource`], or `None` if no column has it.
+    /// Untyped like [`QuerySourceRef::column`]; use [`QuerySourceRef::value_column`] for its type.
+    pub fn value_column_named(&self, name: &str) -> Option<Column>
+    where
+        QS: ValuesColumns,
+    {
+        let index = self.source.column_index(name)?;
+        Some(Column::new(
+            self.alias.clone(),
+            self.source.column_name(index),
+        ))
+    }
+}
+
+impl<QS> QuerySource for QuerySourceRef<QS>
+where
+    QS: QuerySource,
+{
+    type Database = QS::Database;
+    type NullableSelf = QuerySourceRef<QS::NullableSelf>;
+
+    fn nullable(self) -> Self::NullableSelf {
+        QuerySourceRef {
+            source: self.source.nullable(),
+            alias: self.alias.clone(),
+        }
+    }
+}
+
+impl<QS> BuildSql for QuerySourceRef<QS>
+where
+    QS: QuerySource + BuildSql,
+{
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        self.source
+            .build_sql_with(ctx, buf, params)
+            .map_err(anyhow::Error::from)?;
+        write!(buf, " as {}", self.alias.to_string()).map_err(anyhow::Error::from)?;
+        ctx.declare_alias(&self.alias)?;
+        ctx.record_visit(|| VisitEvent::Source {
+            table: self.source.table_name().map(String::from),
+            alias: self.alias.to_string(),
+        });
+        if let Some(table) = self.source.table_name() {
+            ctx.record_scoped_source(
+                table,
+                &self.alias.to_string(),
+                self.source.is_scoped(),
+                self.source.soft_delete(),
+            );
+        }
+        // SQLite has no column list on aliases; such sources name their columns themselves.
+        let column_aliases = self.source.column_aliases();
+        if !column_aliases.is_empty() && ctx.dialect() != DialectKind::Sqlite {
+            write!(buf, "({})", column_aliases.join(", ")).map_err(anyhow::Error::from)?;
+        }
+        for hint in self.source.index_hints() {
+            hint.build_sql_with(ctx, buf, params)?;
+        }
+        Ok(())
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        let column_aliases = self.source.column_aliases();
+        let column_aliases_len = if column_aliases.is_empty() {
+            0
+        } else {
+            column_aliases
+                .iter()
+                .map(|c| c.len() + ", ".len())
+                .sum::<usize>()
+        };
+        self.source
+            .index_hints()
+            .iter()
+            .fold(self.source.size_hint(), |hint, index_hint| {
+                hint + index_hint.size_hint()
+            })
+            + " as ".len()
+            + self.alias.len()
+            + column_aliases_len
+    }
+}
+
+pub trait AsColumnName {
+    fn as_column_name(&self) -> &str;
+}
+
+impl AsColumnName for &str {
+    fn as_column_name(&self) -> &str {
+        self
+    }
+}
+
+impl<T> AsColumnName for &T
+where
+    T: AsColumnName,
+{
+    fn as_column_name(&self) -> &str {
+        (**self).as_column_name()
+    }
+}
+
+impl<T> AsColumnName for &mut T
+where
+    T: AsColumnName,
+{
+    fn as_column_name(&self) -> &str {
+        (**self).as_column_name()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Join<L, R, ON> {
+    Inner(L, R, ON),
+    LeftOuter(L, R, ON),
+    RightOuter(L, R, ON),
+    FullOuter(L, R, ON),
+    Cross(L, R),
+    Natural(L, R),
+}
+
+/// Condition of a join: a boolean expression for `ON`, or [`using`] columns.
+pub trait JoinConstraint {
+    const KEYWORD: &'static str;
+}
+
+impl<E> JoinConstraint for E
+where
+    E: Expression<SqlType = SqlTypeBool>,
+{
+    const KEYWORD: &'static str = " ON ";
+}
+
+/// `USING (c1, c2)` of a join. The columns must exist on both sides.
+#[derive(Debug, Clone)]
+pub struct Using {
+    columns: Vec<String>,
+}
+
+/// Join by columns of the same name, returned from the `on` closure of a join.
+///
+/// ```ignore
+/// builder.inner_join("posts", |_| using(["user_id"]))
+/// ```
+pub fn using<I, T>(columns: I) -> Using
+where
+    I: IntoIterator<Item = T>,
+    T: AsColumnName,
+{
+    Using {
+        columns: columns
+            .into_iter()
+            .map(|c| c.as_column_name().to_string())
+            .collect(),
+    }
+}
+
+impl JoinConstraint for Using {
+    const KEYWORD: &'static str = " USING ";
+}
+
+impl BuildSql for Using {
+    fn build_sql_with(
+        &self,
+        _ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        _params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        write!(buf, "({})", self.columns.join(", "))?;
+        Ok(())
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::bytes(
+            self.columns.iter().map(String::len).sum::<usize>()
+                + self.columns.len().saturating_sub(1) * ", ".len()
+                + "()".len(),
+        )
+    }
+}
+
+impl<L, R, E> QuerySource for Join<L, R, E>
+where
+    L: QuerySource,
+    R: QuerySource,
+    L::Database: Joinable<R::Database>,
+    L::NullableSelf: QuerySource,
+    R::NullableSelf: QuerySource,
+    E: JoinConstraint + Clone,
+{
+    type Database = <L::Database as Joinable<R::Database>>::Database;
+    type NullableSelf = Join<L::NullableSelf, R::NullableSelf, E>;
+
+    fn nullable(self) -> Self::NullableSelf {
+        match self {
+            Join::Inner(l, r, on) => Join::Inner(l.nullable(), r.nullable(), on),
+            Join::LeftOuter(l, r, on) => Join::LeftOuter(l.nullable(), r.nullable(), on),
+            Join::RightOuter(l, r, on) => Join::RightOuter(l.nullable(), r.nullable(), on),
+            Join::FullOuter(l, r, on) => Join::FullOuter(l.nullable(), r.nullable(), on),
+            Join::Cross(l, r) => Join::Cross(l.nullable(), r.nullable()),
+            Join::Natural(l, r) => Join::Natural(l.nullable(), r.nullable()),
+        }
+    }
+}
+
+impl<L, R, E> BuildSql for Join<L, R, E>
+where
+    L: QuerySource + BuildSql,
+    R: QuerySource + BuildSql,
+    E: JoinConstraint + BuildSql,
+{
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        match self {
+            Join::Inner(l, r, on) => (|| -> Result<(), anyhow::Error> {
+                l.build_sql_with(ctx, buf, params)?;
+                ctx.write_clause_separator(buf)?;
+                write!(buf, "JOIN ")?;
+                r.build_sql_with(ctx, buf, params)?;
+                write!(buf, "{}", E::KEYWORD)?;
+                on.build_sql_with(ctx, buf, params)?;
+                Ok(())
+            })()
+            .map_err(From::from),
+            Join::LeftOuter(l, r, on) => (|| -> Result<(), anyhow::Error> {
+                l.build_sql_with(ctx, buf, params)?;
+                ctx.write_clause_separator(buf)?;
+                write!(buf, "LEFT OUTER JOIN ")?;
+                let r_start = ctx.scoped_sources.len();
+                r.build_sql_with(ctx, buf, params)?;
+                let nullable_sources = ctx.scoped_sources.split_off(r_start);
+                ctx.write_outer_join_constraint(on, &nullable_sources, buf, params)?;
+                Ok(())
+            })()
+            .map_err(From::from),
+            Join::RightOuter(l, r, on) => (|| -> Result<(), anyhow::Error> {
+                let l_start = ctx.scoped_sources.len();
+                l.build_sql_with(ctx, buf, params)?;
+                let nullable_sources = ctx.scoped_sources.split_off(l_start);
+                ctx.write_clause_separator(buf)?;
+                write!(buf, "RIGHT OUTER JOIN ")?;
+                r.build_sql_with(ctx, buf, params)?;
+                ctx.write_outer_join_constraint(on, &nullable_sources, buf, params)?;
+                Ok(())
+            })()
+            .map_err(From::from),
+            Join::FullOuter(l, r, on) => (|| -> Result<(), anyhow::Error> {
+                if ctx.dialect() == DialectKind::MySql {
+                    ctx.reject(anyhow::anyhow!("FULL OUTER JOIN is not supported by mysql"))?;
+                }
+                let l_start = ctx.scoped_sources.len();
+                l.build_sql_with(ctx, buf, params)?;
+                ctx.write_clause_separator(buf)?;
+                write!(buf, "FULL OUTER JOIN ")?;
+                r.build_sql_with(ctx, buf, params)?;
+                // Neither ON nor WHERE can scope both sides while keeping the unmatched rows.
+                if ctx.scoped_sources.len() > l_start {
+                    ctx.reject(anyhow::anyhow!(
+                        "FULL OUTER JOIN of scoped sources is not supported; use unscoped or scoped subqueries"
+                    ))?;
+                }
+                write!(buf, "{}", E::KEYWORD)?;
+                on.build_sql_with(ctx, buf, params)?;
+                Ok(())
+            })()
+            .map_err(From::from),
+            Join::Cross(l, r) => (|| -> Result<(), anyhow::Error> {
+                l.build_sql_with(ctx, buf, params)?;
+                ctx.write_clause_separator(buf)?;
+                write!(buf, "CROSS JOIN ")?;
+                r.build_sql_with(ctx, buf, params)?;
+                Ok(())
+            })()
+            .map_err(From::from),
+            Join::Natural(l, r) => (|| -> Result<(), anyhow::Error> {
+                l.build_sql_with(ctx, buf, params)?;
+                ctx.write_clause_separator(buf)?;
+                write!(buf, "NATURAL JOIN ")?;
+                r.build_sql_with(ctx, buf, params)?;
+                Ok(())
+            })()
+            .map_err(From::from),
+        }
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        match self {
+            Join::Inner(l, r, on) => {
+                l.size_hint() + " JOIN ".len() + r.size_hint() + E::KEYWORD.len() + on.size_hint()
+            }
+            Join::LeftOuter(l, r, on) => {
+                l.size_hint()
+                    + " LEFT OUTER JOIN ".len()
+                    + r.size_hint()
+                    + E::KEYWORD.len()
+                    + on.size_hint()
+            }
+            Join::RightOuter(l, r, on) => {
+                l.size_hint()
+                    + " RIGHT OUTER JOIN ".len()
+                    + r.size_hint()
+                    + E::KEYWORD.len()
+                    + on.size_hint()
+            }
+            Join::FullOuter(l, r, on) => {
+                l.size_hint()
+                    + " FULL OUTER JOIN ".len()
+                    + r.size_hint()
+                    + E::KEYWORD.len()
+                    + on.size_hint()
+            }
+            Join::Cross(l, r) => l.size_hint() + " CROSS JOIN ".len() + r.size_hint(),
+            Join::Natural(l, r) => l.size_hint() + " NATURAL JOIN ".len() + r.size_hint(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Monomial;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Polynomial;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Hash)]
+pub struct BoolAnd;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Hash)]
+pub struct BoolOr;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Hash)]
+pub struct BoolMono;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Hash)]
+pub struct NonBool;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Hash)]
+pub struct Aggregate;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Hash)]
+pub struct NonAggregate;
+
+pub trait Aggregation<T> {
+    type Output;
+}
+
+pub trait AggregationKind {
+    const IS_AGGREGATE: bool;
+}
+
+impl AggregationKind for Aggregate {
+    const IS_AGGREGATE: bool = true;
+}
+
+impl AggregationKind for NonAggregate {
+    const IS_AGGREGATE: bool = false;
+}
+
+impl Aggregation<Aggregate> for NonAggregate {
+    type Output = Aggregate;
+}
+
+impl Aggregation<NonAggregate> for Aggregate {
+    type Output = Aggregate;
+}
+
+impl Aggregation<Aggregate> for Aggregate {
+    type Output = Aggregate;
+}
+
+impl Aggregation<NonAggregate> for NonAggregate {
+    type Output = NonAggregate;
+}
+
+impl Aggregation<()> for Aggregate {
+    type Output = Aggregate;
+}
+
+impl Aggregation<()> for NonAggregate {
+    type Output = NonAggregate;
+}
+
+impl<A, B, C> Aggregation<(B, C)> for A
+where
+    A: Aggregation<B>,
+    A::Output: Aggregation<C>,
+{
+    type Output = <A::Output as Aggregation<C>>::Output;
+}
+
+pub trait Expression {
+    type SqlType;
+    type Term;
+    type BoolOperation;
+    type Aggregation;
+}
+
+/// Only for cross join.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Hash)]
+pub struct BlankBoolExpression;
+
+impl Expression for BlankBoolExpression {
+    type SqlType = SqlTypeBool;
+    type Term = Monomial;
+    type BoolOperation = ();
+    type Aggregation = NonAggregate;
+}
+
+/// `TRUE`, e.g. for `JOIN LATERAL (...) ON TRUE`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Hash)]
+pub struct True;
+
+impl Expression for True {
+    type SqlType = SqlTypeBool;
+    type Term = Monomial;
+    type BoolOperation = BoolMono;
+    type Aggregation = NonAggregate;
+}
+
+impl BuildSql for True {
+    fn build_sql_with(
+        &self,
+        _ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        _params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        write!(buf, "TRUE")?;
+        Ok(())
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::bytes("TRUE".len())
+    }
+}
+
+impl BuildSql for BlankBoolExpression {
+    fn build_sql_with(
+        &self,
+        _ctx: &mut BuildContext,
+        _buf: &mut Vec<u8>,
+        _params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Order<E> {
+    Asc(E),
+    Desc(E),
+}
+
+impl<E> BuildSql for Order<E>
+where
+    E: BuildSql,
+{
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        match self {
+            Order::Asc(expr) => {
+                expr.build_sql_with(ctx, buf, params)?;
+                write!(buf, " ASC")?;
+            }
+            Order::Desc(expr) => {
+                expr.build_sql_with(ctx, buf, params)?;
+                write!(buf, " DESC")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        match self {
+            Order::Asc(expr) => expr.size_hint() + " ASC".len(),
+            Order::Desc(expr) => expr.size_hint() + " DESC".len(),
+        }
+    }
+}
+
+impl<E> Order<E> {
+    pub fn nulls_first(self) -> NullsOrder<E> {
+        NullsOrder {
+            order: self,
+            nulls: Nulls::First,
+        }
+    }
+
+    pub fn nulls_last(self) -> NullsOrder<E> {
+        NullsOrder {
+            order: self,
+            nulls: Nulls::Last,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Nulls {
+    First,
+    Last,
+}
+
+/// `ORDER BY x ASC NULLS FIRST`. MySQL has no `NULLS`, so it is emulated by `x IS NULL DESC, x ASC`.
+#[derive(Debug, Clone)]
+pub struct NullsOrder<E> {
+    order: Order<E>,
+    nulls: Nulls,
+}
+
+impl<E> BuildSql for NullsOrder<E>
+where
+    E: BuildSql,
+{
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        if ctx.dialect() == DialectKind::MySql {
+            let expr = match &self.order {
+                Order::Asc(expr) | Order::Desc(expr) => expr,
+            };
+            expr.build_sql_with(ctx, buf, params)?;
+            match self.nulls {
+                Nulls::First => write!(buf, " IS NULL DESC, ")?,
+                Nulls::Last => write!(buf, " IS NULL ASC, ")?,
+            }
+            return ctx.write_repeated(|ctx| self.order.build_sql_with(ctx, buf, params));
+        }
+        self.order.build_sql_with(ctx, buf, params)?;
+        match self.nulls {
+            Nulls::First => write!(buf, " NULLS FIRST")?,
+            Nulls::Last => write!(buf, " NULLS LAST")?,
+        }
+        Ok(())
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.order.size_hint() + " NULLS FIRST".len()
+    }
+}
+
+/// An output column referred to by its alias in the select list, e.g. `ORDER BY total`.
+/// Building fails if the select list has no such column.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct AliasRef(String);
+
+impl BuildSql for AliasRef {
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        _params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        let mut aliases = ctx.select_list.iter().flatten().flatten();
+        if !aliases.any(|alias| *alias == self.0) {
+            ctx.reject(anyhow::anyhow!(
+                "`{}` is not an alias in the select list",
+                self.0
+            ))?;
+        }
+        write!(buf, "{}", self.0)?;
+        Ok(())
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::bytes(self.0.len())
+    }
+}
+
+/// An output column referred to by its 1-based position in the select list, e.g. `ORDER BY 2`.
+/// Building fails if the select list has no such column.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct ColumnPosition(pub usize);
+
+impl BuildSql for ColumnPosition {
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        _params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        let len = ctx.select_list.as_ref().map_or(0, Vec::len);
+        if !(1..=len).contains(&self.0) {
+            ctx.reject(anyhow::anyhow!(
+                "column position {} is out of the select list of {} columns",
+                self.0,
+                len
+            ))?;
+        }
+        write!(buf, "{}", self.0)?;
+        Ok(())
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::bytes(self.0.to_string().len())
+    }
+}
+
+/// An item of `ORDER BY`.
+pub trait OrderItem {}
+
+impl<E> OrderItem for Order<E> where E: Expression {}
+impl<E> OrderItem for NullsOrder<E> where E: Expression {}
+impl OrderItem for Order<AliasRef> {}
+impl OrderItem for NullsOrder<AliasRef> {}
+impl OrderItem for Order<ColumnPosition> {}
+
+pub trait Orders {}
+
+impl<T> Orders for T where T: OrderItem {}
+
+#[derive(Debug, thiserror::Error)]
+pub enum OrderError {
+    #[error("OrderError::UnknownColumn: {0}")]
+    UnknownColumn(String),
+}
+
+/// Columns which may be sorted by at runtime, keyed by the names exposed to clients.
+///
+/// ```ignore
+/// let sortable = SortableColumns::new()
+///     .column("name", t1.typed_column::<SqlTypeString>("name"))
+///     .column("created", t1.typed_column::<SqlTypeInt>("created_at"));
+/// builder.order_by(sortable.parse("-created,name")?)
+/// ```
+#[derive(Clone, Default)]
+pub struct SortableColumns {
+    columns: Vec<(String, Column)>,
+}
+
+impl SortableColumns {
+    pub fn new() -> SortableColumns {
+        Default::default()
+    }
+
+    pub fn column<ST>(mut self, key: &str, column: Column<ST>) -> SortableColumns {
+        self.columns.push((
+            key.to_string(),
+            Column {
+                table_name: column.table_name,
+                column_name: column.column_name,
+                sql_type: SqlTypeAny,
+            },
+        ));
+        self
+    }
+
+    /// Order by the column of `key`. Keys which were not registered are rejected.
+    pub fn order(&self, key: &str, descending: bool) -> Result<Order<Column>, OrderError> {
+        let column = self
+            .columns
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, column)| column.clone())
+            .ok_or_else(|| OrderError::UnknownColumn(key.to_string()))?;
+        Ok(if descending {
+            Order::Desc(column)
+        } else {
+            Order::Asc(column)
+        })
+    }
+
+    /// Parse comma separated keys, descending if prefixed by `-` and optionally followed by
+    /// `nulls first` or `nulls last`, e.g. `-created nulls last,name`.
+    pub fn parse(&self, spec: &str) -> Result<DynOrders, OrderError> {
+        spec.split(',')
+            .map(str::trim)
+            .filter(|key| !key.is_empty())
+            .map(|key| {
+                let (key, nulls) = split_nulls(key);
+                let order = match key.strip_prefix('-') {
+                    Some(key) => self.order(key, true)?,
+                    None => self.order(key, false)?,
+                };
+                Ok(match nulls {
+                    Some(nulls) => DynOrder::Nulls(NullsOrder { order, nulls }),
+                    None => DynOrder::Order(order),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(DynOrders)
+    }
+}
+
+/// Split a trailing `nulls first` or `nulls last`, in any case, off `key`.
+fn split_nulls(key: &str) -> (&str, Option<Nulls>) {
+    let lower = key.to_ascii_lowercase();
+    for (suffix, nulls) in [(" nulls first", Nulls::First), (" nulls last", Nulls::Last)] {
+        if lower.ends_with(suffix) {
+            return (key[..key.len() - suffix.len()].trim_end(), Some(nulls));
+        }
+    }
+    (key, None)
+}
+
+/// An item of [`DynOrders`], with or without `NULLS FIRST`/`NULLS LAST`.
+#[derive(Clone)]
+pub enum DynOrder {
+    Order(Order<Column>),
+    Nulls(NullsOrder<Column>),
+}
+
+impl From<Order<Column>> for DynOrder {
+    fn from(order: Order<Column>) -> Self {
+        DynOrder::Order(order)
+    }
+}
+
+impl From<NullsOrder<Column>> for DynOrder {
+    fn from(order: NullsOrder<Column>) -> Self {
+        DynOrder::Nulls(order)
+    }
+}
+
+impl BuildSql for DynOrder {
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        match self {
+            DynOrder::Order(order) => order.build_sql_with(ctx, buf, params),
+            DynOrder::Nulls(order) => order.build_sql_with(ctx, buf, params),
+        }
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        match self {
+            DynOrder::Order(order) => order.size_hint(),
+            DynOrder::Nulls(order) => order.size_hint(),
+        }
+    }
+}
+
+/// Orders chosen at runtime, see [`SortableColumns`]. It is the whole `ORDER BY` list,
+/// which is omitted if it is empty.
+#[derive(Clone, Default)]
+pub struct DynOrders(Vec<DynOrder>);
+
+impl DynOrders {
+    pub fn new<O: Into<DynOrder>>(orders: Vec<O>) -> DynOrders {
+        DynOrders(orders.into_iter().map(Into::into).collect())
+    }
+
+    pub fn push(&mut self, order: impl Into<DynOrder>) {
+        self.0.push(order.into());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Orders for DynOrders {}
+
+impl BuildSql for DynOrders {
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        for (i, order) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(buf, ", ")?;
+            }
+            order.build_sql_with(ctx, buf, params)?;
+        }
+        Ok(())
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.0.iter().fold(SizeHint::default(), |hint, order| {
+            hint + order.size_hint() + ", ".len()
+        })
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Limit {
+    pub offset: Option<usize>,
+    pub row_count: usize,
+}
+
+impl From<usize> for Limit {
+    fn from(row_count: usize) -> Self {
+        Limit {
+            offset: None,
+            row_count,
+        }
+    }
+}
+
+impl From<(usize, usize)> for Limit {
+    fn from((offset, row_count): (usize, usize)) -> Self {
+        Limit {
+            offset: Some(offset),
+            row_count,
+        }
+    }
+}
+
+impl BuildSql for Limit {
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        if let Some(offset) = self.offset {
+            ctx.write_param(buf, params, (offset as i64).into())?;
+            write!(buf, ", ")?;
+        }
+
+        ctx.write_param(buf, params, (self.row_count as i64).into())
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        match self.offset {
+            Some(_) => SizeHint::new("?, ?".len(), 2),
+            None => SizeHint::new("?".len(), 1),
+        }
+    }
+}
+
+pub trait Columns {
+    type SqlType;
+    type Aggregation;
+
+    /// One item per output column, with its alias if it has one.
+    fn output_columns(&self) -> Vec<Option<String>> {
+        vec![None]
+    }
+}
+
+impl<E: Expression> Columns for E {
+    type SqlType = E::SqlType;
+    type Aggregation = E::Aggregation;
+}
+
+/// `ROLLUP (a, b)` in `GROUP BY`, or `a, b WITH ROLLUP` on MySQL. Not supported by SQLite.
+///
+/// Rolled up columns are NULL in subtotal rows, so select them with a `Nullable` type.
+#[derive(Debug, Clone)]
+pub struct Rollup<G>(G);
+
+pub fn rollup<G>(columns: G) -> Rollup<G>
+where
+    G: Columns<Aggregation = NonAggregate>,
+{
+    Rollup(columns)
+}
+
+impl<G> Columns for Rollup<G>
+where
+    G: Columns<Aggregation = NonAggregate>,
+{
+    type SqlType = G::SqlType;
+    type Aggregation = NonAggregate;
+}
+
+impl<G> BuildSql for Rollup<G>
+where
+    G: BuildSql,
+{
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        match ctx.dialect() {
+            DialectKind::MySql => {
+                ctx.write_rolled_up(|ctx| self.0.build_sql_with(ctx, buf, params))?;
+                write!(buf, " WITH ROLLUP")?;
+            }
+            dialect => {
+                if dialect == DialectKind::Sqlite {
+                    ctx.reject(anyhow::anyhow!("ROLLUP is not supported by sqlite"))?;
+                }
+                write!(buf, "ROLLUP (")?;
+                ctx.write_rolled_up(|ctx| self.0.build_sql_with(ctx, buf, params))?;
+                write!(buf, ")")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.0.size_hint() + " WITH ROLLUP".len()
+    }
+}
+
+/// `CUBE (a, b)` in `GROUP BY`. Postgres only.
+#[derive(Debug, Clone)]
+pub struct Cube<G>(G);
+
+pub fn cube<G>(columns: G) -> Cube<G>
+where
+    G: Columns<Aggregation = NonAggregate>,
+{
+    Cube(columns)
+}
+
+impl<G> Columns for Cube<G>
+where
+    G: Columns<Aggregation = NonAggregate>,
+{
+    type SqlType = G::SqlType;
+    type Aggregation = NonAggregate;
+}
+
+impl<G> BuildSql for Cube<G>
+where
+    G: BuildSql,
+{
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        if ctx.dialect() != DialectKind::Postgres {
+            ctx.reject(anyhow::anyhow!(
+                "CUBE is not supported by {}",
+                ctx.dialect().name()
+            ))?;
+        }
+        write!(buf, "CUBE (")?;
+        ctx.write_rolled_up(|ctx| self.0.build_sql_with(ctx, buf, params))?;
+        write!(buf, ")")?;
+        Ok(())
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.0.size_hint() + "CUBE ()".len()
+    }
+}
+
+/// A set of [`grouping_sets`], e.g. `(a, b)`.
+#[derive(Debug, Clone)]
+pub struct GroupingSet<G>(G);
+
+pub fn grouping_set<G>(columns: G) -> GroupingSet<G>
+where
+    G: Columns<Aggregation = NonAggregate>,
+{
+    GroupingSet(columns)
+}
+
+impl<G> BuildSql for GroupingSet<G>
+where
+    G: BuildSql,
+{
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        write!(buf, "(")?;
+        self.0.build_sql_with(ctx, buf, params)?;
+        write!(buf, ")")?;
+        Ok(())
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.0.size_hint() + "()".len()
+    }
+}
+
+/// The empty set `()` of [`grouping_sets`], i.e. the grand total.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EmptyGroupingSet;
+
+impl BuildSql for EmptyGroupingSet {
+    fn build_sql_with(
+        &self,
+        _ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        _params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        write!(buf, "()")?;
+        Ok(())
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::bytes("()".len())
+    }
+}
+
+/// A grouping set or a tuple of them.
+pub trait GroupingSetList {}
+
+impl<G> GroupingSetList for GroupingSet<G> {}
+impl GroupingSetList for EmptyGroupingSet {}
+
+macro_rules! impl_grouping_set_list_for_tuple {
+    ( $( $type_param:ident ),+ ) => {
+        impl<$( $type_param ),+> GroupingSetList for ( $( $type_param, )+ )
+        where
+            $( $type_param: GroupingSetList, )+
+        {}
+    };
+}
+
+impl_grouping_set_list_for_tuple!(A, B);
+impl_grouping_set_list_for_tuple!(A, B, C);
+impl_grouping_set_list_for_tuple!(A, B, C, D);
+impl_grouping_set_list_for_tuple!(A, B, C, D, E);
+impl_grouping_set_list_for_tuple!(A, B, C, D, E, F);
+
+/// `GROUPING SETS ((a, b), (a), ())` in `GROUP BY`. Postgres only.
+#[derive(Debug, Clone)]
+pub struct GroupingSets<S>(S);
+
+pub fn grouping_sets<S>(sets: S) -> GroupingSets<S>
+where
+    S: GroupingSetList,
+{
+    GroupingSets(sets)
+}
+
+impl<S> Columns for GroupingSets<S>
+where
+    S: GroupingSetList,
+{
+    type SqlType = ();
+    type Aggregation = NonAggregate;
+}
+
+impl<S> BuildSql for GroupingSets<S>
+where
+    S: BuildSql,
+{
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        if ctx.dialect() != DialectKind::Postgres {
+            ctx.reject(anyhow::anyhow!(
+                "GROUPING SETS is not supported by {}",
+                ctx.dialect().name()
+            ))?;
+        }
+        write!(buf, "GROUPING SETS (")?;
+        ctx.write_rolled_up(|ctx| self.0.build_sql_with(ctx, buf, params))?;
+        write!(buf, ")")?;
+        Ok(())
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.0.size_hint() + "GROUPING SETS ()".len()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Distinct<T>(T);
+
+impl<T> Distinct<T> {
+    pub fn new(x: T) -> Distinct<T> {
+        Distinct(x)
+    }
+}
+
+impl<T> Columns for Distinct<T>
+where
+    T: Columns<Aggregation = NonAggregate>,
+{
+    type SqlType = T::SqlType;
+    type Aggregation = T::Aggregation;
+
+    fn output_columns(&self) -> Vec<Option<String>> {
+        self.0.output_columns()
+    }
+}
+
+/// Postgres `DISTINCT ON (keys) columns`, keeping the first row of each `keys`.
+/// `ORDER BY` must start with the `keys`, which is checked while building.
+#[derive(Debug, Clone)]
+pub struct DistinctOn<K, T> {
+    keys: K,
+    columns: T,
+}
+
+impl<K, T> DistinctOn<K, T> {
+    pub fn new(keys: K, columns: T) -> DistinctOn<K, T> {
+        DistinctOn { keys, columns }
+    }
+}
+
+impl<K, T> Columns for DistinctOn<K, T>
+where
+    K: Columns<Aggregation = NonAggregate>,
+    T: Columns<Aggregation = NonAggregate>,
+{
+    type SqlType = T::SqlType;
+    type Aggregation = T::Aggregation;
+
+    fn output_columns(&self) -> Vec<Option<String>> {
+        self.columns.output_columns()
+    }
+}
+
+impl<K, T> BuildSql for DistinctOn<K, T>
+where
+    K: BuildSql,
+    T: BuildSql,
+{
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        if ctx.dialect() != DialectKind::Postgres {
+            ctx.reject(anyhow::anyhow!(
+                "DISTINCT ON is not supported by {}",
+                ctx.dialect().name()
+            ))?;
+        }
+        write!(buf, "DISTINCT ON (")?;
+        let keys = ctx.collect_grouping(|ctx| self.keys.build_sql_with(ctx, buf, params))?;
+        write!(buf, ") ")?;
+        ctx.distinct_on = Some(keys.columns);
+        self.columns.build_sql_with(ctx, buf, params)
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.keys.size_hint() + self.columns.size_hint() + "DISTINCT ON () ".len()
+    }
+}
+
+impl<T: BuildSql> BuildSql for Distinct<T> {
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        write!(buf, "DISTINCT ")?;
+        self.0.build_sql_with(ctx, buf, params)
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.0.size_hint() + "DISTINCT ".len()
+    }
+}
+
+/// Build SQL string as a part of SQL.
+pub trait BuildSql {
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError>;
+
+    fn build_sql(&self, buf: &mut Vec<u8>, params: &mut Vec<Value>) -> Result<(), BuildSqlError> {
+        self.build_sql_with(&mut BuildContext::default(), buf, params)
+    }
+
+    /// Estimated size of the SQL built by `build_sql`.
+    /// Used to allocate the query buffers only once.
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::default()
+    }
+}
+
+/// Receives the parts of a query tree. See [`Visit`].
+pub trait Visitor {
+    /// A source of `FROM` or `JOIN`. `table` is `None` for subqueries, `VALUES` and functions.
+    fn visit_source(&mut self, _table: Option<&str>, _alias: &str) {}
+
+    fn visit_column(&mut self, _alias: &str, _column: &str) {}
+
+    /// An operator, e.g. `=` or `IS NULL`, before its operands.
+    fn visit_operator(&mut self, _operator: &str) {}
+
+    /// A function, e.g. `count`, before its arguments.
+    fn visit_function(&mut self, _name: &str) {}
+
+    /// A parameter, where it is bound in the tree.
+    fn visit_param(&mut self, _param: &Value) {}
+
+    fn enter_subquery(&mut self) {}
+
+    fn leave_subquery(&mut self) {}
+}
+
+#[derive(Debug, Clone)]
+enum VisitEvent {
+    Source {
+        table: Option<String>,
+        alias: String,
+    },
+    Column {
+        alias: String,
+        column: String,
+    },
+    Operator {
+        operator: &'static str,
+    },
+    Function {
+        name: &'static str,
+    },
+    /// Index of the parameter in the built parameters.
+    Param {
+        index: usize,
+    },
+    EnterSubquery {
+        at: usize,
+    },
+    LeaveSubquery {
+        at: usize,
+    },
+}
+
+/// Walk a builder, source, column, operator or function with a [`Visitor`],
+/// e.g. to list the referenced tables without parsing the SQL.
+///
+/// The tree is walked as it is built for the dialect of `options`, but past the checks of
+/// the builder: queries it rejects, e.g. for `ONLY_FULL_GROUP_BY` or constructs the dialect
+/// does not support, are visited too. An expression the dialect repeats, e.g. `NULLS FIRST`
+/// on MySQL, is visited once.
+pub trait Visit {
+    fn visit_with(
+        &self,
+        options: BuildOptions,
+        visitor: &mut dyn Visitor,
+    ) -> Result<(), BuildSqlError>;
+
+    fn visit(&self, visitor: &mut dyn Visitor) -> Result<(), BuildSqlError> {
+        self.visit_with(BuildOptions::default(), visitor)
+    }
+}
+
+impl<T> Visit for T
+where
+    T: BuildSql + ?Sized,
+{
+    fn visit_with(
+        &self,
+        options: BuildOptions,
+        visitor: &mut dyn Visitor,
+    ) -> Result<(), BuildSqlError> {
+        let mut ctx = BuildContext::new(options);
+        ctx.visits = Some(Vec::new());
+        ctx.visiting = true;
+        let mut buf = Vec::new();
+        let mut params = Vec::new();
+        self.build_sql_with(&mut ctx, &mut buf, &mut params)?;
+        let mut events = ctx.visits.unwrap_or_default();
+        // A select builder visited by itself is a statement rather than a subquery.
+        if let (Some(VisitEvent::EnterSubquery { at: 0 }), Some(VisitEvent::LeaveSubquery { at })) =
+            (events.first(), events.last())
+        {
+            if *at == buf.len() {
+                events.pop();
+                events.remove(0);
+            }
+        }
+        for event in &events {
+            match event {
+                VisitEvent::Source { table, alias } => {
+                    visitor.visit_source(table.as_deref(), alias)
+                }
+                VisitEvent::Column { alias, column } => visitor.visit_column(alias, column),
+                VisitEvent::Operator { operator } => visitor.visit_operator(operator),
+                VisitEvent::Function { name } => visitor.visit_function(name),
+                VisitEvent::Param { index } => visitor.visit_param(&params[*index]),
+                VisitEvent::EnterSubquery { .. } => visitor.enter_subquery(),
+                VisitEvent::LeaveSubquery { .. } => visitor.leave_subquery(),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Tables, columns, parameters and subqueries referenced by a query tree.
+#[derive(Debug, Clone, Default)]
+pub struct QueryReferences {
+    /// `(table, alias)` of each source. See [`Visitor::visit_source`].
+    pub sources: Vec<(Option<String>, String)>,
+    /// `(alias, column)` of each column reference.
+    pub columns: Vec<(String, String)>,
+    pub operators: Vec<String>,
+    pub functions: Vec<String>,
+    pub params: Vec<Value>,
+    pub subqueries: usize,
+}
+
+impl QueryReferences {
+    pub fn of(node: &impl Visit) -> Result<QueryReferences, BuildSqlError> {
+        QueryReferences::of_with(node, BuildOptions::default())
+    }
+
+    /// References of `node` built with `options`. See [`Visit`].
+    pub fn of_with(
+        node: &impl Visit,
+        options: BuildOptions,
+    ) -> Result<QueryReferences, BuildSqlError> {
+        let mut references = QueryReferences::default();
+        node.visit_with(options, &mut references)?;
+        Ok(references)
+    }
+
+    /// Distinct names of the tables, in order of appearance.
+    pub fn tables(&self) -> Vec<&str> {
+        let mut tables: Vec<&str> = Vec::new();
+        for table in self
+            .sources
+            .iter()
+            .filter_map(|(table, _)| table.as_deref())
+        {
+            if !tables.contains(&table) {
+                tables.push(table);
+            }
+        }
+        tables
+    }
+}
+
+impl Visitor for QueryReferences {
+    fn visit_source(&mut self, table: Option<&str>, alias: &str) {
+        self.sources
+            .push((table.map(String::from), alias.to_string()));
+    }
+
+    fn visit_column(&mut self, alias: &str, column: &str) {
+        self.columns.push((alias.to_string(), column.to_string()));
+    }
+
+    fn visit_operator(&mut self, operator: &str) {
+        self.operators.push(operator.to_string());
+    }
+
+    fn visit_function(&mut self, name: &str) {
+        self.functions.push(name.to_string());
+    }
+
+    fn visit_param(&mut self, param: &Value) {
+        self.params.push(param.clone());
+    }
+
+    fn enter_subquery(&mut self) {
+        self.subqueries += 1;
+    }
+}
+
+/// Estimated byte length of SQL and number of parameters.
+#[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash, Default)]
+pub struct SizeHint {
+    pub bytes: usize,
+    pub params: usize,
+}
+
+impl SizeHint {
+    pub const fn new(bytes: usize, params: usize) -> SizeHint {
+        SizeHint { bytes, params }
+    }
+
+    pub const fn bytes(bytes: usize) -> SizeHint {
+        SizeHint { bytes, params: 0 }
+    }
+}
+
+impl std::ops::Add for SizeHint {
+    type Output = SizeHint;
+
+    fn add(self, rhs: SizeHint) -> Self::Output {
+        SizeHint {
+            bytes: self.bytes + rhs.bytes,
+            params: self.params + rhs.params,
+        }
+    }
+}
+
+impl std::ops::Add<usize> for SizeHint {
+    type Output = SizeHint;
+
+    fn add(self, rhs: usize) -> Self::Output {
+        SizeHint {
+            bytes: self.bytes + rhs,
+            params: self.params,
+        }
+    }
+}
+
+impl std::ops::AddAssign for SizeHint {
+    fn add_assign(&mut self, rhs: SizeHint) {
+        *self = *self + rhs;
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum BuildSqlError {
+    #[error("Failed to build sql: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Failed to build sql: {0}")]
+    AnyError(#[from] anyhow::Error),
+}
+
+/// Layout of the built SQL.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub enum Format {
+    /// Whole statement on one line.
+    #[default]
+    Compact,
+    /// Each clause on its own line, subqueries indented.
+    Pretty,
+}
+
+/// Options given to `build_with()`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Default)]
+pub struct BuildOptions {
+    pub dialect: DialectKind,
+    pub format: Format,
+    /// Skip the `ONLY_FULL_GROUP_BY` check, e.g. for SQLite, MySQL without the mode,
+    /// or columns functionally dependent on a grouped key.
+    pub allow_partial_group_by: bool,
+    /// Tags added to every statement from where it is built. See [`BuildOptions::tag_hook`].
+    pub tag_hook: Option<TagHook>,
+}
+
+impl BuildOptions {
+    pub fn new() -> BuildOptions {
+        Default::default()
+    }
+
+    pub fn dialect(self, dialect: impl Dialect) -> BuildOptions {
+        BuildOptions {
+            dialect: dialect.kind(),
+            ..self
+        }
+    }
+
+    pub fn pretty(self) -> BuildOptions {
+        BuildOptions {
+            format: Format::Pretty,
+            ..self
+        }
+    }
+
+    pub fn allow_partial_group_by(self) -> BuildOptions {
+        BuildOptions {
+            allow_partial_group_by: true,
+            ..self
+        }
+    }
+
+    /// Fill tags from the location calling `build()`, e.g. with [`caller_location_tags`].
+    pub fn tag_hook<F>(self, hook: F) -> BuildOptions
+    where
+        F: Fn(&'static Location<'static>) -> Vec<(String, String)> + Send + Sync + 'static,
+    {
+        BuildOptions {
+            tag_hook: Some(TagHook(Arc::new(hook))),
+            ..self
+        }
+    }
+}
+
+/// Makes tags from the location calling `build()`. See [`BuildOptions::tag_hook`].
+#[derive(Clone)]
+pub struct TagHook(
+    pub Arc<dyn Fn(&'static Location<'static>) -> Vec<(String, String)> + Send + Sync>,
+);
+
+impl fmt::Debug for TagHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TagHook")
+    }
+}
+
+// Compared by address, so that `BuildOptions` stays `Eq` and `Hash`.
+impl PartialEq for TagHook {
+    fn eq(&self, other: &TagHook) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl std::cmp::Eq for TagHook {}
+
+impl std::hash::Hash for TagHook {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        (Arc::as_ptr(&self.0) as *const () as usize).hash(state)
+    }
+}
+
+/// A tag hook tagging the source file and line, e.g. `/* location='src%2Fmain.rs%3A42' */`.
+pub fn caller_location_tags(location: &'static Location<'static>) -> Vec<(String, String)> {
+    vec![(
+        "location".to_string(),
+        format!("{}:{}", location.file(), location.line()),
+    )]
+}
+
+/// Percent-encode like sqlcommenter, so that neither `'` nor `*/` survive in a tag.
+fn encode_tag(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(b as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    encoded
+}
+
+/// ` /* a='1',b='2' */`, sorted by key. A later tag of the same key wins.
+fn write_tags<'a>(
+    tags: impl Iterator<Item = &'a (String, String)>,
+    buf: &mut Vec<u8>,
+) -> Result<(), BuildSqlError> {
+    let tags: BTreeMap<String, String> = tags
+        .map(|(key, value)| (encode_tag(key), encode_tag(value)))
+        .collect();
+    if tags.is_empty() {
+        return Ok(());
+    }
+    let tags: Vec<String> = tags
+        .iter()
+        .map(|(key, value)| format!("{}='{}'", key, value))
+        .collect();
+    write!(buf, " /* {} */", tags.join(","))?;
+    Ok(())
+}
+
+/// State passed down the tree while building SQL.
+#[derive(Debug, Clone, Default)]
+pub struct BuildContext {
+    options: BuildOptions,
+    depth: usize,
+    grouping: Option<GroupingScope>,
+    aliases: Vec<AliasScope>,
+    /// Columns of `DISTINCT ON` of the query being built.
+    distinct_on: Option<Vec<ColumnRef>>,
+    /// Output columns of the query whose `ORDER BY` is being built, with their aliases.
+    select_list: Option<Vec<Option<String>>>,
+    /// Tags filled by [`BuildOptions::tag_hook`].
+    tags: Vec<(String, String)>,
+    /// Parts of the tree met while building, for [`Visit`].
+    visits: Option<Vec<VisitEvent>>,
+    /// Walking the tree for [`Visit`], past the checks of the builder.
+    visiting: bool,
+    /// Scopes of the query being built and of its outer queries.
+    scopes: Vec<Rc<dyn SourceScope>>,
+    /// Tables of the query being built whose scope predicates go to `WHERE`.
+    scoped_sources: Vec<ScopedSource>,
+    /// Byte offset of the condition of the last `WHERE` written.
+    where_condition: Option<usize>,
+    /// A source written without alias and the name its columns are qualified with instead.
+    unaliased_source: Option<(AliasId, String)>,
+    /// Byte offsets of the placeholders written by [`BuildContext::write_param`].
+    placeholders: Vec<usize>,
+}
+
+/// Source aliases declared and referenced by a query, without its subqueries.
+#[derive(Debug, Clone, Default)]
+struct AliasScope {
+    declared: Vec<(String, AliasId)>,
+    referenced: Vec<(String, AliasId)>,
+}
+
+/// Column references outside of aggregate functions, collected for the `ONLY_FULL_GROUP_BY` check.
+#[derive(Debug, Clone, Default)]
+struct GroupingScope {
+    columns: Vec<ColumnRef>,
+    /// Arguments of `grouping()`, which must be grouped although it is an aggregate.
+    grouping_args: Vec<ColumnRef>,
+    aggregate_depth: usize,
+    grouping_depth: usize,
+    rollup_depth: usize,
+}
+
+/// A column reference identified by its source and name, with its SQL text for messages.
+#[derive(Debug, Clone)]
+struct ColumnRef {
+    source: AliasId,
+    name: String,
+    text: String,
+    /// Rolled up in `GROUP BY`, or of a type decoding NULL elsewhere.
+    may_be_null: bool,
+}
+
+impl PartialEq for ColumnRef {
+    fn eq(&self, other: &ColumnRef) -> bool {
+        self.source == other.source && self.name == other.name
+    }
+}
+
+impl BuildContext {
+    const INDENT: &'static str = "  ";
+
+    pub fn new(options: BuildOptions) -> BuildContext {
+        BuildContext {
+            options,
+            depth: 0,
+            grouping: None,
+            aliases: Vec::new(),
+            distinct_on: None,
+            select_list: None,
+            tags: Vec::new(),
+            visits: None,
+            visiting: false,
+            scopes: Vec::new(),
+            scoped_sources: Vec::new(),
+            where_condition: None,
+            unaliased_source: None,
+            placeholders: Vec::new(),
+        }
+    }
+
+    pub fn dialect(&self) -> DialectKind {
+        self.options.dialect
+    }
+
+    pub fn format(&self) -> Format {
+        self.options.format
+    }
+
+    /// Write a `?` placeholder bound to `value`, tracking its position for [`Query::to_debug_sql`].
+    pub fn write_param(
+        &mut self,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+        value: Value,
+    ) -> Result<(), BuildSqlError> {
+        self.placeholders.push(buf.len());
+        self.record_visit(|| VisitEvent::Param {
+            index: params.len(),
+        });
+        write!(buf, "?")?;
+        params.push(value);
+        Ok(())
+    }
+
+    /// Byte offsets of the placeholders written so far. See [`Query::with_placeholders`].
+    pub fn placeholders(&self) -> &[usize] {
+        &self.placeholders
+    }
+
+    /// Write the separator put before a clause such as `FROM` or `LEFT OUTER JOIN`.
+    pub fn write_clause_separator(&self, buf: &mut Vec<u8>) -> std::io::Result<()> {
+        match self.options.format {
+            Format::Compact => write!(buf, " "),
+            Format::Pretty => self.write_newline(buf),
+        }
+    }
+
+    /// Write a parenthesized subquery, indenting it one level deeper in pretty format.
+    pub fn write_subquery<F>(&mut self, buf: &mut Vec<u8>, f: F) -> Result<(), BuildSqlError>
+    where
+        F: FnOnce(&mut BuildContext, &mut Vec<u8>) -> Result<(), BuildSqlError>,
+    {
+        write!(buf, "(")?;
+        self.record_visit(|| VisitEvent::EnterSubquery { at: buf.len() - 1 });
+        self.depth += 1;
+        // Columns of a subquery are checked by the subquery itself.
+        let grouping = self.grouping.take();
+        if self.options.format == Format::Pretty {
+            self.write_newline(buf)?;
+        }
+        f(self, buf)?;
+        self.grouping = grouping;
+        self.depth -= 1;
+        if self.options.format == Format::Pretty {
+            self.write_newline(buf)?;
+        }
+        write!(buf, ")")?;
+        self.record_visit(|| VisitEvent::LeaveSubquery { at: buf.len() });
+        Ok(())
+    }
+
+    /// Write an aggregate function if `A` is `Aggregate`, so that its arguments need not be grouped.
+    pub fn write_function<A, F>(&mut self, f: F) -> Result<(), BuildSqlError>
+    where
+        A: AggregationKind,
+        F: FnOnce(&mut BuildContext) -> Result<(), BuildSqlError>,
+    {
+        if !A::IS_AGGREGATE {
+            return f(self);
+        }
+        if let Some(scope) = &mut self.grouping {
+            scope.aggregate_depth += 1;
+        }
+        f(self)?;
+        if let Some(scope) = &mut self.grouping {
+            scope.aggregate_depth -= 1;
+        }
+        Ok(())
+    }
+
+    /// Run `f` as the body of a query, checking that its aliases are unique and that its
+    /// references to outer sources are not shadowed by its own sources.
+    fn alias_scope<F>(&mut self, f: F) -> Result<(), BuildSqlError>
+    where
+        F: FnOnce(&mut BuildContext) -> Result<(), BuildSqlError>,
+    {
+        self.aliases.push(AliasScope::default());
+        let result = f(self);
+        let scope = self.aliases.pop().unwrap_or_default();
+        result?;
+        for (name, id) in scope.referenced {
+            match scope
+                .declared
+                .iter()
+                .find(|(declared, _)| *declared == name)
+            {
+                Some((_, declared_id)) if *declared_id != id => {
+                    self.reject(anyhow::anyhow!(
+                        "source alias `{}` of an outer query is shadowed by a subquery source; \
+                         rename one with change_sources_alias_name or source_as",
+                        name
+                    ))?;
+                }
+                Some(_) => {}
+                None => {
+                    if let Some(outer) = self.aliases.last_mut() {
+                        outer.referenced.push((name, id));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn declare_alias(&mut self, alias: &SourceAlias) -> Result<(), BuildSqlError> {
+        let visiting = self.visiting;
+        let scope = match self.aliases.last_mut() {
+            Some(scope) => scope,
+            None => return Ok(()),
+        };
+        let name = alias.to_string();
+        match scope
+            .declared
+            .iter()
+            .find(|(declared, _)| *declared == name)
+        {
+            Some((_, id)) if *id != alias.id() && !visiting => Err(anyhow::anyhow!(
+                "source alias `{}` is used by more than one source",
+                name
+            )
+            .into()),
+            Some(_) => Ok(()),
+            None => {
+                scope.declared.push((name, alias.id()));
+                Ok(())
+            }
+        }
+    }
+
+    fn record_scoped_source(
+        &mut self,
+        table: &str,
+        alias: &str,
+        scoped: bool,
+        soft_delete: Option<(&'static str, DeletedRows)>,
+    ) {
+        let scoped = scoped && self.scopes.iter().any(|scope| scope.applies_to(table));
+        let soft_delete = soft_delete.filter(|(_, rows)| *rows != DeletedRows::Include);
+        if scoped || soft_delete.is_some() {
+            self.scoped_sources.push(ScopedSource {
+                table: table.to_string(),
+                alias: alias.to_string(),
+                scoped,
+                soft_delete,
+            });
+        }
+    }
+
+    /// Write the scope and soft delete predicates of `sources` joined by ` AND `.
+    fn write_scope_predicates(
+        &mut self,
+        sources: &[ScopedSource],
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        let mut first = true;
+        let mut separate = |buf: &mut Vec<u8>| {
+            let result = if first { Ok(()) } else { write!(buf, " AND ") };
+            first = false;
+            result
+        };
+        for source in sources {
+            if source.scoped {
+                let scopes: Vec<Rc<dyn SourceScope>> = self
+                    .scopes
+                    .iter()
+                    .filter(|s| s.applies_to(&source.table))
+                    .cloned()
+                    .collect();
+                for scope in scopes {
+                    separate(buf)?;
+                    scope.write_predicate(self, &source.alias, buf, params)?;
+                }
+            }
+            if let Some((column, rows)) = source.soft_delete {
+                separate(buf)?;
+                let predicate = match rows {
+                    DeletedRows::Only => "IS NOT NULL",
+                    _ => "IS NULL",
+                };
+                write!(buf, "{}.{} {}", source.alias, column, predicate)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Add the scope predicates of `sources` to the `WHERE` clause whose condition starts at
+    /// `condition_start`, parenthesizing the original condition, or write a `WHERE` if there is none.
+    fn write_scoped_where(
+        &mut self,
+        sources: &[ScopedSource],
+        condition_start: Option<usize>,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        if sources.is_empty() {
+            return Ok(());
+        }
+        if let Some(condition_start) = condition_start {
+            buf.insert(condition_start, b'(');
+            for at in self
+                .placeholders
+                .iter_mut()
+                .filter(|at| **at >= condition_start)
+            {
+                *at += 1;
+            }
+            write!(buf, ") AND ")?;
+        } else {
+            self.write_clause_separator(buf)?;
+            write!(buf, "WHERE ")?;
+        }
+        self.write_scope_predicates(sources, buf, params)
+    }
+
+    /// Write the constraint of an outer join, adding the scope predicates of its nullable side.
+    fn write_outer_join_constraint<E>(
+        &mut self,
+        on: &E,
+        nullable_sources: &[ScopedSource],
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError>
+    where
+        E: JoinConstraint + BuildSql,
+    {
+        write!(buf, "{}", E::KEYWORD)?;
+        if nullable_sources.is_empty() {
+            return on.build_sql_with(self, buf, params);
+        }
+        if E::KEYWORD != " ON " {
+            return Err(anyhow::anyhow!(
+                "scoped sources on the nullable side of an outer join need ON instead of USING"
+            )
+            .into());
+        }
+        write!(buf, "(")?;
+        on.build_sql_with(self, buf, params)?;
+        write!(buf, ") AND ")?;
+        self.write_scope_predicates(nullable_sources, buf, params)
+    }
+
+    /// Fail with `error`, unless visiting. See [`Visit`].
+    fn reject(&self, error: anyhow::Error) -> Result<(), BuildSqlError> {
+        if self.visiting {
+            return Ok(());
+        }
+        Err(error.into())
+    }
+
+    fn record_visit(&mut self, event: impl FnOnce() -> VisitEvent) {
+        if let Some(events) = &mut self.visits {
+            events.push(event());
+        }
+    }
+
+    /// The alias as written, i.e. the table name for [`BuildContext::unaliased_source`].
+    fn alias_text(&self, alias: &SourceAlias) -> String {
+        match &self.unaliased_source {
+            Some((id, name)) if *id == alias.id() => name.clone(),
+            _ => alias.to_string(),
+        }
+    }
+
+    fn reference_alias(&mut self, alias: &SourceAlias) {
+        let name = self.alias_text(alias);
+        if let Some(scope) = self.aliases.last_mut() {
+            scope.referenced.push((name, alias.id()));
+        }
+    }
+
+    fn record_column(&mut self, alias: &SourceAlias, name: &str, text: &[u8], may_be_null: bool) {
+        if let Some(scope) = &mut self.grouping {
+            let column = ColumnRef {
+                source: alias.id(),
+                name: name.to_string(),
+                text: String::from_utf8_lossy(text).into_owned(),
+                may_be_null: may_be_null || scope.rollup_depth > 0,
+            };
+            if scope.grouping_depth > 0 {
+                scope.grouping_args.push(column);
+            } else if scope.aggregate_depth == 0 {
+                scope.columns.push(column);
+            }
+        }
+    }
+
+    /// Write the arguments of `grouping()`, which are checked against `GROUP BY`.
+    fn write_grouping_args<F>(&mut self, f: F) -> Result<(), BuildSqlError>
+    where
+        F: FnOnce(&mut BuildContext) -> Result<(), BuildSqlError>,
+    {
+        if let Some(scope) = &mut self.grouping {
+            scope.grouping_depth += 1;
+        }
+        f(self)?;
+        if let Some(scope) = &mut self.grouping {
+            scope.grouping_depth -= 1;
+        }
+        Ok(())
+    }
+
+    /// Write columns of `ROLLUP`, `CUBE` or `GROUPING SETS`, which are NULL in subtotal rows.
+    fn write_rolled_up<F>(&mut self, f: F) -> Result<(), BuildSqlError>
+    where
+        F: FnOnce(&mut BuildContext) -> Result<(), BuildSqlError>,
+    {
+        if let Some(scope) = &mut self.grouping {
+            scope.rollup_depth += 1;
+        }
+        f(self)?;
+        if let Some(scope) = &mut self.grouping {
+            scope.rollup_depth -= 1;
+        }
+        Ok(())
+    }
+
+    /// Run `f` collecting the column references written by it.
+    fn collect_grouping<F>(&mut self, f: F) -> Result<GroupingScope, BuildSqlError>
+    where
+        F: FnOnce(&mut BuildContext) -> Result<(), BuildSqlError>,
+    {
+        let outer = self.grouping.replace(GroupingScope::default());
+        let result = f(self);
+        let scope = std::mem::replace(&mut self.grouping, outer);
+        result.map(|_| scope.unwrap_or_default())
+    }
+
+    /// Write an expression again without visiting it twice. See [`Visit`].
+    fn write_repeated<F>(&mut self, f: F) -> Result<(), BuildSqlError>
+    where
+        F: FnOnce(&mut BuildContext) -> Result<(), BuildSqlError>,
+    {
+        let visits = self.visits.take();
+        let result = f(self);
+        self.visits = visits;
+        result
+    }
+
+    /// Fill the tags of [`BuildOptions::tag_hook`] from the location calling `build()`.
+    #[track_caller]
+    fn fill_caller_tags(&mut self) {
+        if let Some(hook) = &self.options.tag_hook {
+            self.tags = (hook.0)(Location::caller());
+        }
+    }
+
+    fn write_newline(&self, buf: &mut Vec<u8>) -> std::io::Result<()> {
+        writeln!(buf)?;
+        for _ in 0..self.depth {
+            buf.extend_from_slice(Self::INDENT.as_bytes());
+        }
+        Ok(())
+    }
+}
+
+impl<ST: DecodeValue> BuildSql for Column<ST> {
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        _params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        let start = buf.len();
+        let alias = ctx.alias_text(&self.table_name);
+        write!(buf, "{}.{}", alias, self.column_name)?;
+        ctx.record_column(
+            &self.table_name,
+            &self.column_name,
+            &buf[start..],
+            ST::MAY_BE_NULL,
+        );
+        ctx.reference_alias(&self.table_name);
+        ctx.record_visit(|| VisitEvent::Column {
+            alias,
+            column: self.column_name.clone(),
+        });
+        Ok(())
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::bytes(self.table_name.len() + ".".len() + self.column_name.len())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Record<T> {
+    columns: T,
+}
+
+impl<T> Record<T> {
+    pub fn new(columns: T) -> Record<T> {
+        Record { columns }
+    }
+}
+
+impl<A> Columns for (A,)
+where
+    A: Expression,
+{
+    type SqlType = (A::SqlType,);
+    type Aggregation = A::Aggregation;
+
+    fn output_columns(&self) -> Vec<Option<String>> {
+        self.0.output_columns()
+    }
+}
+
+impl<A: Expression> Expression for Record<(A,)> {
+    type SqlType = A::SqlType;
+    type Term = A::Term;
+    type BoolOperation = A::BoolOperation;
+    type Aggregation = A::Aggregation;
+}
+
+impl<A> Orders for (A,) where A: OrderItem {}
+
+impl<A> BuildSql for (A,)
+where
+    A: BuildSql,
+{
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        self.0.build_sql_with(ctx, buf, params)
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.0.size_hint()
+    }
+}
+
+macro_rules! recursive_aggregation {
+    ( $first_ty:ty, $( $ty:ty ),* $(,)* ) => {
+        ($first_ty, recursive_aggregation!( $($ty,)* ))
+    };
+
+    () => {
+        ()
+    };
+}
+
+macro_rules! impl_traits_for_tuple {
+    ($type_paramA:ident, $field0:tt $(,$type_param:ident, $field:tt)*) => {
+        impl<$type_paramA $(, $type_param)*> Columns for ($type_paramA $(, $type_param)*)
+        where
+            $type_paramA: Columns,
+            $($type_param: Columns,)*
+            <$type_paramA>::Aggregation: Aggregation<recursive_aggregation!( $( <$type_param>::Aggregation, )* )>,
+        {
+            type SqlType = ( $type_paramA::SqlType, $( $type_param::SqlType, )* );
+            type Aggregation = <<$type_paramA>::Aggregation as Aggregation<recursive_aggregation!( $( <$type_param>::Aggregation, )* )>>::Output;
+
+            fn output_columns(&self) -> Vec<Option<String>> {
+                let mut columns = self.$field0.output_columns();
+                $( columns.extend(self.$field.output_columns()); )*
+                columns
+            }
+        }
+
+        impl<$type_paramA $(, $type_param)*> Expression for Record<($type_paramA $(, $type_param)*)>
+        where
+            $type_paramA: Expression,
+            $($type_param: Expression,)*
+            <$type_paramA>::Aggregation: Aggregation<recursive_aggregation!( $( <$type_param>::Aggregation, )* )>,
+        {
+            type SqlType = ( $type_paramA::SqlType, $( $type_param::SqlType, )* );
+            type Term = Polynomial;
+            type BoolOperation = BoolMono;
+            type Aggregation = <<$type_paramA>::Aggregation as Aggregation<recursive_aggregation!( $( <$type_param>::Aggregation, )* )>>::Output;
+        }
+
+        impl<$type_paramA $(, $type_param)*> DecodeRow for ($type_paramA $(, $type_param)*)
+        where
+            $type_paramA: DecodeRow,
+            $($type_param: DecodeRow,)*
+        {
+            type Row = ( $type_paramA::Row, $( $type_param::Row, )* );
+
+            const LEN: usize = $type_paramA::LEN $( + $type_param::LEN )*;
+
+            fn decode_from(values: &mut std::vec::IntoIter<Value>) -> Result<Self::Row, DecodeError> {
+                Ok(( $type_paramA::decode_from(values)?, $( $type_param::decode_from(values)?, )* ))
+            }
+        }
+
+        impl<$type_paramA $(, $type_param)*> TextColumns for ($type_paramA $(, $type_param)*)
+        where
+            $type_paramA: TextColumns,
+            $($type_param: TextColumns,)*
+            <$type_paramA>::Aggregation: Aggregation<recursive_aggregation!( $( <$type_param>::Aggregation, )* )>,
+        {
+            const LEN: usize = $type_paramA::LEN $( + $type_param::LEN )*;
+        }
+
+        impl<$type_paramA $(, $type_param)*> Orders for ($type_paramA $(, $type_param)*)
+        where
+            $type_paramA: OrderItem,
+            $($type_param: OrderItem,)*
+        {}
+
+        impl<$type_paramA $(, $type_param)*> BuildSql for ($type_paramA $(, $type_param)*)
+        where
+            $type_paramA: BuildSql,
+            $($type_param: BuildSql,)*
+        {
+            fn build_sql_with(&self, ctx: &mut BuildContext, buf: &mut Vec<u8>, params: &mut Vec<Value>) -> Result<(), BuildSqlError> {
+                self.$field0.build_sql_with(ctx, buf, params)?;
+
+                $(
+                    write!(buf, ", ")?;
+                    self.$field.build_sql_with(ctx, buf, params)?;
+                )*
+
+                Ok(())
+            }
+
+            fn size_hint(&self) -> SizeHint {
+                self.$field0.size_hint() $( + self.$field.size_hint() + ", ".len() )*
+            }
+        }
+
+        impl<$type_paramA $(, $type_param)*> BuildSql for Record<($type_paramA $(, $type_param)*)>
+        where
+            $type_paramA: BuildSql,
+            $($type_param: BuildSql,)*
+        {
+            fn build_sql_with(&self, ctx: &mut BuildContext, buf: &mut Vec<u8>, params: &mut Vec<Value>) -> Result<(), BuildSqlError> {
+                write!(buf, "(")?; // Rowって付けた方がいい？
+                self.columns.$field0.build_sql_with(ctx, buf, params)?;
+                $(
+                    write!(buf, ", ")?;
+                    self.columns.$field.build_sql_with(ctx, buf, params)?;
+                )*
+                write!(buf, ")")?;
+                Ok(())
+            }
+
+            fn size_hint(&self) -> SizeHint {
+                self.columns.$field0.size_hint() $( + self.columns.$field.size_hint() + ", ".len() )* + "()".len()
+            }
+        }
+    }
+}
+
+// Increase if needed.
+impl_traits_for_tuple!(A, 0, B, 1);
+impl_traits_for_tuple!(A, 0, B, 1, C, 2);
+impl_traits_for_tuple!(A, 0, B, 1, C, 2, D, 3);
+impl_traits_for_tuple!(A, 0, B, 1, C, 2, D, 3, E, 4);
+impl_traits_for_tuple!(A, 0, B, 1, C, 2, D, 3, E, 4, F, 5);
+impl_traits_for_tuple!(A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6);
+impl_traits_for_tuple!(A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7);
+impl_traits_for_tuple!(A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8);
+impl_traits_for_tuple!(A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9);
+impl_traits_for_tuple!(A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10);
+impl_traits_for_tuple!(A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
+    AC, 28
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
+    AC, 28, AD, 29
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
+    AC, 28, AD, 29, AE, 30
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
+    AC, 28, AD, 29, AE, 30, AF, 31
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
+    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
+    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
+    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
+    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
+    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
+    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
+    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37, AM, 38
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
+    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37, AM, 38, AN, 39
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
+    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37, AM, 38, AN, 39,
+    AO, 40
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
+    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37, AM, 38, AN, 39,
+    AO, 40, AP, 41
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
+    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37, AM, 38, AN, 39,
+    AO, 40, AP, 41, AQ, 42
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
+    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37, AM, 38, AN, 39,
+    AO, 40, AP, 41, AQ, 42, AR, 43
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
+    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37, AM, 38, AN, 39,
+    AO, 40, AP, 41, AQ, 42, AR, 43, AS, 44
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
+    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37, AM, 38, AN, 39,
+    AO, 40, AP, 41, AQ, 42, AR, 43, AS, 44, AT, 45
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
+    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37, AM, 38, AN, 39,
+    AO, 40, AP, 41, AQ, 42, AR, 43, AS, 44, AT, 45, AU, 46
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
+    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37, AM, 38, AN, 39,
+    AO, 40, AP, 41, AQ, 42, AR, 43, AS, 44, AT, 45, AU, 46, AV, 47
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
+    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37, AM, 38, AN, 39,
+    AO, 40, AP, 41, AQ, 42, AR, 43, AS, 44, AT, 45, AU, 46, AV, 47, AW, 48
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
+    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37, AM, 38, AN, 39,
+    AO, 40, AP, 41, AQ, 42, AR, 43, AS, 44, AT, 45, AU, 46, AV, 47, AW, 48, AX, 49
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
+    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37, AM, 38, AN, 39,
+    AO, 40, AP, 41, AQ, 42, AR, 43, AS, 44, AT, 45, AU, 46, AV, 47, AW, 48, AX, 49, AY, 50
+);
+impl_traits_for_tuple!(
+    A, 0, B, 1, C, 2, D, 3, E, 4, F, 5, G, 6, H, 7, I, 8, J, 9, K, 10, L, 11, M, 12, N, 13, O, 14,
+    P, 15, Q, 16, R, 17, S, 18, T, 19, U, 20, V, 21, W, 22, X, 23, Y, 24, Z, 25, AA, 26, AB, 27,
+    AC, 28, AD, 29, AE, 30, AF, 31, AG, 32, AH, 33, AI, 34, AJ, 35, AK, 36, AL, 37, AM, 38, AN, 39,
+    AO, 40, AP, 41, AQ, 42, AR, 43, AS, 44, AT, 45, AU, 46, AV, 47, AW, 48, AX, 49, AY, 50, AZ, 51
+);
+
+#[derive(Debug, Clone)]
+pub struct Row<T> {
+    columns: T,
+}
+
+impl<T: Columns> Row<T> {
+    pub fn new(columns: T) -> Row<T> {
+        Row { columns }
+    }
+}
+
+/// 事前にコードレベルでSQL上での型が確定できない場合に使用する。
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub struct SqlTypeAny;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub struct SqlTypeString;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub struct SqlTypeInt;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub struct SqlTypeUint;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub struct SqlTypeBool;
+
+/// JSON documents: `JSON` on MySQL, `jsonb` on Postgres and JSON text on SQLite.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub struct SqlTypeJson;
+
+pub trait Comparable<T> {}
+
+impl Comparable<SqlTypeString> for SqlTypeAny {}
+
+impl Comparable<SqlTypeInt> for SqlTypeAny {}
+
+impl Comparable<SqlTypeUint> for SqlTypeAny {}
+
+impl Comparable<SqlTypeBool> for SqlTypeAny {}
+
+impl Comparable<SqlTypeAny> for SqlTypeString {}
+
+impl Comparable<SqlTypeAny> for SqlTypeInt {}
+
+impl Comparable<SqlTypeAny> for SqlTypeUint {}
+
+impl Comparable<SqlTypeAny> for SqlTypeBool {}
+
+impl Comparable<SqlTypeInt> for SqlTypeUint {}
+
+impl Comparable<SqlTypeUint> for SqlTypeInt {}
+
+impl Comparable<SqlTypeJson> for SqlTypeAny {}
+
+impl Comparable<SqlTypeAny> for SqlTypeJson {}
+
+impl<T> Comparable<T> for T {}
+
+/// A SQL type which admits NULL, e.g. a column of the nullable side of an outer join.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub struct Nullable<ST>(ST);
+
+// Generic impls would overlap `impl<T> Comparable<T> for T`, so list the pairs.
+macro_rules! impl_nullable_comparable {
+    ( $( ( $lhs:ty, $rhs:ty ) ),* $(,)* ) => {
+        $(
+            impl Comparable<$rhs> for Nullable<$lhs> {}
+            impl Comparable<Nullable<$rhs>> for $lhs {}
+            impl Comparable<Nullable<$rhs>> for Nullable<$lhs> {}
+        )*
+    };
+    ( $( $ty:ty ),* $(,)* ) => {
+        $(
+            impl Comparable<$ty> for Nullable<$ty> {}
+            impl Comparable<Nullable<$ty>> for $ty {}
+        )*
+    };
+}
+
+impl_nullable_comparable!(
+    SqlTypeAny,
+    SqlTypeString,
+    SqlTypeInt,
+    SqlTypeUint,
+    SqlTypeBool,
+    SqlTypeJson
+);
+impl_nullable_comparable!(
+    (SqlTypeAny, SqlTypeString),
+    (SqlTypeAny, SqlTypeInt),
+    (SqlTypeAny, SqlTypeUint),
+    (SqlTypeAny, SqlTypeBool),
+    (SqlTypeString, SqlTypeAny),
+    (SqlTypeInt, SqlTypeAny),
+    (SqlTypeUint, SqlTypeAny),
+    (SqlTypeBool, SqlTypeAny),
+    (SqlTypeAny, SqlTypeJson),
+    (SqlTypeJson, SqlTypeAny),
+    (SqlTypeInt, SqlTypeUint),
+    (SqlTypeUint, SqlTypeInt),
+);
+
+/// SQL types which may be NULL. `is_null` and `is_not_null` need one of them.
+pub trait NullableSqlType {}
+
+impl<ST> NullableSqlType for Nullable<ST> {}
+impl NullableSqlType for SqlTypeAny {}
+
+/// The SQL type as it is when it may be NULL.
+pub trait IntoNullable {
+    type Nullable: Default;
+}
+
+macro_rules! impl_into_nullable {
+    ( $( $ty:ty ),* $(,)* ) => {
+        $(
+            impl IntoNullable for $ty {
+                type Nullable = Nullable<$ty>;
+            }
+        )*
+    };
+}
+
+impl_into_nullable!(
+    SqlTypeString,
+    SqlTypeInt,
+    SqlTypeUint,
+    SqlTypeBool,
+    SqlTypeJson
+);
+
+// SqlTypeAny already admits NULL.
+impl IntoNullable for SqlTypeAny {
+    type Nullable = SqlTypeAny;
+}
+
+impl<ST: Default> IntoNullable for Nullable<ST> {
+    type Nullable = Nullable<ST>;
+}
+
+/// Nullability of a source or a SQL type: columns of it are never NULL unless their type says so.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub struct NotNull;
+
+/// Nullability of a source or a SQL type: any of its values may be NULL.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub struct MaybeNull;
+
+/// Apply a nullability (`NotNull` or `MaybeNull`) to the SQL type `ST`.
+pub trait ApplyNullability<ST> {
+    type Output: Default;
+}
+
+impl<ST: Default> ApplyNullability<ST> for NotNull {
+    type Output = ST;
+}
+
+impl<ST: IntoNullable> ApplyNullability<ST> for MaybeNull {
+    type Output = ST::Nullable;
+}
+
+/// `MaybeNull` if any of both is.
+pub trait OrNullability<N> {
+    type Output;
+}
+
+impl OrNullability<NotNull> for NotNull {
+    type Output = NotNull;
+}
+
+impl OrNullability<MaybeNull> for NotNull {
+    type Output = MaybeNull;
+}
+
+impl<N> OrNullability<N> for MaybeNull {
+    type Output = MaybeNull;
+}
+
+/// Nullability of a SQL type, or of a tuple of SQL types (any of them).
+pub trait SqlTypeNullability {
+    type Nullability;
+}
+
+macro_rules! impl_sql_type_nullability {
+    ( $( $ty:ty ),* $(,)* ) => {
+        $(
+            impl SqlTypeNullability for $ty {
+                type Nullability = NotNull;
+            }
+        )*
+    };
+}
+
+impl_sql_type_nullability!(
+    SqlTypeAny,
+    SqlTypeString,
+    SqlTypeInt,
+    SqlTypeUint,
+    SqlTypeBool,
+    SqlTypeJson
+);
+
+impl<ST> SqlTypeNullability for Nullable<ST> {
+    type Nullability = MaybeNull;
+}
+
+impl SqlTypeNullability for () {
+    type Nullability = NotNull;
+}
+
+impl<A: SqlTypeNullability> SqlTypeNullability for (A,) {
+    type Nullability = A::Nullability;
+}
+
+impl<A, B> SqlTypeNullability for (A, B)
+where
+    A: SqlTypeNullability,
+    B: SqlTypeNullability,
+    A::Nullability: OrNullability<B::Nullability>,
+{
+    type Nullability = <A::Nullability as OrNullability<B::Nullability>>::Output;
+}
+
+/// `ST` made nullable if columns of the source `QS` may be NULL.
+pub type NullableIf<QS, ST> =
+    <<QS as SourceNullability>::Nullability as ApplyNullability<ST>>::Output;
+
+/// Return type of a SQL function: NULL in gives NULL out for `NonAggregate`,
+/// aggregates are NULL over no rows, and `NotNull` functions are never NULL.
+pub trait FunctionReturn<Args, Ret> {
+    type Output;
+}
+
+impl<Args, Ret> FunctionReturn<Args, Ret> for NonAggregate
+where
+    Args: SqlTypeNullability,
+    Args::Nullability: ApplyNullability<Ret>,
+{
+    type Output = <Args::Nullability as ApplyNullability<Ret>>::Output;
+}
+
+impl<Args, Ret: IntoNullable> FunctionReturn<Args, Ret> for Aggregate {
+    type Output = Ret::Nullable;
+}
+
+impl<Args, Ret> FunctionReturn<Args, Ret> for NotNull {
+    type Output = Ret;
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DecodeError {
+    #[error("DecodeError::UnexpectedNull")]
+    UnexpectedNull,
+    #[error("DecodeError::TypeMismatch: expected {expected}, found {found:?}")]
+    TypeMismatch {
+        expected: &'static str,
+        found: Value,
+    },
+    #[error("DecodeError::ColumnCount: expected {expected}, found {found}")]
+    ColumnCount { expected: usize, found: usize },
+}
+
+/// Decode a value of a SQL type into its Rust type. `Nullable<ST>` decodes into `Option`.
+pub trait DecodeValue {
+    type Rust;
+
+    /// Whether a NULL decodes, so that the column may be rolled up. See [`rollup`].
+    const MAY_BE_NULL: bool = false;
+
+    fn decode_value(value: Value) -> Result<Self::Rust, DecodeError>;
+}
+
+impl DecodeValue for SqlTypeAny {
+    type Rust = Value;
+
+    const MAY_BE_NULL: bool = true;
+
+    fn decode_value(value: Value) -> Result<Self::Rust, DecodeError> {
+        Ok(value)
+    }
+}
+
+impl DecodeValue for SqlTypeString {
+    type Rust = String;
+
+    fn decode_value(value: Value) -> Result<Self::Rust, DecodeError> {
+        match value {
+            Value::String(s) => Ok(s),
+            Value::Null => Err(DecodeError::UnexpectedNull),
+            found => Err(DecodeError::TypeMismatch {
+                expected: "string",
+                found,
+            }),
+        }
+    }
+}
+
+impl DecodeValue for SqlTypeInt {
+    type Rust = i64;
+
+    fn decode_value(value: Value) -> Result<Self::Rust, DecodeError> {
+        match value {
+            Value::Int(i) => Ok(i),
+            Value::Uint(u) if u <= i64::MAX as u64 => Ok(u as i64),
+            Value::Null => Err(DecodeError::UnexpectedNull),
+            found => Err(DecodeError::TypeMismatch {
+                expected: "int",
+                found,
+            }),
+        }
+    }
+}
+
+impl DecodeValue for SqlTypeUint {
+    type Rust = u64;
+
+    fn decode_value(value: Value) -> Result<Self::Rust, DecodeError> {
+        match value {
+            Value::Uint(u) => Ok(u),
+            Value::Int(i) if i >= 0 => Ok(i as u64),
+            Value::Null => Err(DecodeError::UnexpectedNull),
+            found => Err(DecodeError::TypeMismatch {
+                expected: "uint",
+                found,
+            }),
+        }
+    }
+}
+
+// Booleans come back as integers from MySQL and SQLite.
+impl DecodeValue for SqlTypeBool {
+    type Rust = bool;
+
+    fn decode_value(value: Value) -> Result<Self::Rust, DecodeError> {
+        match value {
+            Value::Bool(b) => Ok(b),
+            Value::Int(i @ 0..=1) => Ok(i == 1),
+            Value::Uint(u @ 0..=1) => Ok(u == 1),
+            Value::Null => Err(DecodeError::UnexpectedNull),
+            found => Err(DecodeError::TypeMismatch {
+                expected: "bool",
+                found,
+            }),
+        }
+    }
+}
+
+// JSON is decoded as its text.
+impl DecodeValue for SqlTypeJson {
+    type Rust = String;
+
+    fn decode_value(value: Value) -> Result<Self::Rust, DecodeError> {
+        match value {
+            Value::String(s) => Ok(s),
+            Value::Null => Err(DecodeError::UnexpectedNull),
+            found => Err(DecodeError::TypeMismatch {
+                expected: "json",
+                found,
+            }),
+        }
+    }
+}
+
+impl<ST: DecodeValue> DecodeValue for Nullable<ST> {
+    type Rust = Option<ST::Rust>;
+
+    const MAY_BE_NULL: bool = true;
+
+    fn decode_value(value: Value) -> Result<Self::Rust, DecodeError> {
+        match value {
+            Value::Null => Ok(None),
+            value => ST::decode_value(value).map(Some),
+        }
+    }
+}
+
+/// Decode the values of a result row by the `SqlType` of the selected columns.
+pub trait DecodeRow {
+    type Row;
+
+    /// Number of values of a row.
+    const LEN: usize;
+
+    fn decode_from(values: &mut std::vec::IntoIter<Value>) -> Result<Self::Row, DecodeError>;
+
+    fn decode_row(values: Vec<Value>) -> Result<Self::Row, DecodeError> {
+        if values.len() != Self::LEN {
+            return Err(DecodeError::ColumnCount {
+                expected: Self::LEN,
+                found: values.len(),
+            });
+        }
+        Self::decode_from(&mut values.into_iter())
+    }
+}
+
+impl<ST: DecodeValue> DecodeRow for ST {
+    type Row = ST::Rust;
+
+    const LEN: usize = 1;
+
+    fn decode_from(values: &mut std::vec::IntoIter<Value>) -> Result<Self::Row, DecodeError> {
+        ST::decode_value(values.next().unwrap_or(Value::Null))
+    }
+}
+
+/// Rust type of a result row of a select with the columns `C`.
+pub type RowOf<C> = <<C as Columns>::SqlType as DecodeRow>::Row;
+
+macro_rules! impl_bool_binary_operators {
+    ( $( ( $ty:ident, $op:expr ) ),* $(,)* ) => {
+        $(
+            #[derive(Debug, Clone)]
+            pub struct $ty<L, R>
+            where
+                L: Expression,
+                R: Expression,
+                L::SqlType: Comparable<R::SqlType>,
+            {
+                lhs: L,
+                rhs: R,
+            }
+
+            impl<L, R> Expression for $ty<L, R>
+            where
+                L: Expression,
+                R: Expression,
+                L::SqlType: Comparable<R::SqlType>,
+                L::Aggregation: Aggregation<R::Aggregation>,
+            {
+                type SqlType = SqlTypeBool;
+                type Term = Polynomial;
+                type BoolOperation = BoolMono;
+                type Aggregation = <L::Aggregation as Aggregation<R::Aggregation>>::Output;
+            }
+
+            impl<L, R> AndOperatorMethod for $ty<L, R>
+            where
+                L: Expression,
+                R: Expression,
+                L::SqlType: Comparable<R::SqlType>,
+                Self: Expression<SqlType = SqlTypeBool>,
+            {}
+
+            impl<L,R> OrOperatorMethod for $ty<L,R>
+            where
+                L: Expression,
+                R: Expression,
+                L::SqlType: Comparable<R::SqlType>,
+                Self: Expression<SqlType = SqlTypeBool>,
+            {}
+
+            impl<L,R> NotOperatorMethod for $ty<L,R>
+            where
+                L: Expression,
+                R: Expression,
+                L::SqlType: Comparable<R::SqlType>,
+                Self: Expression<SqlType = SqlTypeBool>,
+            {}
+
+            impl<L, R> BuildSql for $ty<L, R>
+            where
+                L: Expression + BuildSql,
+                R: Expression + BuildSql,
+                L::SqlType: Comparable<R::SqlType>,
+            {
+                fn build_sql_with(
+                    &self,
+                    ctx: &mut BuildContext,
+                    buf: &mut Vec<u8>,
+                    params: &mut Vec<Value>,
+                ) -> Result<(), BuildSqlError> {
+                    (|| -> Result<(), anyhow::Error> {
+                        ctx.record_visit(|| VisitEvent::Operator { operator: $op.trim() });
+                        self.lhs.build_sql_with(ctx, buf, params)?;
+                        write!(buf, $op)?;
+                        self.rhs.build_sql_with(ctx, buf, params)?;
+                        Ok(())
+                    })()
+                    .map_err(From::from)
+                }
+
+                fn size_hint(&self) -> SizeHint {
+                    self.lhs.size_hint() + $op.len() + self.rhs.size_hint()
+                }
+            }
+        )*
+    };
+}
+
+impl_bool_binary_operators!(
+    (Eq, " = "),
+    (NotEq, " != "),
+    (Gt, " > "),
+    (Ge, " >= "),
+    (Lt, " < "),
+    (Le, " <= "),
+);
+
+macro_rules! impl_subquery_bool_binary_operators {
+    ( $( ( $ty:ident, $op:expr ) ),* $(,)* ) => {
+        $(
+            #[derive(Clone)]
+            pub struct $ty<Lhs, QS, W, C, G, H, O, L, LM>
+            where
+                C: Columns,
+                Lhs: Expression,
+                Lhs::SqlType: Comparable<C::SqlType>,
+            {
+                lhs: Lhs,
+                rhs: SelectBuilder<QS, W, C, G, H, O, L, LM>,
+            }
+
+            impl<Lhs, QS, W, C, G, H, O, L, LM> Expression for $ty<Lhs, QS, W, C, G, H, O, L, LM>
+            where
+                C: Columns,
+                Lhs: Expression,
+                Lhs::SqlType: Comparable<C::SqlType>,
+            {
+                type SqlType = SqlTypeBool;
+                type Term = Polynomial;
+                type BoolOperation = BoolMono;
+                type Aggregation = NonAggregate;
+            }
+
+            impl<Lhs, QS, W, C, G, H, O, L, LM> AndOperatorMethod for $ty<Lhs, QS, W, C, G, H, O, L, LM>
+            where
+                C: Columns,
+                Lhs: Expression,
+                Lhs::SqlType: Comparable<C::SqlType>,
+                Self: Expression<SqlType = SqlTypeBool>,
+            {}
+
+            impl<Lhs, QS, W, C, G, H, O, L, LM> OrOperatorMethod for $ty<Lhs, QS, W, C, G, H, O, L, LM>
+            where
+                C: Columns,
+                Lhs: Expression,
+                Lhs::SqlType: Comparable<C::SqlType>,
+                Self: Expression<SqlType = SqlTypeBool>,
+            {}
+
+            impl<Lhs, QS, W, C, G, H, O, L, LM> NotOperatorMethod for $ty<Lhs, QS, W, C, G, H, O, L, LM>
+            where
+                C: Columns,
+                Lhs: Expression,
+                Lhs::SqlType: Comparable<C::SqlType>,
+                Self: Expression<SqlType = SqlTypeBool>,
+            {}
+
+            impl<Lhs, QS, W, C, G, H, O, L, LM> BuildSql for $ty<Lhs, QS, W, C, G, H, O, L, LM>
+            where
+                QS: BuildSql,
+                C: BuildSql + Columns,
+                W: BuildSql,
+                G: BuildSql,
+                H: BuildSql,
+                O: BuildSql,
+                L: BuildSql,
+                LM: BuildSql,
+                Lhs: Expression + BuildSql,
+                Lhs::SqlType: Comparable<C::SqlType>,
+            {
+                fn build_sql_with(
+                    &self,
+                    ctx: &mut BuildContext,
+                    buf: &mut Vec<u8>,
+                    params: &mut Vec<Value>,
+                ) -> Result<(), BuildSqlError> {
+                    (|| -> Result<(), anyhow::Error> {
+                        ctx.record_visit(|| VisitEvent::Operator { operator: $op.trim() });
+                        self.lhs.build_sql_with(ctx, buf, params)?;
+                        write!(buf, $op)?;
+                        self.rhs.build_sql_with(ctx, buf, params)?;
+                        Ok(())
+                    })()
+                    .map_err(From::from)
+                }
+
+                fn size_hint(&self) -> SizeHint {
+                    self.lhs.size_hint() + $op.len() + self.rhs.size_hint()
+                }
+            }
+        )*
+    };
+}
+
+impl_subquery_bool_binary_operators!(
+    (EqAny, " = ANY "),
+    (NotEqAny, " != ANY "),
+    (GtAny, " > ANY "),
+    (GeAny, " >= ANY "),
+    (LtAny, " < ANY "),
+    (LeAny, " <= ANY "),
+    (EqAll, " = ALL "),
+    (NotEqAll, " != ALL "),
+    (GtAll, " > ALL "),
+    (GeAll, " >= ALL "),
+    (LtAll, " < ALL "),
+    (LeAll, " <= ALL "),
+);
+
+#[derive(Debug, Clone)]
+pub struct Between<T, L, U>
+where
+    T: Expression,
+    L: Expression,
+    U: Expression,
+    L::SqlType: Comparable<T::SqlType>,
+    U::SqlType: Comparable<T::SqlType>,
+{
+    target: T,
+    lower_bound: L,
+    upper_bound: U,
+}
+
+impl<T, L, U> Expression for Between<T, L, U>
+where
+    T: Expression,
+    L: Expression,
+    U: Expression,
+    L::SqlType: Comparable<T::SqlType>,
+    U::SqlType: Comparable<T::SqlType>,
+{
+    type SqlType = SqlTypeBool;
+    type Term = Polynomial;
+    type BoolOperation = BoolMono;
+    type Aggregation = NonAggregate;
+}
+
+impl<T, L, U> AndOperatorMethod for Between<T, L, U>
+where
+    T: Expression,
+    L: Expression,
+    U: Expression,
+    L::SqlType: Comparable<T::SqlType>,
+    U::SqlType: Comparable<T::SqlType>,
+    Self: Expression<SqlType = SqlTypeBool>,
+{
+}
+
+impl<T, L, U> OrOperatorMethod for Between<T, L, U>
+where
+    T: Expression,
+    L: Expression,
+    U: Expression,
+    L::SqlType: Comparable<T::SqlType>,
+    U::SqlType: Comparable<T::SqlType>,
+    Self: Expression<SqlType = SqlTypeBool>,
+{
+}
+
+impl<T, L, U> NotOperatorMethod for Between<T, L, U>
+where
+    T: Expression,
+    L: Expression,
+    U: Expression,
+    L::SqlType: Comparable<T::SqlType>,
+    U::SqlType: Comparable<T::SqlType>,
+    Self: Expression<SqlType = SqlTypeBool>,
+{
+}
+
+impl<T, L, U> BuildSql for Between<T, L, U>
+where
+    T: Expression + BuildSql,
+    L: Expression + BuildSql,
+    U: Expression + BuildSql,
+    L::SqlType: Comparable<T::SqlType>,
+    U::SqlType: Comparable<T::SqlType>,
+{
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        (|| -> Result<(), anyhow::Error> {
+            ctx.record_visit(|| VisitEvent::Operator {
+                operator: "BETWEEN",
+            });
+            self.target.build_sql_with(ctx, buf, params)?;
+            write!(buf, " BETWEEN ")?;
+            self.lower_bound.build_sql_with(ctx, buf, params)?;
+            write!(buf, " AND ")?;
+            self.upper_bound.build_sql_with(ctx, buf, params)?;
+            Ok(())
+        })()
+        .map_err(From::from)
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.target.size_hint()
+            + " BETWEEN ".len()
+            + self.lower_bound.size_hint()
+            + " AND ".len()
+            + self.upper_bound.size_hint()
+    }
+}
+
+macro_rules! impl_in_operators {
+( $( ( $ty:ident, $op:expr ) ),* $(,)* ) => {
+    $(
+        #[derive(Debug, Clone)]
+        pub struct $ty<L, R, ARR>
+        where
+            L: Expression,
+            R: Expression,
+            R::SqlType: Comparable<L::SqlType>,
+            ARR: AsRef<[R]>,
+        {
+            lhs: L,
+            rhs: ARR,
+            rhs_value: PhantomData<R>,
+        }
+
+        impl<L, R, ARR> Expression for $ty<L, R, ARR>
+        where
+            L: Expression,
+            R: Expression,
+            R::SqlType: Comparable<L::SqlType>,
+            ARR: AsRef<[R]>,
+            R::Aggregation: Aggregation<L::Aggregation>,
+        {
+            type SqlType = SqlTypeBool;
+            type Term = Polynomial;
+            type BoolOperation = BoolMono;
+            type Aggregation = <R::Aggregation as Aggregation<L::Aggregation>>::Output;
+        }
+
+        impl<L, R, ARR> AndOperatorMethod for $ty<L, R, ARR>
+        where
+            L: Expression,
+            R: Expression,
+            R::SqlType: Comparable<L::SqlType>,
+            ARR: AsRef<[R]>,
+            Self: Expression<SqlType = SqlTypeBool>,
+        {}
+
+        impl<L, R, ARR> OrOperatorMethod for $ty<L, R, ARR>
+        where
+            L: Expression,
+            R: Expression,
+            R::SqlType: Comparable<L::SqlType>,
+            ARR: AsRef<[R]>,
+            Self: Expression<SqlType = SqlTypeBool>,
+        {}
+
+        impl<L, R, ARR> NotOperatorMethod for $ty<L, R, ARR>
+        where
+            L: Expression,
+            R: Expression,
+            R::SqlType: Comparable<L::SqlType>,
+            ARR: AsRef<[R]>,
+            Self: Expression<SqlType = SqlTypeBool>,
+        {}
+
+        impl<L, R, ARR> BuildSql for $ty<L, R, ARR>
+        where
+            L: Expression + BuildSql,
+            R: Expression + BuildSql + Clone + Into<Value>,
+            R::SqlType: Comparable<L::SqlType>,
+            ARR: AsRef<[R]>,
+        {
+            fn build_sql_with(
+                &self,
+                ctx: &mut BuildContext,
+                buf: &mut Vec<u8>,
+                params: &mut Vec<Value>,
+            ) -> Result<(), BuildSqlError> {
+                (|| -> Result<(), anyhow::Error> {
+                    ctx.record_visit(|| VisitEvent::Operator { operator: $op.trim() });
+                    self.lhs.build_sql_with(ctx, buf, params)?;
+                    write!(buf, $op)?;
+                    write!(buf, "(")?;
+                    let mut is_first = true;
+                    for v in self.rhs.as_ref() {
+                        if is_first {
+                            is_first = false;
+                        } else {
+                            write!(buf, ", ")?;
+                        }
+                        ctx.write_param(buf, params, (*v).clone().into())?;
+                    }
+                    write!(buf, ")")?;
+                    Ok(())
+                })()
+                .map_err(From::from)
+            }
+
+            fn size_hint(&self) -> SizeHint {
+                let n = self.rhs.as_ref().len();
+                let values = SizeHint::new(("?, ".len() * n).saturating_sub(", ".len()), n);
+                self.lhs.size_hint() + $op.len() + "()".len() + values
+            }
+        }
+    )*
+};
+}
+
+impl_in_operators!((Any, " IN "), (NotAny, " NOT IN "));
+
+macro_rules! impl_null_check_operators {
+    ( $( ( $ty:ident, $op:expr ) ),* $(,)* ) => {
+        $(
+            #[derive(Debug, Clone)]
+            pub struct $ty<T>
+            where
+                T: Expression,
+            {
+                target: T
+            }
+
+            impl<T> Expression for $ty<T>
+            where
+                T: Expression,
+            {
+                type SqlType = SqlTypeBool;
+                type Term = Monomial;
+                type BoolOperation = BoolMono;
+                type Aggregation = T::Aggregation;
+            }
+
+            impl<T> AndOperatorMethod for $ty<T>
+            where
+                T: Expression,
+                Self: Expression<SqlType = SqlTypeBool>,
+            {}
+
+            impl<T> OrOperatorMethod for $ty<T>
+            where
+                T: Expression,
+                Self: Expression<SqlType = SqlTypeBool>,
+            {}
+
+            impl<T> NotOperatorMethod for $ty<T>
+            where
+                T: Expression,
+                Self: Expression<SqlType = SqlTypeBool>,
+            {}
+
+            impl<T> BuildSql for $ty<T>
+            where
+                T: Expression + BuildSql,
+            {
+                fn build_sql_with(
+                    &self,
+                    ctx: &mut BuildContext,
+                    buf: &mut Vec<u8>,
+                    params: &mut Vec<Value>,
+                ) -> Result<(), BuildSqlError> {
+                    (|| -> Result<(), anyhow::Error> {
+                        ctx.record_visit(|| VisitEvent::Operator { operator: $op.trim() });
+                        self.target.build_sql_with(ctx, buf, params)?;
+                        write!(buf, $op)?;
+                        Ok(())
+                    })()
+                    .map_err(From::from)
+                }
+
+                fn size_hint(&self) -> SizeHint {
+                    self.target.size_hint() + $op.len()
+                }
+            }
+        )*
+    };
+}
+
+impl_null_check_operators!((IsNull, " IS NULL"), (IsNotNull, " IS NOT NULL"));
+
+/// `LIKE` pattern built from user input by [`like_escape`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct LikePattern {
+    pattern: String,
+}
+
+impl LikePattern {
+    /// Escape character rendered in the `ESCAPE` clause.
+    /// `!` is used because a backslash needs escaping differently in each dialect.
+    pub const ESCAPE_CHAR: char = '!';
+
+    /// Match values starting with the input.
+    pub fn starts_with(mut self) -> LikePattern {
+        self.pattern.push('%');
+        self
+    }
+
+    /// Match values ending with the input.
+    pub fn ends_with(mut self) -> LikePattern {
+        self.pattern.insert(0, '%');
+        self
+    }
+
+    /// Match values containing the input.
+    pub fn contains(self) -> LikePattern {
+        self.starts_with().ends_with()
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+}
+
+/// Escape `%`, `_` and the escape character in `input` so that `LIKE` matches it literally.
+///
+/// The result is not an expression: it is only accepted by `like()` / `not_like()`,
+/// which render `LIKE ? ESCAPE '!'`.
+///
+/// ```compile_fail
+/// use aqueryon::query_builder::*;
+///
+/// let (builder, t1) = EmptySelectBuilder::new().source("users");
+/// builder.filter(t1.column("name").eq(like_escape("50%")));
+/// ```
+pub fn like_escape(input: &str) -> LikePattern {
+    let mut pattern = String::with_capacity(input.len() + 2);
+    for c in input.chars() {
+        if c == '%' || c == '_' || c == LikePattern::ESCAPE_CHAR {
+            pattern.push(LikePattern::ESCAPE_CHAR);
+        }
+        pattern.push(c);
+    }
+    LikePattern { pattern }
+}
+
+impl BuildSql for LikePattern {
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        ctx.write_param(buf, params, Value::String(self.pattern.clone()))?;
+        write!(buf, " ESCAPE '{}'", LikePattern::ESCAPE_CHAR)?;
+        Ok(())
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::new("? ESCAPE '!'".len(), 1)
+    }
+}
+
+/// Right hand side of `LIKE`: a string expression or a [`LikePattern`].
+pub trait LikeOperand: BuildSql {
+    type Aggregation;
+}
+
+impl<E> LikeOperand for E
+where
+    E: Expression + BuildSql,
+    E::SqlType: Comparable<SqlTypeString>,
+{
+    type Aggregation = E::Aggregation;
+}
+
+impl LikeOperand for LikePattern {
+    type Aggregation = NonAggregate;
+}
+
+macro_rules! impl_like_operators {
+    ( $( ( $ty:ident, $op:expr ) ),* $(,)* ) => {
+        $(
+            #[derive(Debug, Clone)]
+            pub struct $ty<L, R> {
+                lhs: L,
+                rhs: R,
+            }
+
+            impl<L, R> Expression for $ty<L, R>
+            where
+                L: Expression,
+                R: LikeOperand,
+                L::Aggregation: Aggregation<R::Aggregation>,
+            {
+                type SqlType = SqlTypeBool;
+                type Term = Polynomial;
+                type BoolOperation = BoolMono;
+                type Aggregation = <L::Aggregation as Aggregation<R::Aggregation>>::Output;
+            }
+
+            impl<L, R> AndOperatorMethod for $ty<L, R> where Self: Expression<SqlType = SqlTypeBool> {}
+
+            impl<L, R> OrOperatorMethod for $ty<L, R> where Self: Expression<SqlType = SqlTypeBool> {}
+
+            impl<L, R> NotOperatorMethod for $ty<L, R> where Self: Expression<SqlType = SqlTypeBool> {}
+
+            impl<L, R> BuildSql for $ty<L, R>
+            where
+                L: BuildSql,
+                R: LikeOperand,
+            {
+                fn build_sql_with(
+                    &self,
+                    ctx: &mut BuildContext,
+                    buf: &mut Vec<u8>,
+                    params: &mut Vec<Value>,
+                ) -> Result<(), BuildSqlError> {
+                    ctx.record_visit(|| VisitEvent::Operator { operator: $op.trim() });
+                    self.lhs.build_sql_with(ctx, buf, params)?;
+                    write!(buf, $op)?;
+                    self.rhs.build_sql_with(ctx, buf, params)
+                }
+
+                fn size_hint(&self) -> SizeHint {
+                    self.lhs.size_hint() + $op.len() + self.rhs.size_hint()
+                }
+            }
+        )*
+    };
+}
+
+impl_like_operators!((Like, " LIKE "), (NotLike, " NOT LIKE "));
+
+pub trait LikeOperatorMethod: Expression + Sized
+where
+    Self::SqlType: Comparable<SqlTypeString>,
+{
+    /// SQL `LIKE`.
+    fn like<R: LikeOperand>(self, rhs: R) -> Like<Self, R> {
+        Like { lhs: self, rhs }
+    }
+
+    /// SQL `NOT LIKE`.
+    fn not_like<R: LikeOperand>(self, rhs: R) -> NotLike<Self, R> {
+        NotLike { lhs: self, rhs }
+    }
+}
+
+impl<T> LikeOperatorMethod for T
+where
+    T: Expression,
+    T::SqlType: Comparable<SqlTypeString>,
+{
+}
+
+pub trait CompareBinaryOperatorMethod<R>: Expression + Sized
+where
+    R: Expression,
+    Self::SqlType: Comparable<R::SqlType>,
+{
+    /// SQL `=`.
+    fn eq(self, rhs: R) -> Eq<Self, R> {
+        Eq { lhs: self, rhs }
+    }
+
+    /// SQL `!=`.
+    fn not_eq(self, rhs: R) -> NotEq<Self, R> {
+        NotEq { lhs: self, rhs }
+    }
+
+    /// SQL `>`.
+    fn gt(self, rhs: R) -> Gt<Self, R> {
+        Gt { lhs: self, rhs }
+    }
+
+    /// SQL `>=`.
+    fn ge(self, rhs: R) -> Ge<Self, R> {
+        Ge { lhs: self, rhs }
+    }
+
+    /// SQL `<`.
+    fn lt(self, rhs: R) -> Lt<Self, R> {
+        Lt { lhs: self, rhs }
+    }
+
+    /// SQL `<=`.
+    fn le(self, rhs: R) -> Le<Self, R> {
+        Le { lhs: self, rhs }
+    }
+}
+
+// これをSelectBuilder毎に定義する必要がある
+// DBによってサポートしてたりしてなかったりなので、それが正しいのかもしれない
+pub trait SubQueryCompareBinaryOperatorMethod<QS, W, C, G, H, O, L, LM>:
+    Expression + Sized
+where
+    C: Columns,
+    Self::SqlType: Comparable<C::SqlType>,
+{
+    /// SQL `= ANY (...)`.
+    fn eq_any(
+        self,
+        rhs: SelectBuilder<QS, W, C, G, H, O, L, LM>,
+    ) -> EqAny<Self, QS, W, C, G, H, O, L, LM> {
+        EqAny { lhs: self, rhs }
+    }
+
+    /// SQL `!= ANY (...)`.
+    fn not_eq_any(
+        self,
+        rhs: SelectBuilder<QS, W, C, G, H, O, L, LM>,
+    ) -> NotEqAny<Self, QS, W, C, G, H, O, L, LM> {
+        NotEqAny { lhs: self, rhs }
+    }
+
+    /// SQL `> ANY (...)`.
+    fn gt_any(
+        self,
+        rhs: SelectBuilder<QS, W, C, G, H, O, L, LM>,
+    ) -> GtAny<Self, QS, W, C, G, H, O, L, LM> {
+        GtAny { lhs: self, rhs }
+    }
+
+    /// SQL `>= ANY (...)`.
+    fn ge_any(
+        self,
+        rhs: SelectBuilder<QS, W, C, G, H, O, L, LM>,
+    ) -> GeAny<Self, QS, W, C, G, H, O, L, LM> {
+        GeAny { lhs: self, rhs }
+    }
+
+    /// SQL `< ANY (...)`.
+    fn lt_any(
+        self,
+        rhs: SelectBuilder<QS, W, C, G, H, O, L, LM>,
+    ) -> LtAny<Self, QS, W, C, G, H, O, L, LM> {
+        LtAny { lhs: self, rhs }
+    }
+
+    /// SQL `<= ANY (...)`.
+    fn le_any(
+        self,
+        rhs: SelectBuilder<QS, W, C, G, H, O, L, LM>,
+    ) -> LeAny<Self, QS, W, C, G, H, O, L, LM> {
+        LeAny { lhs: self, rhs }
+    }
+
+    /// SQL `= ALL (...)`.
+    fn eq_all(
+        self,
+        rhs: SelectBuilder<QS, W, C, G, H, O, L, LM>,
+    ) -> EqAll<Self, QS, W, C, G, H, O, L, LM> {
+        EqAll { lhs: self, rhs }
+    }
+
+    /// SQL `!= ALL (...)`.
+    fn not_eq_all(
+        self,
+        rhs: SelectBuilder<QS, W, C, G, H, O, L, LM>,
+    ) -> NotEqAll<Self, QS, W, C, G, H, O, L, LM> {
+        NotEqAll { lhs: self, rhs }
+    }
+
+    /// SQL `> ALL (...)`.
+    fn gt_all(
+        self,
+        rhs: SelectBuilder<QS, W, C, G, H, O, L, LM>,
+    ) -> GtAll<Self, QS, W, C, G, H, O, L, LM> {
+        GtAll { lhs: self, rhs }
+    }
+
+    /// SQL `>= ALL (...)`.
+    fn ge_all(
+        self,
+        rhs: SelectBuilder<QS, W, C, G, H, O, L, LM>,
+    ) -> GeAll<Self, QS, W, C, G, H, O, L, LM> {
+        GeAll { lhs: self, rhs }
+    }
+
+    /// SQL `< ALL (...)`.
+    fn lt_all(
+        self,
+        rhs: SelectBuilder<QS, W, C, G, H, O, L, LM>,
+    ) -> LtAll<Self, QS, W, C, G, H, O, L, LM> {
+        LtAll { lhs: self, rhs }
+    }
+
+    /// SQL `<= ALL (...)`.
+    fn le_all(
+        self,
+        rhs: SelectBuilder<QS, W, C, G, H, O, L, LM>,
+    ) -> LeAll<Self, QS, W, C, G, H, O, L, LM> {
+        LeAll { lhs: self, rhs }
+    }
+}
+
+pub trait BetweenOperatorMethod<L, U>: Expression + Sized
+where
+    L: Expression,
+    U: Expression,
+    L::SqlType: Comparable<Self::SqlType>,
+    U::SqlType: Comparable<Self::SqlType>,
+{
+    /// SQL `BETWEEN`.
+    fn between(self, lower_bound: L, upper_bound: U) -> Between<Self, L, U> {
+        Between {
+            target: self,
+            lower_bound,
+            upper_bound,
+        }
+    }
+}
+
+pub trait InOperatorMethod<T>: Expression + Sized
+where
+    T: Expression,
+    T::SqlType: Comparable<Self::SqlType>,
+{
+    /// SQL `IN`.
+    fn any<V: AsRef<[T]>>(self, values: V) -> Any<Self, T, V> {
+        Any {
+            lhs: self,
+            rhs: values,
+            rhs_value: PhantomData,
+        }
+    }
+
+    /// SQL `NOT IN`.
+    fn not_any<V: AsRef<[T]>>(self, values: V) -> NotAny<Self, T, V> {
+        NotAny {
+            lhs: self,
+            rhs: values,
+            rhs_value: PhantomData,
+        }
+    }
+}
+
+/// `IS NULL` checks, only for expressions of a [`NullableSqlType`].
+///
+/// ```compile_fail
+/// use aqueryon::query_builder::*;
+///
+/// let (builder, t1) = EmptySelectBuilder::new().source("users");
+/// builder.filter(t1.typed_column::<SqlTypeInt>("id").is_null());
+/// ```
+pub trait NullCheckOperatorMethod: Expression + Sized {
+    /// SQL `IS NULL`.
+    #[allow(clippy::wrong_self_convention)]
+    fn is_null(self) -> IsNull<Self> {
+        IsNull { target: self }
+    }
+
+    /// SQL `IS NOT NULL`.
+    #[allow(clippy::wrong_self_convention)]
+    fn is_not_null(self) -> IsNotNull<Self> {
+        IsNotNull { target: self }
+    }
+}
+
+impl<L, R> CompareBinaryOperatorMethod<R> for L
+where
+    L: Expression,
+    R: Expression,
+    L::SqlType: Comparable<R::SqlType>,
+{
+}
+
+impl<Lhs, QS, W, C, G, H, O, L, LM> SubQueryCompareBinaryOperatorMethod<QS, W, C, G, H, O, L, LM>
+    for Lhs
+where
+    C: Columns,
+    Lhs: Expression,
+    Lhs::SqlType: Comparable<C::SqlType>,
+{
+}
+
+impl<T, L, U> BetweenOperatorMethod<L, U> for T
+where
+    T: Expression,
+    L: Expression,
+    U: Expression,
+    L::SqlType: Comparable<T::SqlType>,
+    U::SqlType: Comparable<T::SqlType>,
+{
+}
+
+impl<L, T> InOperatorMethod<T> for L
+where
+    L: Expression,
+    T: Expression,
+    T::SqlType: Comparable<L::SqlType>,
+{
+}
+
+impl<T> NullCheckOperatorMethod for T
+where
+    T: Expression,
+    T::SqlType: NullableSqlType,
+{
+}
+
+#[derive(Debug, Clone)]
+pub struct And<L, LK, R, RK> {
+    lhs: L,
+    rhs: R,
+    lhs_kind: PhantomData<LK>,
+    rhs_kind: PhantomData<RK>,
+}
+
+impl<L, R> And<L, L::BoolOperation, R, R::BoolOperation>
+where
+    L: Expression,
+    R: Expression,
+{
+    pub fn new(lhs: L, rhs: R) -> Self {
+        And {
+            lhs,
+            rhs,
+            lhs_kind: PhantomData,
+            rhs_kind: PhantomData,
+        }
+    }
+}
+
+impl<L, R> Expression for And<L, L::BoolOperation, R, R::BoolOperation>
+where
+    L: Expression,
+    R: Expression,
+    L::SqlType: Comparable<SqlTypeBool>,
+    R::SqlType: Comparable<L::SqlType>,
+    L::Aggregation: Aggregation<R::Aggregation>,
+{
+    type SqlType = SqlTypeBool;
+    type Term = Polynomial;
+    type BoolOperation = BoolAnd;
+    type Aggregation = <L::Aggregation as Aggregation<R::Aggregation>>::Output;
+}
+
+impl<L, R> AndOperatorMethod for And<L, L::BoolOperation, R, R::BoolOperation>
+where
+    L: Expression,
+    R: Expression,
+    L::SqlType: Comparable<SqlTypeBool>,
+    R::SqlType: Comparable<L::SqlType>,
+    Self: Expression<SqlType = SqlTypeBool>,
+{
+}
+
+impl<L, R> NotOperatorMethod for And<L, L::BoolOperation, R, R::BoolOperation>
+where
+    L: Expression,
+    R: Expression,
+    L::SqlType: Comparable<SqlTypeBool>,
+    R::SqlType: Comparable<L::SqlType>,
+    Self: Expression<SqlType = SqlTypeBool>,
+{
+}
+
+#[derive(Debug, Clone)]
+pub struct Or<L, LK, R, RK> {
+    lhs: L,
+    rhs: R,
+    lhs_kind: PhantomData<LK>,
+    rhs_kind: PhantomData<RK>,
+}
+
+impl<L, R> Or<L, L::BoolOperation, R, R::BoolOperation>
+where
+    L: Expression,
+    R: Expression,
+{
+    pub fn new(lhs: L, rhs: R) -> Self {
+        Or {
+            lhs,
+            rhs,
+            lhs_kind: PhantomData,
+            rhs_kind: PhantomData,
+        }
+    }
+}
+
+impl<L, R> Expression for Or<L, L::BoolOperation, R, R::BoolOperation>
+where
+    L: Expression,
+    R: Expression,
+    L::SqlType: Comparable<SqlTypeBool>,
+    R::SqlType: Comparable<L::SqlType>,
+    L::Aggregation: Aggregation<R::Aggregation>,
+{
+    type SqlType = SqlTypeBool;
+    type Term = Polynomial;
+    type BoolOperation = BoolOr;
+    type Aggregation = <L::Aggregation as Aggregation<R::Aggregation>>::Output;
+}
+
+impl<L, R> OrOperatorMethod for Or<L, L::BoolOperation, R, R::BoolOperation>
+where
+    L: Expression,
+    R: Expression,
+    L::SqlType: Comparable<SqlTypeBool>,
+    R::SqlType: Comparable<L::SqlType>,
+    Self: Expression<SqlType = SqlTypeBool>,
+{
+}
+
+impl<L, R> NotOperatorMethod for Or<L, L::BoolOperation, R, R::BoolOperation>
+where
+    L: Expression,
+    R: Expression,
+    L::SqlType: Comparable<SqlTypeBool>,
+    R::SqlType: Comparable<L::SqlType>,
+    Self: Expression<SqlType = SqlTypeBool>,
+{
+}
+
+macro_rules! impl_build_sql_and_or {
+    ( $ty:ident, $op:expr, both_no_parentheses, $l_kind:ty, $r_kind:ty ) => {
+        impl<L, R> BuildSql for $ty<L, $l_kind, R, $r_kind>
+        where
+            L: Expression<BoolOperation = $l_kind> + BuildSql,
+            R: Expression<BoolOperation = $r_kind> + BuildSql,
+            L::SqlType: Comparable<SqlTypeBool>,
+            R::SqlType: Comparable<SqlTypeBool>,
+        {
+            fn build_sql_with(
+                &self,
+                ctx: &mut BuildContext,
+                buf: &mut Vec<u8>,
+                params: &mut Vec<Value>,
+            ) -> Result<(), BuildSqlError> {
+                (|| -> Result<(), anyhow::Error> {
+                    ctx.record_visit(|| VisitEvent::Operator {
+                        operator: $op.trim(),
+                    });
+                    self.lhs.build_sql_with(ctx, buf, params)?;
+                    write!(buf, $op)?;
+                    self.rhs.build_sql_with(ctx, buf, params)?;
+                    Ok(())
+                })()
+                .map_err(From::from)
             }
-        )*
-    };
-}
-
-impl_bool_binary_operators!(
-    (Eq, " = "),
-    (NotEq, " != "),
-    (Gt, " > "),
-    (Ge, " >= "),
-    (Lt, " < "),
-    (Le, " <= "),
-    (Like, " LIKE "),
-    (NotLike, " NOT LIKE "),
-);
 
-macro_rules! impl_subquery_bool_binary_operators {
-    ( $( ( $ty:ident, $op:expr ) ),* $(,)* ) => {
-        $(
-            #[derive(Clone)]
-            pub struct $ty<Lhs, QS, W, C, G, H, O, L, LM>
-            where
-                C: Columns,
-                Lhs: Expression,
-                Lhs::SqlType: Comparable<C::SqlType>,
-            {
-                lhs: Lhs,
-                rhs: SelectBuilder<QS, W, C, G, H, O, L, LM>,
+            fn size_hint(&self) -> SizeHint {
+                self.lhs.size_hint() + $op.len() + self.rhs.size_hint()
             }
-
-            impl<Lhs, QS, W, C, G, H, O, L, LM> Expression for $ty<Lhs, QS, W, C, G, H, O, L, LM>
-            where
-                C: Columns,
-                Lhs: Expression,
-                Lhs::SqlType: Comparable<C::SqlType>,
-            {
-                type SqlType = SqlTypeBool;
-                type Term = Polynomial;
-                type BoolOperation = BoolMono;
-                type Aggregation = NonAggregate;
+        }
+    };
+    ( $ty:ident, $op:expr, lhs_parentheses, $l_kind:ty, $r_kind:ty ) => {
+        impl<L, R> BuildSql for $ty<L, $l_kind, R, $r_kind>
+        where
+            L: Expression<BoolOperation = $l_kind> + BuildSql,
+            R: Expression<BoolOperation = $r_kind> + BuildSql,
+            L::SqlType: Comparable<SqlTypeBool>,
+            R::SqlType: Comparable<SqlTypeBool>,
+        {
+            fn build_sql_with(
+                &self,
+                ctx: &mut BuildContext,
+                buf: &mut Vec<u8>,
+                params: &mut Vec<Value>,
+            ) -> Result<(), BuildSqlError> {
+                (|| -> Result<(), anyhow::Error> {
+                    ctx.record_visit(|| VisitEvent::Operator {
+                        operator: $op.trim(),
+                    });
+                    write!(buf, "(")?;
+                    self.lhs.build_sql_with(ctx, buf, params)?;
+                    write!(buf, ")")?;
+                    write!(buf, $op)?;
+                    self.rhs.build_sql_with(ctx, buf, params)?;
+                    Ok(())
+                })()
+                .map_err(From::from)
             }
 
-            impl<Lhs, QS, W, C, G, H, O, L, LM> AndOperatorMethod for $ty<Lhs, QS, W, C, G, H, O, L, LM>
-            where
-                C: Columns,
-                Lhs: Expression,
-                Lhs::SqlType: Comparable<C::SqlType>,
-                Self: Expression<SqlType = SqlTypeBool>,
-            {}
+            fn size_hint(&self) -> SizeHint {
+                self.lhs.size_hint() + "()".len() + $op.len() + self.rhs.size_hint()
+            }
+        }
+    };
+    ( $ty:ident, $op:expr, rhs_parentheses, $l_kind:ty, $r_kind:ty ) => {
+        impl<L, R> BuildSql for $ty<L, $l_kind, R, $r_kind>
+        where
+            L: Expression<BoolOperation = $l_kind> + BuildSql,
+            R: Expression<BoolOperation = $r_kind> + BuildSql,
+            L::SqlType: Comparable<SqlTypeBool>,
+            R::SqlType: Comparable<SqlTypeBool>,
+        {
+            fn build_sql_with(
+                &self,
+                ctx: &mut BuildContext,
+                buf: &mut Vec<u8>,
+                params: &mut Vec<Value>,
+            ) -> Result<(), BuildSqlError> {
+                (|| -> Result<(), anyhow::Error> {
+                    ctx.record_visit(|| VisitEvent::Operator {
+                        operator: $op.trim(),
+                    });
+                    self.lhs.build_sql_with(ctx, buf, params)?;
+                    write!(buf, $op)?;
+                    write!(buf, "(")?;
+                    self.rhs.build_sql_with(ctx, buf, params)?;
+                    write!(buf, ")")?;
+                    Ok(())
+                })()
+                .map_err(From::from)
+            }
 
-            impl<Lhs, QS, W, C, G, H, O, L, LM> OrOperatorMethod for $ty<Lhs, QS, W, C, G, H, O, L, LM>
-            where
-                C: Columns,
-                Lhs: Expression,
-                Lhs::SqlType: Comparable<C::SqlType>,
-                Self: Expression<SqlType = SqlTypeBool>,
-            {}
+            fn size_hint(&self) -> SizeHint {
+                self.lhs.size_hint() + $op.len() + "()".len() + self.rhs.size_hint()
+            }
+        }
+    };
 
-            impl<Lhs, QS, W, C, G, H, O, L, LM> NotOperatorMethod for $ty<Lhs, QS, W, C, G, H, O, L, LM>
-            where
-                C: Columns,
-                Lhs: Expression,
-                Lhs::SqlType: Comparable<C::SqlType>,
-                Self: Expression<SqlType = SqlTypeBool>,
-            {}
+    ( $ty:ident, $op:expr, both_parentheses, $l_kind:ty, $r_kind:ty ) => {
+        impl<L, R> BuildSql for $ty<L, $l_kind, R, $r_kind>
+        where
+            L: Expression<BoolOperation = $l_kind> + BuildSql,
+            R: Expression<BoolOperation = $r_kind> + BuildSql,
+            L::SqlType: Comparable<SqlTypeBool>,
+            R::SqlType: Comparable<SqlTypeBool>,
+        {
+            fn build_sql_with(
+                &self,
+                ctx: &mut BuildContext,
+                buf: &mut Vec<u8>,
+                params: &mut Vec<Value>,
+            ) -> Result<(), BuildSqlError> {
+                (|| -> Result<(), anyhow::Error> {
+                    ctx.record_visit(|| VisitEvent::Operator {
+                        operator: $op.trim(),
+                    });
+                    write!(buf, "(")?;
+                    self.lhs.build_sql_with(ctx, buf, params)?;
+                    write!(buf, ")")?;
+                    write!(buf, $op)?;
+                    write!(buf, "(")?;
+                    self.rhs.build_sql_with(ctx, buf, params)?;
+                    write!(buf, ")")?;
+                    Ok(())
+                })()
+                .map_err(From::from)
+            }
 
-            impl<Lhs, QS, W, C, G, H, O, L, LM> BuildSql for $ty<Lhs, QS, W, C, G, H, O, L, LM>
-            where
-                QS: BuildSql,
-                C: BuildSql + Columns,
-                W: BuildSql,
-                G: BuildSql,
-                H: BuildSql,
-                O: BuildSql,
-                L: BuildSql,
-                LM: BuildSql,
-                Lhs: Expression + BuildSql,
-                Lhs::SqlType: Comparable<C::SqlType>,
-            {
-                fn build_sql(
-                    &self,
-                    buf: &mut Vec<u8>,
-                    params: &mut Vec<Value>,
-                ) -> Result<(), BuildSqlError> {
-                    (|| -> Result<(), anyhow::Error> {
-                        self.lhs.build_sql(buf, params)?;
-                        write!(buf, $op)?;
-                        self.rhs.build_sql(buf, params)?;
-                        Ok(())
-                    })()
-                    .map_err(From::from)
-                }
+            fn size_hint(&self) -> SizeHint {
+                self.lhs.size_hint() + $op.len() + "()()".len() + self.rhs.size_hint()
             }
-        )*
+        }
     };
 }
 
-impl_subquery_bool_binary_operators!(
-    (EqAny, " = ANY "),
-    (NotEqAny, " != ANY "),
-    (GtAny, " > ANY "),
-    (GeAny, " >= ANY "),
-    (LtAny, " < ANY "),
-    (LeAny, " <= ANY "),
-    (EqAll, " = ALL "),
-    (NotEqAll, " != ALL "),
-    (GtAll, " > ALL "),
-    (GeAll, " >= ALL "),
-    (LtAll, " < ALL "),
-    (LeAll, " <= ALL "),
-);
+impl_build_sql_and_or!(And, " AND ", both_no_parentheses, BoolMono, BoolMono);
+impl_build_sql_and_or!(And, " AND ", both_no_parentheses, BoolMono, BoolAnd);
+impl_build_sql_and_or!(And, " AND ", rhs_parentheses, BoolMono, BoolOr);
+impl_build_sql_and_or!(And, " AND ", both_no_parentheses, BoolAnd, BoolMono);
+impl_build_sql_and_or!(And, " AND ", both_no_parentheses, BoolAnd, BoolAnd);
+impl_build_sql_and_or!(And, " AND ", rhs_parentheses, BoolAnd, BoolOr);
+impl_build_sql_and_or!(And, " AND ", lhs_parentheses, BoolOr, BoolMono);
+impl_build_sql_and_or!(And, " AND ", lhs_parentheses, BoolOr, BoolAnd);
+impl_build_sql_and_or!(And, " AND ", both_parentheses, BoolOr, BoolOr);
+
+impl_build_sql_and_or!(Or, " OR ", both_no_parentheses, BoolMono, BoolMono);
+impl_build_sql_and_or!(Or, " OR ", rhs_parentheses, BoolMono, BoolAnd);
+impl_build_sql_and_or!(Or, " OR ", both_no_parentheses, BoolMono, BoolOr);
+impl_build_sql_and_or!(Or, " OR ", lhs_parentheses, BoolAnd, BoolMono);
+impl_build_sql_and_or!(Or, " OR ", both_parentheses, BoolAnd, BoolAnd);
+impl_build_sql_and_or!(Or, " OR ", lhs_parentheses, BoolAnd, BoolOr);
+impl_build_sql_and_or!(Or, " OR ", both_no_parentheses, BoolOr, BoolMono);
+impl_build_sql_and_or!(Or, " OR ", rhs_parentheses, BoolOr, BoolAnd);
+impl_build_sql_and_or!(Or, " OR ", both_no_parentheses, BoolOr, BoolOr);
 
 #[derive(Debug, Clone)]
-pub struct Between<T, L, U>
+pub struct Not<T, S> {
+    expr: T,
+    expr_term: PhantomData<S>,
+}
+
+impl<T> Not<T, T::Term>
 where
     T: Expression,
-    L: Expression,
-    U: Expression,
-    L::SqlType: Comparable<T::SqlType>,
-    U::SqlType: Comparable<T::SqlType>,
+    T::SqlType: Comparable<SqlTypeBool>,
 {
-    target: T,
-    lower_bound: L,
-    upper_bound: U,
+    pub fn new(expr: T) -> Not<T, T::Term> {
+        Not {
+            expr,
+            expr_term: PhantomData,
+        }
+    }
 }
 
-impl<T, L, U> Expression for Between<T, L, U>
+impl<T> Expression for Not<T, T::Term>
 where
     T: Expression,
-    L: Expression,
-    U: Expression,
-    L::SqlType: Comparable<T::SqlType>,
-    U::SqlType: Comparable<T::SqlType>,
+    T::SqlType: Comparable<SqlTypeBool>,
 {
     type SqlType = SqlTypeBool;
-    type Term = Polynomial;
+    type Term = Monomial;
     type BoolOperation = BoolMono;
-    type Aggregation = NonAggregate;
+    type Aggregation = T::Aggregation;
 }
 
-impl<T, L, U> AndOperatorMethod for Between<T, L, U>
+impl<T> BuildSql for Not<T, Monomial>
 where
-    T: Expression,
-    L: Expression,
-    U: Expression,
-    L::SqlType: Comparable<T::SqlType>,
-    U::SqlType: Comparable<T::SqlType>,
-    Self: Expression<SqlType = SqlTypeBool>,
+    T: Expression<Term = Monomial> + BuildSql,
+    T::SqlType: Comparable<SqlTypeBool>,
+{
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        (|| -> Result<(), anyhow::Error> {
+            ctx.record_visit(|| VisitEvent::Operator { operator: "NOT" });
+            write!(buf, "NOT ")?;
+            self.expr.build_sql_with(ctx, buf, params)?;
+            Ok(())
+        })()
+        .map_err(From::from)
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.expr.size_hint() + "NOT ".len()
+    }
+}
+
+impl<T> BuildSql for Not<T, Polynomial>
+where
+    T: Expression<Term = Polynomial> + BuildSql,
+    T::SqlType: Comparable<SqlTypeBool>,
+{
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        (|| -> Result<(), anyhow::Error> {
+            ctx.record_visit(|| VisitEvent::Operator { operator: "NOT" });
+            write!(buf, "NOT (")?;
+            self.expr.build_sql_with(ctx, buf, params)?;
+            write!(buf, ")")?;
+            Ok(())
+        })()
+        .map_err(From::from)
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.expr.size_hint() + "NOT ()".len()
+    }
+}
+
+pub trait AndOperatorMethod: Expression + Sized
+where
+    Self::SqlType: Comparable<SqlTypeBool>,
 {
+    fn and<R>(self, rhs: R) -> And<Self, Self::BoolOperation, R, R::BoolOperation>
+    where
+        R: Expression,
+        R::SqlType: Comparable<Self::SqlType>,
+    {
+        And::new(self, rhs)
+    }
 }
 
-impl<T, L, U> OrOperatorMethod for Between<T, L, U>
+pub trait OrOperatorMethod: Expression + Sized
 where
-    T: Expression,
-    L: Expression,
-    U: Expression,
-    L::SqlType: Comparable<T::SqlType>,
-    U::SqlType: Comparable<T::SqlType>,
-    Self: Expression<SqlType = SqlTypeBool>,
+    Self::SqlType: Comparable<SqlTypeBool>,
 {
+    fn or<R>(self, rhs: R) -> Or<Self, Self::BoolOperation, R, R::BoolOperation>
+    where
+        R: Expression,
+        R::SqlType: Comparable<Self::SqlType>,
+    {
+        Or::new(self, rhs)
+    }
 }
 
-impl<T, L, U> NotOperatorMethod for Between<T, L, U>
+pub trait NotOperatorMethod: Expression + Sized
 where
-    T: Expression,
-    L: Expression,
-    U: Expression,
-    L::SqlType: Comparable<T::SqlType>,
-    U::SqlType: Comparable<T::SqlType>,
-    Self: Expression<SqlType = SqlTypeBool>,
+    Self::SqlType: Comparable<SqlTypeBool>,
 {
+    fn not(self) -> Not<Self, Self::Term> {
+        Not::new(self)
+    }
 }
 
-impl<T, L, U> BuildSql for Between<T, L, U>
+pub fn not<T: NotOperatorMethod + Expression>(expr: T) -> Not<T, T::Term>
 where
-    T: Expression + BuildSql,
-    L: Expression + BuildSql,
-    U: Expression + BuildSql,
-    L::SqlType: Comparable<T::SqlType>,
-    U::SqlType: Comparable<T::SqlType>,
+    T: NotOperatorMethod + Expression,
+    T::SqlType: Comparable<SqlTypeBool>,
 {
-    fn build_sql(&self, buf: &mut Vec<u8>, params: &mut Vec<Value>) -> Result<(), BuildSqlError> {
-        (|| -> Result<(), anyhow::Error> {
-            self.target.build_sql(buf, params)?;
-            write!(buf, " BETWEEN ")?;
-            self.lower_bound.build_sql(buf, params)?;
-            write!(buf, " AND ")?;
-            self.upper_bound.build_sql(buf, params)?;
-            Ok(())
-        })()
-        .map_err(From::from)
-    }
+    expr.not()
 }
 
-macro_rules! impl_in_operators {
-( $( ( $ty:ident, $op:expr ) ),* $(,)* ) => {
-    $(
+macro_rules! define_sql_function {
+    ( $func_type:ident, $func_name:ident ( $( $arg_name:ident : $arg_type:ty ),* ) -> $ret_type:ty, $aggregation:ty ) => {
+        define_sql_function!($func_type, $func_name ( $( $arg_name : $arg_type ),* ) -> $ret_type, $aggregation, $aggregation);
+    };
+    ( $func_type:ident, $func_name:ident ( $( $arg_name:ident : $arg_type:ty ),* ) -> $ret_type:ty, $aggregation:ty, $returns:ty ) => {
+        #[allow(non_camel_case_types)]
         #[derive(Debug, Clone)]
-        pub struct $ty<L, R, ARR>
-        where
-            L: Expression,
-            R: Expression,
-            R::SqlType: Comparable<L::SqlType>,
-            ARR: AsRef<[R]>,
-        {
-            lhs: L,
-            rhs: ARR,
-            rhs_value: PhantomData<R>,
+        pub struct $func_type< $( $arg_name, )* > {
+            $( $arg_name: $arg_name, )*
         }
 
-        impl<L, R, ARR> Expression for $ty<L, R, ARR>
-        where
-            L: Expression,
-            R: Expression,
-            R::SqlType: Comparable<L::SqlType>,
-            ARR: AsRef<[R]>,
-            R::Aggregation: Aggregation<L::Aggregation>,
-        {
-            type SqlType = SqlTypeBool;
-            type Term = Polynomial;
-            type BoolOperation = BoolMono;
-            type Aggregation = <R::Aggregation as Aggregation<L::Aggregation>>::Output;
+        #[allow(non_camel_case_types)]
+        impl<$( $arg_name, )*> $func_type<$( $arg_name, )*> {
+            pub fn new( $( $arg_name: $arg_name, )* ) -> $func_type<$( $arg_name, )*> {
+                $func_type { $( $arg_name, )* }
+            }
         }
 
-        impl<L, R, ARR> AndOperatorMethod for $ty<L, R, ARR>
-        where
-            L: Expression,
-            R: Expression,
-            R::SqlType: Comparable<L::SqlType>,
-            ARR: AsRef<[R]>,
-            Self: Expression<SqlType = SqlTypeBool>,
-        {}
-
-        impl<L, R, ARR> OrOperatorMethod for $ty<L, R, ARR>
+        #[allow(non_camel_case_types)]
+        pub fn $func_name<$( $arg_name, )*>( $( $arg_name: $arg_name, )* ) -> $func_type<$( $arg_name, )*>
         where
-            L: Expression,
-            R: Expression,
-            R::SqlType: Comparable<L::SqlType>,
-            ARR: AsRef<[R]>,
-            Self: Expression<SqlType = SqlTypeBool>,
-        {}
+            $(
+                $arg_name: Expression,
+                <$arg_name>::SqlType: Comparable<$arg_type>,
+            )*
+        {
+            $func_type::new($( $arg_name, )*)
+        }
 
-        impl<L, R, ARR> NotOperatorMethod for $ty<L, R, ARR>
+        #[allow(non_camel_case_types)]
+        impl<$( $arg_name, )*> Expression for $func_type<$( $arg_name, )*>
         where
-            L: Expression,
-            R: Expression,
-            R::SqlType: Comparable<L::SqlType>,
-            ARR: AsRef<[R]>,
-            Self: Expression<SqlType = SqlTypeBool>,
-        {}
+            $(
+                $arg_name: Expression,
+                <$arg_name>::SqlType: Comparable<$arg_type>,
+            )*
+            $returns: FunctionReturn<( $( <$arg_name>::SqlType, )* ), $ret_type>,
+        {
+            type SqlType = <$returns as FunctionReturn<( $( <$arg_name>::SqlType, )* ), $ret_type>>::Output;
+            type Term = Monomial;
+            type BoolOperation = NonBool;
+            type Aggregation = $aggregation;
+        }
 
-        impl<L, R, ARR> BuildSql for $ty<L, R, ARR>
+        #[allow(non_camel_case_types)]
+        impl<$( $arg_name, )*> BuildSql for $func_type<$( $arg_name, )*>
         where
-            L: Expression + BuildSql,
-            R: Expression + BuildSql + Clone + Into<Value>,
-            R::SqlType: Comparable<L::SqlType>,
-            ARR: AsRef<[R]>,
+            $( $arg_name: BuildSql, )*
         {
-            fn build_sql(
+            fn build_sql_with(
                 &self,
+                ctx: &mut BuildContext,
                 buf: &mut Vec<u8>,
                 params: &mut Vec<Value>,
             ) -> Result<(), BuildSqlError> {
-                (|| -> Result<(), anyhow::Error> {
-                    self.lhs.build_sql(buf, params)?;
-                    write!(buf, $op)?;
-                    write!(buf, "(")?;
-                    let mut is_first = true;
-                    for v in self.rhs.as_ref() {
-                        if is_first {
-                            write!(buf, "?")?;
-                            is_first = false;
-                        } else {
-                            write!(buf, ", ?")?;
-                        }
-                        params.push((*v).clone().into());
-                    }
+                ctx.record_visit(|| VisitEvent::Function {
+                    name: stringify!($func_name),
+                });
+                ctx.write_function::<$aggregation, _>(|ctx| {
+                    write!(buf, concat!(stringify!($func_name), "("))?;
+                    build_sql_comma_separated_values!(ctx, buf, params, self, $( $arg_name, )*);
                     write!(buf, ")")?;
                     Ok(())
-                })()
-                .map_err(From::from)
+                })
+            }
+
+            fn size_hint(&self) -> SizeHint {
+                size_hint_comma_separated_values!(self, $( $arg_name, )*)
+                    + concat!(stringify!($func_name), "()").len()
             }
         }
-    )*
-};
+    };
 }
 
-impl_in_operators!((Any, " IN "), (NotAny, " NOT IN "));
-
-macro_rules! impl_null_check_operators {
-    ( $( ( $ty:ident, $op:expr ) ),* $(,)* ) => {
+macro_rules! build_sql_comma_separated_values {
+    ( $ctx:ident, $buf:ident, $params:ident, $x:ident, $first_field:ident $(, $field:ident )* $(,)* ) => {
+        $x.$first_field.build_sql_with($ctx, $buf, $params)?;
         $(
-            #[derive(Debug, Clone)]
-            pub struct $ty<T>
-            where
-                T: Expression,
-            {
-                target: T
-            }
-
-            impl<T> Expression for $ty<T>
-            where
-                T: Expression,
-            {
-                type SqlType = SqlTypeBool;
-                type Term = Monomial;
-                type BoolOperation = BoolMono;
-                type Aggregation = T::Aggregation;
-            }
-
-            impl<T> AndOperatorMethod for $ty<T>
-            where
-                T: Expression,
-                Self: Expression<SqlType = SqlTypeBool>,
-            {}
-
-            impl<T> OrOperatorMethod for $ty<T>
-            where
-                T: Expression,
-                Self: Expression<SqlType = SqlTypeBool>,
-            {}
-
-            impl<T> NotOperatorMethod for $ty<T>
-            where
-                T: Expression,
-                Self: Expression<SqlType = SqlTypeBool>,
-            {}
-
-            impl<T> BuildSql for $ty<T>
-            where
-                T: Expression + BuildSql,
-            {
-                fn build_sql(
-                    &self,
-                    buf: &mut Vec<u8>,
-                    params: &mut Vec<Value>,
-                ) -> Result<(), BuildSqlError> {
-                    (|| -> Result<(), anyhow::Error> {
-                        self.target.build_sql(buf, params)?;
-                        write!(buf, $op)?;
-                        Ok(())
-                    })()
-                    .map_err(From::from)
-                }
-            }
+            write!($buf, ", ")?;
+            $x.$field.build_sql_with($ctx, $buf, $params)?;
         )*
     };
+    ( $x:ident, ) => {};
 }
 
-impl_null_check_operators!((IsNull, " IS NULL"), (IsNotNull, " IS NOT NULL"));
-
-pub trait CompareBinaryOperatorMethod<R>: Expression + Sized
-where
-    R: Expression,
-    Self::SqlType: Comparable<R::SqlType>,
-{
-    /// SQL `=`.
-    fn eq(self, rhs: R) -> Eq<Self, R> {
-        Eq { lhs: self, rhs }
-    }
-
-    /// SQL `!=`.
-    fn not_eq(self, rhs: R) -> NotEq<Self, R> {
-        NotEq { lhs: self, rhs }
-    }
+macro_rules! size_hint_comma_separated_values {
+    ( $x:ident, $first_field:ident $(, $field:ident )* $(,)* ) => {
+        $x.$first_field.size_hint() $( + $x.$field.size_hint() + ", ".len() )*
+    };
+    ( $x:ident, ) => {
+        SizeHint::default()
+    };
+}
 
-    /// SQL `>`.
-    fn gt(self, rhs: R) -> Gt<Self, R> {
-        Gt { lhs: self, rhs }
-    }
+// SQLは動的型付けなので関数も動的な型に対応できる必要がある。
+// 例えばsumは整数型にも実数型にも使えるので、複数の型を取り得る。
+// なので関数はtraitとして実装した方が良いのではないか？
+// define_sql_function!(SumInt, sum(t: SqlTypeInt) -> SqlTypeInt, Aggregate);
+define_sql_function!(
+    Count,
+    count(t: SqlTypeAny) -> SqlTypeInt,
+    Aggregate,
+    NotNull
+);
 
-    /// SQL `>=`.
-    fn ge(self, rhs: R) -> Ge<Self, R> {
-        Ge { lhs: self, rhs }
-    }
+define_sql_function!(Date, date(t: SqlTypeString) -> SqlTypeString, NonAggregate);
+define_sql_function!(Left, left(t: SqlTypeString, n: SqlTypeInt) -> SqlTypeString, NonAggregate);
 
-    /// SQL `<`.
-    fn lt(self, rhs: R) -> Lt<Self, R> {
-        Lt { lhs: self, rhs }
-    }
+/// `grouping(t)`: 1 if `t` is rolled up into a subtotal row. `t` must be in `GROUP BY`.
+#[derive(Debug, Clone)]
+pub struct Grouping<T> {
+    t: T,
+}
 
-    /// SQL `<=`.
-    fn le(self, rhs: R) -> Le<Self, R> {
-        Le { lhs: self, rhs }
+impl<T> Grouping<T> {
+    pub fn new(t: T) -> Grouping<T> {
+        Grouping { t }
     }
+}
 
-    /// SQL `LIKE`.
-    fn like(self, rhs: R) -> Like<Self, R> {
-        Like { lhs: self, rhs }
-    }
+pub fn grouping<T>(t: T) -> Grouping<T>
+where
+    T: Expression,
+    T::SqlType: Comparable<SqlTypeAny>,
+{
+    Grouping::new(t)
+}
 
-    /// SQL `NOT LIKE`.
-    fn not_like(self, rhs: R) -> NotLike<Self, R> {
-        NotLike { lhs: self, rhs }
-    }
+impl<T> Expression for Grouping<T>
+where
+    T: Expression,
+    T::SqlType: Comparable<SqlTypeAny>,
+{
+    type SqlType = SqlTypeInt;
+    type Term = Monomial;
+    type BoolOperation = NonBool;
+    type Aggregation = Aggregate;
 }
 
-// これをSelectBuilder毎に定義する必要がある
-// DBによってサポートしてたりしてなかったりなので、それが正しいのかもしれない
-pub trait SubQueryCompareBinaryOperatorMethod<QS, W, C, G, H, O, L, LM>:
-    Expression + Sized
+impl<T> BuildSql for Grouping<T>
 where
-    C: Columns,
-    Self::SqlType: Comparable<C::SqlType>,
+    T: BuildSql,
 {
-    /// SQL `= ANY (...)`.
-    fn eq_any(
-        self,
-        rhs: SelectBuilder<QS, W, C, G, H, O, L, LM>,
-    ) -> EqAny<Self, QS, W, C, G, H, O, L, LM> {
-        EqAny { lhs: self, rhs }
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        ctx.record_visit(|| VisitEvent::Function { name: "grouping" });
+        write!(buf, "grouping(")?;
+        ctx.write_grouping_args(|ctx| self.t.build_sql_with(ctx, buf, params))?;
+        write!(buf, ")")?;
+        Ok(())
     }
 
-    /// SQL `!= ANY (...)`.
-    fn not_eq_any(
-        self,
-        rhs: SelectBuilder<QS, W, C, G, H, O, L, LM>,
-    ) -> NotEqAny<Self, QS, W, C, G, H, O, L, LM> {
-        NotEqAny { lhs: self, rhs }
+    fn size_hint(&self) -> SizeHint {
+        self.t.size_hint() + "grouping()".len()
     }
+}
 
-    /// SQL `> ANY (...)`.
-    fn gt_any(
-        self,
-        rhs: SelectBuilder<QS, W, C, G, H, O, L, LM>,
-    ) -> GtAny<Self, QS, W, C, G, H, O, L, LM> {
-        GtAny { lhs: self, rhs }
-    }
+pub trait SqlKindNumber {}
 
-    /// SQL `>= ANY (...)`.
-    fn ge_any(
-        self,
-        rhs: SelectBuilder<QS, W, C, G, H, O, L, LM>,
-    ) -> GeAny<Self, QS, W, C, G, H, O, L, LM> {
-        GeAny { lhs: self, rhs }
-    }
+impl SqlKindNumber for SqlTypeInt {}
+impl SqlKindNumber for SqlTypeUint {}
+impl SqlKindNumber for SqlTypeAny {}
+impl<T: SqlKindNumber> SqlKindNumber for Nullable<T> {}
 
-    /// SQL `< ANY (...)`.
-    fn lt_any(
-        self,
-        rhs: SelectBuilder<QS, W, C, G, H, O, L, LM>,
-    ) -> LtAny<Self, QS, W, C, G, H, O, L, LM> {
-        LtAny { lhs: self, rhs }
-    }
+/// Aggregates of a single expression which are NULL over no rows, e.g. `sum(x)`.
+macro_rules! define_aggregate_function {
+    ( $func_type:ident, $func_name:ident, $kind:ident -> $ret_type:ty ) => {
+        #[derive(Debug, Clone)]
+        pub struct $func_type<T>
+        where
+            T: Expression,
+            T::SqlType: $kind,
+        {
+            t: T,
+        }
 
-    /// SQL `<= ANY (...)`.
-    fn le_any(
-        self,
-        rhs: SelectBuilder<QS, W, C, G, H, O, L, LM>,
-    ) -> LeAny<Self, QS, W, C, G, H, O, L, LM> {
-        LeAny { lhs: self, rhs }
-    }
+        impl<T> $func_type<T>
+        where
+            T: Expression,
+            T::SqlType: $kind,
+        {
+            pub fn new(t: T) -> $func_type<T> {
+                $func_type { t }
+            }
+        }
 
-    /// SQL `= ALL (...)`.
-    fn eq_all(
-        self,
-        rhs: SelectBuilder<QS, W, C, G, H, O, L, LM>,
-    ) -> EqAll<Self, QS, W, C, G, H, O, L, LM> {
-        EqAll { lhs: self, rhs }
-    }
+        pub fn $func_name<T>(t: T) -> $func_type<T>
+        where
+            T: Expression,
+            T::SqlType: $kind,
+        {
+            $func_type::new(t)
+        }
 
-    /// SQL `!= ALL (...)`.
-    fn not_eq_all(
-        self,
-        rhs: SelectBuilder<QS, W, C, G, H, O, L, LM>,
-    ) -> NotEqAll<Self, QS, W, C, G, H, O, L, LM> {
-        NotEqAll { lhs: self, rhs }
-    }
+        impl<T> Expression for $func_type<T>
+        where
+            T: Expression,
+            T::SqlType: $kind + IntoNullable,
+        {
+            type SqlType = $ret_type;
+            type Term = Monomial;
+            type BoolOperation = NonBool;
+            type Aggregation = Aggregate;
+        }
 
-    /// SQL `> ALL (...)`.
-    fn gt_all(
-        self,
-        rhs: SelectBuilder<QS, W, C, G, H, O, L, LM>,
-    ) -> GtAll<Self, QS, W, C, G, H, O, L, LM> {
-        GtAll { lhs: self, rhs }
-    }
+        impl<T> BuildSql for $func_type<T>
+        where
+            T: BuildSql + Expression,
+            T::SqlType: $kind,
+        {
+            fn build_sql_with(
+                &self,
+                ctx: &mut BuildContext,
+                buf: &mut Vec<u8>,
+                params: &mut Vec<Value>,
+            ) -> Result<(), BuildSqlError> {
+                ctx.record_visit(|| VisitEvent::Function {
+                    name: stringify!($func_name),
+                });
+                ctx.write_function::<Aggregate, _>(|ctx| {
+                    write!(buf, concat!(stringify!($func_name), "("))?;
+                    self.t.build_sql_with(ctx, buf, params)?;
+                    write!(buf, ")")?;
+                    Ok(())
+                })
+            }
 
-    /// SQL `>= ALL (...)`.
-    fn ge_all(
-        self,
-        rhs: SelectBuilder<QS, W, C, G, H, O, L, LM>,
-    ) -> GeAll<Self, QS, W, C, G, H, O, L, LM> {
-        GeAll { lhs: self, rhs }
-    }
+            fn size_hint(&self) -> SizeHint {
+                self.t.size_hint() + concat!(stringify!($func_name), "()").len()
+            }
+        }
 
-    /// SQL `< ALL (...)`.
-    fn lt_all(
-        self,
-        rhs: SelectBuilder<QS, W, C, G, H, O, L, LM>,
-    ) -> LtAll<Self, QS, W, C, G, H, O, L, LM> {
-        LtAll { lhs: self, rhs }
-    }
+        impl<T> FilterAggregate for $func_type<T>
+        where
+            T: BuildSql + Expression,
+            T::SqlType: $kind + IntoNullable,
+        {
+            fn write_case_when(
+                &self,
+                ctx: &mut BuildContext,
+                buf: &mut Vec<u8>,
+                params: &mut Vec<Value>,
+                cond: &dyn BuildSql,
+            ) -> Result<(), BuildSqlError> {
+                ctx.record_visit(|| VisitEvent::Function {
+                    name: stringify!($func_name),
+                });
+                write!(buf, concat!(stringify!($func_name), "(CASE WHEN "))?;
+                cond.build_sql_with(ctx, buf, params)?;
+                write!(buf, " THEN ")?;
+                self.t.build_sql_with(ctx, buf, params)?;
+                write!(buf, " END)")?;
+                Ok(())
+            }
+        }
+    };
+}
 
-    /// SQL `<= ALL (...)`.
-    fn le_all(
-        self,
-        rhs: SelectBuilder<QS, W, C, G, H, O, L, LM>,
-    ) -> LeAll<Self, QS, W, C, G, H, O, L, LM> {
-        LeAll { lhs: self, rhs }
+define_aggregate_function!(Sum, sum, SqlKindNumber -> <T::SqlType as IntoNullable>::Nullable);
+define_aggregate_function!(Max, max, IntoNullable -> <T::SqlType as IntoNullable>::Nullable);
+define_aggregate_function!(Min, min, IntoNullable -> <T::SqlType as IntoNullable>::Nullable);
+// The average of integers is a decimal, which has no SQL type of its own yet.
+define_aggregate_function!(Avg, avg, SqlKindNumber -> SqlTypeAny);
+
+/// Aggregates which take `FILTER (WHERE cond)`.
+pub trait FilterAggregate: Expression<Aggregation = Aggregate> + BuildSql + Sized {
+    /// Write the aggregate of `CASE WHEN cond THEN arg END`, for MySQL which has no `FILTER`.
+    fn write_case_when(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+        cond: &dyn BuildSql,
+    ) -> Result<(), BuildSqlError>;
+
+    /// Aggregate only the rows matching `cond`, e.g. `count(t1.id) FILTER (WHERE t1.paid)`.
+    fn filter<W>(self, cond: W) -> Filtered<Self, W>
+    where
+        W: Expression<SqlType = SqlTypeBool>,
+    {
+        Filtered {
+            aggregate: self,
+            cond,
+        }
     }
 }
 
-pub trait BetweenOperatorMethod<L, U>: Expression + Sized
+impl<T> FilterAggregate for Count<T>
 where
-    L: Expression,
-    U: Expression,
-    L::SqlType: Comparable<Self::SqlType>,
-    U::SqlType: Comparable<Self::SqlType>,
+    T: BuildSql + Expression,
+    T::SqlType: Comparable<SqlTypeAny>,
 {
-    /// SQL `BETWEEN`.
-    fn between(self, lower_bound: L, upper_bound: U) -> Between<Self, L, U> {
-        Between {
-            target: self,
-            lower_bound,
-            upper_bound,
-        }
+    fn write_case_when(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+        cond: &dyn BuildSql,
+    ) -> Result<(), BuildSqlError> {
+        ctx.record_visit(|| VisitEvent::Function { name: "count" });
+        write!(buf, "count(CASE WHEN ")?;
+        cond.build_sql_with(ctx, buf, params)?;
+        write!(buf, " THEN ")?;
+        self.t.build_sql_with(ctx, buf, params)?;
+        write!(buf, " END)")?;
+        Ok(())
     }
 }
 
-pub trait InOperatorMethod<T>: Expression + Sized
+/// An aggregate with `FILTER (WHERE cond)`. See [`FilterAggregate::filter`].
+#[derive(Debug, Clone)]
+pub struct Filtered<A, W> {
+    aggregate: A,
+    cond: W,
+}
+
+impl<A, W> Expression for Filtered<A, W>
 where
-    T: Expression,
-    T::SqlType: Comparable<Self::SqlType>,
+    A: FilterAggregate,
+    W: Expression<SqlType = SqlTypeBool>,
 {
-    /// SQL `IN`.
-    fn any<V: AsRef<[T]>>(self, values: V) -> Any<Self, T, V> {
-        Any {
-            lhs: self,
-            rhs: values,
-            rhs_value: PhantomData,
-        }
+    type SqlType = A::SqlType;
+    type Term = Monomial;
+    type BoolOperation = NonBool;
+    type Aggregation = Aggregate;
+}
+
+impl<A, W> BuildSql for Filtered<A, W>
+where
+    A: FilterAggregate,
+    W: BuildSql,
+{
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        ctx.write_function::<Aggregate, _>(|ctx| {
+            if ctx.dialect() == DialectKind::MySql {
+                return self.aggregate.write_case_when(ctx, buf, params, &self.cond);
+            }
+            self.aggregate.build_sql_with(ctx, buf, params)?;
+            write!(buf, " FILTER (WHERE ")?;
+            self.cond.build_sql_with(ctx, buf, params)?;
+            write!(buf, ")")?;
+            Ok(())
+        })
     }
 
-    /// SQL `NOT IN`.
-    fn not_any<V: AsRef<[T]>>(self, values: V) -> NotAny<Self, T, V> {
-        NotAny {
-            lhs: self,
-            rhs: values,
-            rhs_value: PhantomData,
-        }
+    fn size_hint(&self) -> SizeHint {
+        self.aggregate.size_hint() + self.cond.size_hint() + " FILTER (WHERE )".len()
     }
 }
 
-pub trait NullCheckOperatorMethod: Expression + Sized {
-    /// SQL `IS NULL`.
-    #[allow(clippy::wrong_self_convention)]
-    fn is_null(self) -> IsNull<Self> {
-        IsNull { target: self }
+/// SQL types of JSON documents.
+pub trait JsonSqlType {}
+
+impl JsonSqlType for SqlTypeJson {}
+
+impl JsonSqlType for Nullable<SqlTypeJson> {}
+
+/// A JSON document bound as a parameter: `CAST(? AS JSON)`, `CAST(? AS jsonb)` or `json(?)`.
+#[derive(Debug, Clone)]
+pub struct JsonValue(String);
+
+pub fn json_value(json: impl Into<String>) -> JsonValue {
+    JsonValue(json.into())
+}
+
+impl Expression for JsonValue {
+    type SqlType = SqlTypeJson;
+    type Term = Monomial;
+    type BoolOperation = NonBool;
+    type Aggregation = NonAggregate;
+}
+
+impl BuildSql for JsonValue {
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        let (open, close) = match ctx.dialect() {
+            DialectKind::MySql => ("CAST(", " AS JSON)"),
+            DialectKind::Postgres => ("CAST(", " AS jsonb)"),
+            DialectKind::Sqlite => ("json(", ")"),
+        };
+        write!(buf, "{}", open)?;
+        ctx.write_param(buf, params, Value::String(self.0.clone()))?;
+        write!(buf, "{}", close)?;
+        Ok(())
     }
 
-    /// SQL `IS NOT NULL`.
-    #[allow(clippy::wrong_self_convention)]
-    fn is_not_null(self) -> IsNotNull<Self> {
-        IsNotNull { target: self }
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::new("CAST(? AS jsonb)".len(), 1)
     }
 }
 
-impl<L, R> CompareBinaryOperatorMethod<R> for L
-where
-    L: Expression,
-    R: Expression,
-    L::SqlType: Comparable<R::SqlType>,
-{
+/// `ST` is `SqlTypeJson` to extract the JSON value, or `SqlTypeString` to extract its unquoted text.
+pub trait JsonExtractKind {
+    const TEXT: bool;
 }
 
-impl<Lhs, QS, W, C, G, H, O, L, LM> SubQueryCompareBinaryOperatorMethod<QS, W, C, G, H, O, L, LM>
-    for Lhs
-where
-    C: Columns,
-    Lhs: Expression,
-    Lhs::SqlType: Comparable<C::SqlType>,
-{
+impl JsonExtractKind for SqlTypeJson {
+    const TEXT: bool = false;
+}
+
+impl JsonExtractKind for SqlTypeString {
+    const TEXT: bool = true;
+}
+
+/// The value at a path of object keys in a JSON document. See [`JsonOperatorMethod`].
+#[derive(Debug, Clone)]
+pub struct JsonExtract<E, ST> {
+    expr: E,
+    path: Vec<String>,
+    sql_type: PhantomData<ST>,
+}
+
+impl<E, ST> JsonExtract<E, ST> {
+    fn new(expr: E, path: Vec<String>) -> JsonExtract<E, ST> {
+        JsonExtract {
+            expr,
+            path,
+            sql_type: PhantomData,
+        }
+    }
+
+    fn quoted_keys(&self) -> impl Iterator<Item = String> + '_ {
+        self.path
+            .iter()
+            .map(|key| format!("\"{}\"", key.replace('\\', "\\\\").replace('"', "\\\"")))
+    }
+
+    /// `$."a"."b"` of MySQL and SQLite.
+    fn json_path(&self) -> String {
+        self.quoted_keys().fold(String::from("$"), |mut path, key| {
+            path.push('.');
+            path.push_str(&key);
+            path
+        })
+    }
+
+    /// `{"a","b"}` of Postgres.
+    fn text_array(&self) -> String {
+        format!("{{{}}}", self.quoted_keys().collect::<Vec<_>>().join(","))
+    }
 }
 
-impl<T, L, U> BetweenOperatorMethod<L, U> for T
+impl<E, ST> Expression for JsonExtract<E, ST>
 where
-    T: Expression,
-    L: Expression,
-    U: Expression,
-    L::SqlType: Comparable<T::SqlType>,
-    U::SqlType: Comparable<T::SqlType>,
+    E: Expression,
+    ST: JsonExtractKind + Default,
 {
+    type SqlType = Nullable<ST>;
+    type Term = Monomial;
+    type BoolOperation = NonBool;
+    type Aggregation = E::Aggregation;
 }
 
-impl<L, T> InOperatorMethod<T> for L
+impl<E, ST> BuildSql for JsonExtract<E, ST>
 where
-    L: Expression,
-    T: Expression,
-    T::SqlType: Comparable<L::SqlType>,
+    E: BuildSql,
+    ST: JsonExtractKind,
 {
-}
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        match ctx.dialect() {
+            DialectKind::Postgres => {
+                self.expr.build_sql_with(ctx, buf, params)?;
+                if self.path.len() == 1 {
+                    write!(buf, "{}", if ST::TEXT { " ->> " } else { " -> " })?;
+                    ctx.write_param(buf, params, Value::String(self.path[0].clone()))?;
+                } else {
+                    write!(buf, "{}", if ST::TEXT { " #>> CAST(" } else { " #> CAST(" })?;
+                    ctx.write_param(buf, params, Value::String(self.text_array()))?;
+                    write!(buf, " AS text[])")?;
+                }
+            }
+            DialectKind::MySql => {
+                if ST::TEXT {
+                    write!(buf, "JSON_UNQUOTE(")?;
+                }
+                write!(buf, "JSON_EXTRACT(")?;
+                self.expr.build_sql_with(ctx, buf, params)?;
+                write!(buf, ", ")?;
+                ctx.write_param(buf, params, Value::String(self.json_path()))?;
+                write!(buf, ")")?;
+                if ST::TEXT {
+                    write!(buf, ")")?;
+                }
+            }
+            // `->` and `->>` of SQLite 3.38+. json_extract() would return SQL values, not JSON.
+            DialectKind::Sqlite => {
+                self.expr.build_sql_with(ctx, buf, params)?;
+                write!(buf, "{}", if ST::TEXT { " ->> " } else { " -> " })?;
+                ctx.write_param(buf, params, Value::String(self.json_path()))?;
+            }
+        }
+        Ok(())
+    }
 
-impl<T> NullCheckOperatorMethod for T where T: Expression {}
+    fn size_hint(&self) -> SizeHint {
+        self.expr.size_hint() + SizeHint::new("JSON_UNQUOTE(JSON_EXTRACT(, ?))".len(), 1)
+    }
+}
 
+/// `doc @> other` on Postgres and `JSON_CONTAINS(doc, other)` on MySQL. SQLite has no containment.
 #[derive(Debug, Clone)]
-pub struct And<L, LK, R, RK> {
+pub struct JsonContains<L, R> {
     lhs: L,
     rhs: R,
-    lhs_kind: PhantomData<LK>,
-    rhs_kind: PhantomData<RK>,
-}
-
-impl<L, R> And<L, L::BoolOperation, R, R::BoolOperation>
-where
-    L: Expression,
-    R: Expression,
-{
-    pub fn new(lhs: L, rhs: R) -> Self {
-        And {
-            lhs,
-            rhs,
-            lhs_kind: PhantomData,
-            rhs_kind: PhantomData,
-        }
-    }
 }
 
-impl<L, R> Expression for And<L, L::BoolOperation, R, R::BoolOperation>
+impl<L, R> Expression for JsonContains<L, R>
 where
     L: Expression,
     R: Expression,
-    L::SqlType: Comparable<SqlTypeBool>,
-    R::SqlType: Comparable<L::SqlType>,
     L::Aggregation: Aggregation<R::Aggregation>,
 {
     type SqlType = SqlTypeBool;
-    type Term = Polynomial;
-    type BoolOperation = BoolAnd;
+    type Term = Monomial;
+    type BoolOperation = BoolMono;
     type Aggregation = <L::Aggregation as Aggregation<R::Aggregation>>::Output;
 }
 
-impl<L, R> AndOperatorMethod for And<L, L::BoolOperation, R, R::BoolOperation>
+impl<L, R> BuildSql for JsonContains<L, R>
 where
-    L: Expression,
-    R: Expression,
-    L::SqlType: Comparable<SqlTypeBool>,
-    R::SqlType: Comparable<L::SqlType>,
-    Self: Expression<SqlType = SqlTypeBool>,
+    L: BuildSql,
+    R: BuildSql,
 {
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        ctx.record_visit(|| VisitEvent::Operator { operator: "@>" });
+        match ctx.dialect() {
+            DialectKind::Postgres => {
+                self.lhs.build_sql_with(ctx, buf, params)?;
+                write!(buf, " @> ")?;
+                self.rhs.build_sql_with(ctx, buf, params)?;
+            }
+            dialect => {
+                if dialect != DialectKind::MySql {
+                    ctx.reject(anyhow::anyhow!(
+                        "JSON containment is not supported by {}",
+                        dialect.name()
+                    ))?;
+                }
+                write!(buf, "JSON_CONTAINS(")?;
+                self.lhs.build_sql_with(ctx, buf, params)?;
+                write!(buf, ", ")?;
+                self.rhs.build_sql_with(ctx, buf, params)?;
+                write!(buf, ")")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.lhs.size_hint() + self.rhs.size_hint() + "JSON_CONTAINS(, )".len()
+    }
 }
 
-impl<L, R> NotOperatorMethod for And<L, L::BoolOperation, R, R::BoolOperation>
+pub trait JsonOperatorMethod: Expression + Sized
 where
-    L: Expression,
-    R: Expression,
-    L::SqlType: Comparable<SqlTypeBool>,
-    R::SqlType: Comparable<L::SqlType>,
-    Self: Expression<SqlType = SqlTypeBool>,
+    Self::SqlType: JsonSqlType,
 {
-}
+    /// The JSON value of `key`: `doc -> 'key'`.
+    fn json_get(self, key: &str) -> JsonExtract<Self, SqlTypeJson> {
+        JsonExtract::new(self, vec![key.to_string()])
+    }
 
-#[derive(Debug, Clone)]
-pub struct Or<L, LK, R, RK> {
-    lhs: L,
-    rhs: R,
-    lhs_kind: PhantomData<LK>,
-    rhs_kind: PhantomData<RK>,
-}
+    /// The text of `key`: `doc ->> 'key'`.
+    fn json_get_text(self, key: &str) -> JsonExtract<Self, SqlTypeString> {
+        JsonExtract::new(self, vec![key.to_string()])
+    }
 
-impl<L, R> Or<L, L::BoolOperation, R, R::BoolOperation>
-where
-    L: Expression,
-    R: Expression,
-{
-    pub fn new(lhs: L, rhs: R) -> Self {
-        Or {
-            lhs,
-            rhs,
-            lhs_kind: PhantomData,
-            rhs_kind: PhantomData,
+    /// The JSON value at a path of keys: `doc #> '{a,b}'`.
+    fn json_path(self, path: &[&str]) -> JsonExtract<Self, SqlTypeJson> {
+        JsonExtract::new(self, path.iter().map(|key| key.to_string()).collect())
+    }
+
+    /// The text at a path of keys: `doc #>> '{a,b}'`.
+    fn json_path_text(self, path: &[&str]) -> JsonExtract<Self, SqlTypeString> {
+        JsonExtract::new(self, path.iter().map(|key| key.to_string()).collect())
+    }
+
+    /// Whether the document contains `other`, e.g. `json_value(r#"{"tags":["a"]}"#)`.
+    fn json_contains<R>(self, other: R) -> JsonContains<Self, R>
+    where
+        R: Expression,
+        R::SqlType: JsonSqlType,
+    {
+        JsonContains {
+            lhs: self,
+            rhs: other,
         }
     }
 }
 
-impl<L, R> Expression for Or<L, L::BoolOperation, R, R::BoolOperation>
+impl<T> JsonOperatorMethod for T
 where
-    L: Expression,
-    R: Expression,
-    L::SqlType: Comparable<SqlTypeBool>,
-    R::SqlType: Comparable<L::SqlType>,
-    L::Aggregation: Aggregation<R::Aggregation>,
+    T: Expression,
+    T::SqlType: JsonSqlType,
 {
-    type SqlType = SqlTypeBool;
-    type Term = Polynomial;
-    type BoolOperation = BoolOr;
-    type Aggregation = <L::Aggregation as Aggregation<R::Aggregation>>::Output;
 }
 
-impl<L, R> OrOperatorMethod for Or<L, L::BoolOperation, R, R::BoolOperation>
+/// `json_agg(x)` on Postgres, `JSON_ARRAYAGG(x)` on MySQL and `json_group_array(x)` on SQLite.
+#[derive(Debug, Clone)]
+pub struct JsonAgg<T>(T);
+
+pub fn json_agg<T>(t: T) -> JsonAgg<T>
 where
-    L: Expression,
-    R: Expression,
-    L::SqlType: Comparable<SqlTypeBool>,
-    R::SqlType: Comparable<L::SqlType>,
-    Self: Expression<SqlType = SqlTypeBool>,
+    T: Expression,
 {
+    JsonAgg(t)
 }
 
-impl<L, R> NotOperatorMethod for Or<L, L::BoolOperation, R, R::BoolOperation>
+// NULL over no rows, except on SQLite which gives `[]`.
+impl<T> Expression for JsonAgg<T>
 where
-    L: Expression,
-    R: Expression,
-    L::SqlType: Comparable<SqlTypeBool>,
-    R::SqlType: Comparable<L::SqlType>,
-    Self: Expression<SqlType = SqlTypeBool>,
+    T: Expression,
 {
+    type SqlType = Nullable<SqlTypeJson>;
+    type Term = Monomial;
+    type BoolOperation = NonBool;
+    type Aggregation = Aggregate;
 }
 
-macro_rules! impl_build_sql_and_or {
-    ( $ty:ident, $op:expr, both_no_parentheses, $l_kind:ty, $r_kind:ty ) => {
-        impl<L, R> BuildSql for $ty<L, $l_kind, R, $r_kind>
-        where
-            L: Expression<BoolOperation = $l_kind> + BuildSql,
-            R: Expression<BoolOperation = $r_kind> + BuildSql,
-            L::SqlType: Comparable<SqlTypeBool>,
-            R::SqlType: Comparable<SqlTypeBool>,
-        {
-            fn build_sql(
-                &self,
-                buf: &mut Vec<u8>,
-                params: &mut Vec<Value>,
-            ) -> Result<(), BuildSqlError> {
-                (|| -> Result<(), anyhow::Error> {
-                    self.lhs.build_sql(buf, params)?;
-                    write!(buf, $op)?;
-                    self.rhs.build_sql(buf, params)?;
-                    Ok(())
-                })()
-                .map_err(From::from)
-            }
-        }
-    };
-    ( $ty:ident, $op:expr, lhs_parentheses, $l_kind:ty, $r_kind:ty ) => {
-        impl<L, R> BuildSql for $ty<L, $l_kind, R, $r_kind>
-        where
-            L: Expression<BoolOperation = $l_kind> + BuildSql,
-            R: Expression<BoolOperation = $r_kind> + BuildSql,
-            L::SqlType: Comparable<SqlTypeBool>,
-            R::SqlType: Comparable<SqlTypeBool>,
-        {
-            fn build_sql(
-                &self,
-                buf: &mut Vec<u8>,
-                params: &mut Vec<Value>,
-            ) -> Result<(), BuildSqlError> {
-                (|| -> Result<(), anyhow::Error> {
-                    write!(buf, "(")?;
-                    self.lhs.build_sql(buf, params)?;
-                    write!(buf, ")")?;
-                    write!(buf, $op)?;
-                    self.rhs.build_sql(buf, params)?;
-                    Ok(())
-                })()
-                .map_err(From::from)
-            }
-        }
-    };
-    ( $ty:ident, $op:expr, rhs_parentheses, $l_kind:ty, $r_kind:ty ) => {
-        impl<L, R> BuildSql for $ty<L, $l_kind, R, $r_kind>
-        where
-            L: Expression<BoolOperation = $l_kind> + BuildSql,
-            R: Expression<BoolOperation = $r_kind> + BuildSql,
-            L::SqlType: Comparable<SqlTypeBool>,
-            R::SqlType: Comparable<SqlTypeBool>,
-        {
-            fn build_sql(
-                &self,
-                buf: &mut Vec<u8>,
-                params: &mut Vec<Value>,
-            ) -> Result<(), BuildSqlError> {
-                (|| -> Result<(), anyhow::Error> {
-                    self.lhs.build_sql(buf, params)?;
-                    write!(buf, $op)?;
-                    write!(buf, "(")?;
-                    self.rhs.build_sql(buf, params)?;
-                    write!(buf, ")")?;
-                    Ok(())
-                })()
-                .map_err(From::from)
-            }
-        }
-    };
-
-    ( $ty:ident, $op:expr, both_parentheses, $l_kind:ty, $r_kind:ty ) => {
-        impl<L, R> BuildSql for $ty<L, $l_kind, R, $r_kind>
-        where
-            L: Expression<BoolOperation = $l_kind> + BuildSql,
-            R: Expression<BoolOperation = $r_kind> + BuildSql,
-            L::SqlType: Comparable<SqlTypeBool>,
-            R::SqlType: Comparable<SqlTypeBool>,
-        {
-            fn build_sql(
-                &self,
-                buf: &mut Vec<u8>,
-                params: &mut Vec<Value>,
-            ) -> Result<(), BuildSqlError> {
-                (|| -> Result<(), anyhow::Error> {
-                    write!(buf, "(")?;
-                    self.lhs.build_sql(buf, params)?;
-                    write!(buf, ")")?;
-                    write!(buf, $op)?;
-                    write!(buf, "(")?;
-                    self.rhs.build_sql(buf, params)?;
-                    write!(buf, ")")?;
-                    Ok(())
-                })()
-                .map_err(From::from)
-            }
-        }
-    };
-}
-
-impl_build_sql_and_or!(And, " AND ", both_no_parentheses, BoolMono, BoolMono);
-impl_build_sql_and_or!(And, " AND ", both_no_parentheses, BoolMono, BoolAnd);
-impl_build_sql_and_or!(And, " AND ", rhs_parentheses, BoolMono, BoolOr);
-impl_build_sql_and_or!(And, " AND ", both_no_parentheses, BoolAnd, BoolMono);
-impl_build_sql_and_or!(And, " AND ", both_no_parentheses, BoolAnd, BoolAnd);
-impl_build_sql_and_or!(And, " AND ", rhs_parentheses, BoolAnd, BoolOr);
-impl_build_sql_and_or!(And, " AND ", lhs_parentheses, BoolOr, BoolMono);
-impl_build_sql_and_or!(And, " AND ", lhs_parentheses, BoolOr, BoolAnd);
-impl_build_sql_and_or!(And, " AND ", both_parentheses, BoolOr, BoolOr);
-
-impl_build_sql_and_or!(Or, " OR ", both_no_parentheses, BoolMono, BoolMono);
-impl_build_sql_and_or!(Or, " OR ", rhs_parentheses, BoolMono, BoolAnd);
-impl_build_sql_and_or!(Or, " OR ", both_no_parentheses, BoolMono, BoolOr);
-impl_build_sql_and_or!(Or, " OR ", lhs_parentheses, BoolAnd, BoolMono);
-impl_build_sql_and_or!(Or, " OR ", both_parentheses, BoolAnd, BoolAnd);
-impl_build_sql_and_or!(Or, " OR ", lhs_parentheses, BoolAnd, BoolOr);
-impl_build_sql_and_or!(Or, " OR ", both_no_parentheses, BoolOr, BoolMono);
-impl_build_sql_and_or!(Or, " OR ", rhs_parentheses, BoolOr, BoolAnd);
-impl_build_sql_and_or!(Or, " OR ", both_no_parentheses, BoolOr, BoolOr);
+impl<T> BuildSql for JsonAgg<T>
+where
+    T: BuildSql,
+{
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        let name = match ctx.dialect() {
+            DialectKind::MySql => "JSON_ARRAYAGG",
+            DialectKind::Postgres => "json_agg",
+            DialectKind::Sqlite => "json_group_array",
+        };
+        ctx.record_visit(|| VisitEvent::Function { name });
+        ctx.write_function::<Aggregate, _>(|ctx| {
+            write!(buf, "{}(", name)?;
+            self.0.build_sql_with(ctx, buf, params)?;
+            write!(buf, ")")?;
+            Ok(())
+        })
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.0.size_hint() + "json_group_array()".len()
+    }
+}
 
+/// `json_object_agg(k, v)` on Postgres, `JSON_OBJECTAGG(k, v)` on MySQL and `json_group_object(k, v)` on SQLite.
 #[derive(Debug, Clone)]
-pub struct Not<T, S> {
-    expr: T,
-    expr_term: PhantomData<S>,
+pub struct JsonObjectAgg<K, V> {
+    key: K,
+    value: V,
 }
 
-impl<T> Not<T, T::Term>
+pub fn json_object_agg<K, V>(key: K, value: V) -> JsonObjectAgg<K, V>
 where
-    T: Expression,
-    T::SqlType: Comparable<SqlTypeBool>,
+    K: Expression,
+    K::SqlType: Comparable<SqlTypeString>,
+    V: Expression,
 {
-    pub fn new(expr: T) -> Not<T, T::Term> {
-        Not {
-            expr,
-            expr_term: PhantomData,
-        }
-    }
+    JsonObjectAgg { key, value }
 }
 
-impl<T> Expression for Not<T, T::Term>
+impl<K, V> Expression for JsonObjectAgg<K, V>
 where
-    T: Expression,
-    T::SqlType: Comparable<SqlTypeBool>,
+    K: Expression,
+    K::SqlType: Comparable<SqlTypeString>,
+    V: Expression,
 {
-    type SqlType = SqlTypeBool;
+    type SqlType = Nullable<SqlTypeJson>;
     type Term = Monomial;
-    type BoolOperation = BoolMono;
-    type Aggregation = T::Aggregation;
+    type BoolOperation = NonBool;
+    type Aggregation = Aggregate;
 }
 
-impl<T> BuildSql for Not<T, Monomial>
+impl<K, V> BuildSql for JsonObjectAgg<K, V>
 where
-    T: Expression<Term = Monomial> + BuildSql,
-    T::SqlType: Comparable<SqlTypeBool>,
+    K: BuildSql,
+    V: BuildSql,
 {
-    fn build_sql(&self, buf: &mut Vec<u8>, params: &mut Vec<Value>) -> Result<(), BuildSqlError> {
-        (|| -> Result<(), anyhow::Error> {
-            write!(buf, "NOT ")?;
-            self.expr.build_sql(buf, params)?;
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        let name = match ctx.dialect() {
+            DialectKind::MySql => "JSON_OBJECTAGG",
+            DialectKind::Postgres => "json_object_agg",
+            DialectKind::Sqlite => "json_group_object",
+        };
+        ctx.record_visit(|| VisitEvent::Function { name });
+        ctx.write_function::<Aggregate, _>(|ctx| {
+            write!(buf, "{}(", name)?;
+            self.key.build_sql_with(ctx, buf, params)?;
+            write!(buf, ", ")?;
+            self.value.build_sql_with(ctx, buf, params)?;
+            write!(buf, ")")?;
             Ok(())
-        })()
-        .map_err(From::from)
+        })
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.key.size_hint() + self.value.size_hint() + "json_group_object(, )".len()
     }
 }
 
-impl<T> BuildSql for Not<T, Polynomial>
+/// Text columns searched together by [`match_against`] and [`to_tsvector`].
+pub trait TextColumns: Columns + BuildSql {
+    const LEN: usize;
+}
+
+impl<E> TextColumns for E
 where
-    T: Expression<Term = Polynomial> + BuildSql,
-    T::SqlType: Comparable<SqlTypeBool>,
+    E: Expression + BuildSql,
+    E::SqlType: Comparable<SqlTypeString>,
 {
-    fn build_sql(&self, buf: &mut Vec<u8>, params: &mut Vec<Value>) -> Result<(), BuildSqlError> {
-        (|| -> Result<(), anyhow::Error> {
-            write!(buf, "NOT (")?;
-            self.expr.build_sql(buf, params)?;
-            write!(buf, ")")?;
-            Ok(())
-        })()
-        .map_err(From::from)
+    const LEN: usize = 1;
+}
+
+/// How [`match_against`] reads its query.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum MatchMode {
+    /// `IN NATURAL LANGUAGE MODE`, or `plainto_tsquery` on Postgres.
+    NaturalLanguage,
+    /// `IN BOOLEAN MODE` with `+word -word "phrase"`, or `websearch_to_tsquery` on Postgres.
+    ///
+    /// `websearch_to_tsquery` reads `-word`, `"phrase"` and `or`, but not the `+`, `*`, `>`, `<`
+    /// and parentheses of MySQL, so the same query may match differently.
+    Boolean,
+}
+
+/// Write the text search configuration as a literal, e.g. `'english'`, so that the expression
+/// matches an index on `to_tsvector('english', ...)`. Only (schema qualified) identifiers are accepted.
+fn write_ts_config(config: &str, buf: &mut Vec<u8>) -> Result<(), BuildSqlError> {
+    let is_identifier = |part: &str| {
+        !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    };
+    if config.split('.').count() > 2 || !config.split('.').all(is_identifier) {
+        return Err(anyhow::anyhow!("invalid text search configuration `{}`", config).into());
     }
+    write!(buf, "'{}', ", config)?;
+    Ok(())
 }
 
-pub trait AndOperatorMethod: Expression + Sized
+/// `to_tsvector(a)`, or `to_tsvector(concat_ws(' ', a, b))` for several columns. Postgres only.
+#[derive(Debug, Clone)]
+pub struct ToTsVector<C> {
+    config: Option<String>,
+    columns: C,
+}
+
+pub fn to_tsvector<C>(columns: C) -> ToTsVector<C>
 where
-    Self::SqlType: Comparable<SqlTypeBool>,
+    C: TextColumns,
 {
-    fn and<R>(self, rhs: R) -> And<Self, Self::BoolOperation, R, R::BoolOperation>
-    where
-        R: Expression,
-        R::SqlType: Comparable<Self::SqlType>,
-    {
-        And::new(self, rhs)
+    ToTsVector {
+        config: None,
+        columns,
     }
 }
 
-pub trait OrOperatorMethod: Expression + Sized
-where
-    Self::SqlType: Comparable<SqlTypeBool>,
-{
-    fn or<R>(self, rhs: R) -> Or<Self, Self::BoolOperation, R, R::BoolOperation>
-    where
-        R: Expression,
-        R::SqlType: Comparable<Self::SqlType>,
-    {
-        Or::new(self, rhs)
+impl<C> ToTsVector<C> {
+    /// The text search configuration, e.g. `english`.
+    pub fn config(mut self, config: impl Into<String>) -> ToTsVector<C> {
+        self.config = Some(config.into());
+        self
+    }
+
+    /// `vector @@ query`.
+    pub fn matches(self, query: TsQuery) -> TsMatch<C> {
+        TsMatch {
+            vector: self,
+            query,
+        }
     }
 }
 
-pub trait NotOperatorMethod: Expression + Sized
+fn write_to_tsvector<C>(
+    config: Option<&str>,
+    columns: &C,
+    ctx: &mut BuildContext,
+    buf: &mut Vec<u8>,
+    params: &mut Vec<Value>,
+) -> Result<(), BuildSqlError>
 where
-    Self::SqlType: Comparable<SqlTypeBool>,
+    C: TextColumns,
 {
-    fn not(self) -> Not<Self, Self::Term> {
-        Not::new(self)
+    if ctx.dialect() != DialectKind::Postgres {
+        ctx.reject(anyhow::anyhow!(
+            "to_tsvector is not supported by {}",
+            ctx.dialect().name()
+        ))?;
+    }
+    write!(buf, "to_tsvector(")?;
+    if let Some(config) = config {
+        write_ts_config(config, buf)?;
     }
+    if C::LEN == 1 {
+        columns.build_sql_with(ctx, buf, params)?;
+    } else {
+        write!(buf, "concat_ws(' ', ")?;
+        columns.build_sql_with(ctx, buf, params)?;
+        write!(buf, ")")?;
+    }
+    write!(buf, ")")?;
+    Ok(())
 }
 
-pub fn not<T: NotOperatorMethod + Expression>(expr: T) -> Not<T, T::Term>
+impl<C> BuildSql for ToTsVector<C>
 where
-    T: NotOperatorMethod + Expression,
-    T::SqlType: Comparable<SqlTypeBool>,
+    C: TextColumns,
 {
-    expr.not()
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        write_to_tsvector(self.config.as_deref(), &self.columns, ctx, buf, params)
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.columns.size_hint()
+            + "to_tsvector('', concat_ws(' ', ))".len()
+            + self.config.as_ref().map_or(0, String::len)
+    }
 }
 
-macro_rules! define_sql_function {
-    ( $func_type:ident, $func_name:ident ( $( $arg_name:ident : $arg_type:ty ),* ) -> $ret_type:ty, $aggregation:ty ) => {
-        #[allow(non_camel_case_types)]
-        #[derive(Debug, Clone)]
-        pub struct $func_type< $( $arg_name, )* > {
-            $( $arg_name: $arg_name, )*
-        }
+/// A text search query bound as a parameter. Postgres only.
+#[derive(Debug, Clone)]
+pub struct TsQuery {
+    function: &'static str,
+    config: Option<String>,
+    query: String,
+}
+
+/// `plainto_tsquery(?)`: all the words of `query`.
+pub fn plainto_tsquery(query: impl Into<String>) -> TsQuery {
+    TsQuery {
+        function: "plainto_tsquery",
+        config: None,
+        query: query.into(),
+    }
+}
 
-        #[allow(non_camel_case_types)]
-        impl<$( $arg_name, )*> $func_type<$( $arg_name, )*> {
-            pub fn new( $( $arg_name: $arg_name, )* ) -> $func_type<$( $arg_name, )*> {
-                $func_type { $( $arg_name, )* }
-            }
-        }
+/// `websearch_to_tsquery(?)`: `query` in the syntax of web search engines, e.g. `"a b" -c`.
+pub fn websearch_to_tsquery(query: impl Into<String>) -> TsQuery {
+    TsQuery {
+        function: "websearch_to_tsquery",
+        config: None,
+        query: query.into(),
+    }
+}
 
-        #[allow(non_camel_case_types)]
-        pub fn $func_name<$( $arg_name, )*>( $( $arg_name: $arg_name, )* ) -> $func_type<$( $arg_name, )*>
-        where
-            $(
-                $arg_name: Expression,
-                <$arg_name>::SqlType: Comparable<$arg_type>,
-            )*
-        {
-            $func_type::new($( $arg_name, )*)
-        }
+impl TsQuery {
+    /// The text search configuration, e.g. `english`.
+    pub fn config(mut self, config: impl Into<String>) -> TsQuery {
+        self.config = Some(config.into());
+        self
+    }
+}
 
-        #[allow(non_camel_case_types)]
-        impl<$( $arg_name, )*> Expression for $func_type<$( $arg_name, )*>
-        where
-            $(
-                $arg_name: Expression,
-                <$arg_name>::SqlType: Comparable<$arg_type>,
-            )*
-        {
-            type SqlType = $ret_type;
-            type Term = Monomial;
-            type BoolOperation = NonBool;
-            type Aggregation = $aggregation;
+impl BuildSql for TsQuery {
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        if ctx.dialect() != DialectKind::Postgres {
+            ctx.reject(anyhow::anyhow!(
+                "{} is not supported by {}",
+                self.function,
+                ctx.dialect().name()
+            ))?;
         }
-
-        #[allow(non_camel_case_types)]
-        impl<$( $arg_name, )*> BuildSql for $func_type<$( $arg_name, )*>
-        where
-            $( $arg_name: BuildSql, )*
-        {
-            fn build_sql(
-                &self,
-                buf: &mut Vec<u8>,
-                params: &mut Vec<Value>,
-            ) -> Result<(), BuildSqlError> {
-                write!(buf, concat!(stringify!($func_name), "("))?;
-                build_sql_comma_separated_values!(buf, params, self, $( $arg_name, )*);
-                write!(buf, ")")?;
-                Ok(())
-            }
+        write!(buf, "{}(", self.function)?;
+        if let Some(config) = &self.config {
+            write_ts_config(config, buf)?;
         }
-    };
+        ctx.write_param(buf, params, Value::String(self.query.clone()))?;
+        write!(buf, ")")?;
+        Ok(())
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::new(
+            "websearch_to_tsquery('', ?)".len() + self.config.as_ref().map_or(0, String::len),
+            1,
+        )
+    }
 }
 
-macro_rules! build_sql_comma_separated_values {
-    ( $buf:ident, $params:ident, $x:ident, $first_field:ident $(, $field:ident )* $(,)* ) => {
-        $x.$first_field.build_sql($buf, $params)?;
-        $(
-            write!($buf, ", ")?;
-            $x.$field.build_sql($buf, $params)?;
-        )*
-    };
-    ( $x:ident, ) => {};
+/// `to_tsvector(...) @@ query`. See [`ToTsVector::matches`].
+#[derive(Debug, Clone)]
+pub struct TsMatch<C> {
+    vector: ToTsVector<C>,
+    query: TsQuery,
 }
 
-// SQLは動的型付けなので関数も動的な型に対応できる必要がある。
-// 例えばsumは整数型にも実数型にも使えるので、複数の型を取り得る。
-// なので関数はtraitとして実装した方が良いのではないか？
-// define_sql_function!(SumInt, sum(t: SqlTypeInt) -> SqlTypeInt, Aggregate);
-define_sql_function!(Count, count(t: SqlTypeAny) -> SqlTypeInt, Aggregate);
+impl<C> TsMatch<C> {
+    /// `ts_rank(to_tsvector(...), query)`, the relevance of a matching row.
+    pub fn rank(&self) -> TsRank<C>
+    where
+        C: Clone,
+    {
+        TsRank(self.clone())
+    }
+}
 
-define_sql_function!(Date, date(t: SqlTypeString) -> SqlTypeString, NonAggregate);
-define_sql_function!(Left, left(t: SqlTypeString, n: SqlTypeInt) -> SqlTypeString, NonAggregate);
+impl<C> Expression for TsMatch<C>
+where
+    C: TextColumns,
+{
+    type SqlType = SqlTypeBool;
+    type Term = Monomial;
+    type BoolOperation = BoolMono;
+    type Aggregation = C::Aggregation;
+}
 
-pub trait SqlKindNumber {}
+impl<C> BuildSql for TsMatch<C>
+where
+    C: TextColumns,
+{
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        self.vector.build_sql_with(ctx, buf, params)?;
+        write!(buf, " @@ ")?;
+        self.query.build_sql_with(ctx, buf, params)
+    }
 
-impl SqlKindNumber for SqlTypeInt {}
-impl SqlKindNumber for SqlTypeUint {}
-impl SqlKindNumber for SqlTypeAny {}
+    fn size_hint(&self) -> SizeHint {
+        self.vector.size_hint() + self.query.size_hint() + " @@ ".len()
+    }
+}
 
+/// `ts_rank(to_tsvector(...), query)`. See [`TsMatch::rank`].
 #[derive(Debug, Clone)]
-pub struct Sum<T>
+pub struct TsRank<C>(TsMatch<C>);
+
+impl<C> Expression for TsRank<C>
 where
-    T: Expression,
-    T::SqlType: SqlKindNumber,
+    C: TextColumns,
 {
-    t: T,
+    type SqlType = SqlTypeAny;
+    type Term = Monomial;
+    type BoolOperation = NonBool;
+    type Aggregation = C::Aggregation;
 }
 
-impl<T> Sum<T>
+impl<C> BuildSql for TsRank<C>
 where
-    T: Expression,
-    T::SqlType: SqlKindNumber,
+    C: TextColumns,
 {
-    pub fn new(t: T) -> Sum<T> {
-        Sum { t }
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        write!(buf, "ts_rank(")?;
+        self.0.vector.build_sql_with(ctx, buf, params)?;
+        write!(buf, ", ")?;
+        self.0.query.build_sql_with(ctx, buf, params)?;
+        write!(buf, ")")?;
+        Ok(())
     }
+
+    fn size_hint(&self) -> SizeHint {
+        self.0.size_hint() + "ts_rank()".len()
+    }
+}
+
+/// `MATCH (a, b) AGAINST (? IN NATURAL LANGUAGE MODE)` on MySQL.
+///
+/// Postgres searches `to_tsvector` of the columns instead. SQLite has no full-text search without FTS tables.
+#[derive(Debug, Clone)]
+pub struct MatchAgainst<C> {
+    columns: C,
+    query: String,
+    mode: MatchMode,
+    config: Option<String>,
 }
 
-pub fn sum<T>(t: T) -> Sum<T>
+pub fn match_against<C>(columns: C, query: impl Into<String>, mode: MatchMode) -> MatchAgainst<C>
 where
-    T: Expression,
-    T::SqlType: SqlKindNumber,
+    C: TextColumns,
 {
-    Sum::new(t)
+    MatchAgainst {
+        columns,
+        query: query.into(),
+        mode,
+        config: None,
+    }
+}
+
+impl<C> MatchAgainst<C> {
+    /// The text search configuration on Postgres, e.g. `english`. MySQL uses the parser of the FULLTEXT index.
+    pub fn config(mut self, config: impl Into<String>) -> MatchAgainst<C> {
+        self.config = Some(config.into());
+        self
+    }
+
+    /// The relevance of a row, usable in `select` and `order_by`.
+    pub fn score(&self) -> MatchScore<C>
+    where
+        C: Clone,
+    {
+        MatchScore(self.clone())
+    }
+
+    fn ts_query(&self) -> TsQuery {
+        let query = match self.mode {
+            MatchMode::NaturalLanguage => plainto_tsquery(self.query.clone()),
+            MatchMode::Boolean => websearch_to_tsquery(self.query.clone()),
+        };
+        TsQuery {
+            config: self.config.clone(),
+            ..query
+        }
+    }
+
+    fn write_match(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError>
+    where
+        C: BuildSql,
+    {
+        write!(buf, "MATCH (")?;
+        self.columns.build_sql_with(ctx, buf, params)?;
+        write!(buf, ") AGAINST (")?;
+        ctx.write_param(buf, params, Value::String(self.query.clone()))?;
+        match self.mode {
+            MatchMode::NaturalLanguage => write!(buf, " IN NATURAL LANGUAGE MODE)")?,
+            MatchMode::Boolean => write!(buf, " IN BOOLEAN MODE)")?,
+        }
+        Ok(())
+    }
 }
 
-impl<T> Expression for Sum<T>
+impl<C> Expression for MatchAgainst<C>
 where
-    T: Expression,
-    T::SqlType: SqlKindNumber,
+    C: TextColumns,
 {
-    type SqlType = T::SqlType;
+    type SqlType = SqlTypeBool;
+    type Term = Monomial;
+    type BoolOperation = BoolMono;
+    type Aggregation = C::Aggregation;
+}
+
+impl<C> BuildSql for MatchAgainst<C>
+where
+    C: TextColumns,
+{
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        match ctx.dialect() {
+            DialectKind::Postgres => {
+                write_to_tsvector(self.config.as_deref(), &self.columns, ctx, buf, params)?;
+                write!(buf, " @@ ")?;
+                self.ts_query().build_sql_with(ctx, buf, params)
+            }
+            dialect => {
+                if dialect == DialectKind::Sqlite {
+                    ctx.reject(anyhow::anyhow!(
+                        "full-text search is not supported by {}",
+                        dialect.name()
+                    ))?;
+                }
+                self.write_match(ctx, buf, params)
+            }
+        }
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.columns.size_hint()
+            + SizeHint::new("MATCH () AGAINST (? IN NATURAL LANGUAGE MODE)".len(), 1)
+    }
+}
+
+/// The relevance of [`MatchAgainst`]: `MATCH ... AGAINST ...` on MySQL and `ts_rank(...)` on Postgres.
+#[derive(Debug, Clone)]
+pub struct MatchScore<C>(MatchAgainst<C>);
+
+impl<C> Expression for MatchScore<C>
+where
+    C: TextColumns,
+{
+    type SqlType = SqlTypeAny;
     type Term = Monomial;
     type BoolOperation = NonBool;
-    type Aggregation = Aggregate;
+    type Aggregation = C::Aggregation;
 }
 
-impl<T> BuildSql for Sum<T>
+impl<C> BuildSql for MatchScore<C>
 where
-    T: BuildSql + Expression,
-    T::SqlType: SqlKindNumber,
+    C: TextColumns,
 {
-    fn build_sql(&self, buf: &mut Vec<u8>, params: &mut Vec<Value>) -> Result<(), BuildSqlError> {
-        write!(buf, "sum(")?;
-        self.t.build_sql(buf, params)?;
-        write!(buf, ")")?;
-        Ok(())
+    fn build_sql_with(
+        &self,
+        ctx: &mut BuildContext,
+        buf: &mut Vec<u8>,
+        params: &mut Vec<Value>,
+    ) -> Result<(), BuildSqlError> {
+        match ctx.dialect() {
+            DialectKind::Postgres => {
+                write!(buf, "ts_rank(")?;
+                write_to_tsvector(self.0.config.as_deref(), &self.0.columns, ctx, buf, params)?;
+                write!(buf, ", ")?;
+                self.0.ts_query().build_sql_with(ctx, buf, params)?;
+                write!(buf, ")")?;
+                Ok(())
+            }
+            _ => self.0.build_sql_with(ctx, buf, params),
+        }
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.0.size_hint() + "ts_rank()".len()
     }
 }
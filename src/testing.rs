@@ -0,0 +1,201 @@
+//! Testing helpers for generated SQL
+//!
+//! Enabled by the `testing` feature.
+//!
+
+use crate::dialect::DialectKind;
+use crate::query_builder::{BuildOptions, BuildQuery, Query, Value};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// Environment variable which makes snapshot assertions rewrite the golden files.
+pub const UPDATE_SNAPSHOTS_ENV: &str = "AQUERYON_UPDATE_SNAPSHOTS";
+
+/// Assert the SQL and parameters built by a builder (or a `Query`).
+///
+/// Whitespace is normalized before comparing, so the expected SQL may be written over multiple lines.
+///
+/// ```ignore
+/// assert_sql!(builder, "SELECT t1.c1 FROM table1 as t1 WHERE t1.c2 = ?;", [1i64]);
+/// ```
+#[macro_export]
+macro_rules! assert_sql {
+    ( $builder:expr, $expected:expr $(,)* ) => {
+        $crate::testing::assert_sql(
+            $crate::testing::build_for_test($builder, $crate::query_builder::BuildOptions::default()),
+            $expected,
+            None,
+        )
+    };
+    ( $builder:expr, $expected:expr, [ $( $param:expr ),* $(,)* ] $(,)* ) => {
+        $crate::testing::assert_sql(
+            $crate::testing::build_for_test($builder, $crate::query_builder::BuildOptions::default()),
+            $expected,
+            Some(&[ $( $crate::query_builder::Value::from($param), )* ]),
+        )
+    };
+}
+
+/// Assert a builder against golden files, one per dialect.
+///
+/// The files are `tests/snapshots/<name>.<dialect>.sql` under the calling crate.
+/// A missing file fails the assertion, and all files are written when `AQUERYON_UPDATE_SNAPSHOTS=1`.
+///
+/// ```ignore
+/// assert_sql_snapshot!(builder, "users_with_posts");
+/// assert_sql_snapshot!(builder, "users_with_posts", [MySql, Postgres]);
+/// ```
+#[macro_export]
+macro_rules! assert_sql_snapshot {
+    ( $builder:expr, $name:expr $(,)* ) => {
+        $crate::testing::assert_snapshot(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots"),
+            $name,
+            $builder,
+            &$crate::dialect::DialectKind::ALL,
+        )
+    };
+    ( $builder:expr, $name:expr, [ $( $dialect:expr ),* $(,)* ] $(,)* ) => {
+        $crate::testing::assert_snapshot(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots"),
+            $name,
+            $builder,
+            &[ $( $crate::dialect::Dialect::kind(&$dialect), )* ],
+        )
+    };
+}
+
+#[doc(hidden)]
+pub fn build_for_test<B: BuildQuery>(builder: B, options: BuildOptions) -> Query {
+    builder
+        .build_query(options)
+        .unwrap_or_else(|e| panic!("failed to build SQL: {}", e))
+}
+
+/// Compare `query` with `expected` after normalizing whitespace, and the parameters if given.
+///
+/// Panics with both SQL and a marker at the first difference.
+#[track_caller]
+pub fn assert_sql(query: Query, expected: &str, expected_params: Option<&[Value]>) {
+    let actual = normalize_sql(query.sql());
+    let expected = normalize_sql(expected);
+    if actual != expected {
+        panic!("SQL mismatch\n{}", diff_message(&expected, &actual));
+    }
+    if let Some(expected_params) = expected_params {
+        if query.params() != expected_params {
+            panic!(
+                "parameters mismatch for `{}`\nexpected: {:?}\n  actual: {:?}",
+                actual,
+                expected_params,
+                query.params()
+            );
+        }
+    }
+}
+
+/// Assert the builder against the golden file of each dialect in `dir`.
+#[track_caller]
+pub fn assert_snapshot<B>(dir: impl AsRef<Path>, name: &str, builder: B, dialects: &[DialectKind])
+where
+    B: BuildQuery + Clone,
+{
+    if std::env::var(UPDATE_SNAPSHOTS_ENV).is_ok_and(|v| v == "1") {
+        return update_snapshots(dir, name, builder, dialects);
+    }
+    for dialect in dialects {
+        let path = snapshot_path(dir.as_ref(), name, *dialect);
+        let actual = render_snapshot(&build_snapshot(builder.clone(), *dialect));
+        match std::fs::read_to_string(&path) {
+            Ok(expected) if expected == actual => {}
+            Ok(expected) => panic!(
+                "snapshot mismatch: {} (set {}=1 to update)\n{}",
+                path.display(),
+                UPDATE_SNAPSHOTS_ENV,
+                diff_message(&expected, &actual)
+            ),
+            Err(e) => panic!(
+                "missing snapshot: {}: {} (set {}=1 to write it)",
+                path.display(),
+                e,
+                UPDATE_SNAPSHOTS_ENV
+            ),
+        }
+    }
+}
+
+/// Write the golden file of each dialect in `dir`, as `assert_snapshot` does with `AQUERYON_UPDATE_SNAPSHOTS=1`.
+pub fn update_snapshots<B>(dir: impl AsRef<Path>, name: &str, builder: B, dialects: &[DialectKind])
+where
+    B: BuildQuery + Clone,
+{
+    for dialect in dialects {
+        let path = snapshot_path(dir.as_ref(), name, *dialect);
+        let actual = render_snapshot(&build_snapshot(builder.clone(), *dialect));
+        write_snapshot(&path, &actual);
+    }
+}
+
+fn build_snapshot<B: BuildQuery>(builder: B, dialect: DialectKind) -> Query {
+    build_for_test(builder, BuildOptions::new().dialect(dialect).pretty())
+}
+
+/// Collapse whitespace runs into a single space and drop whitespace inside parentheses.
+pub fn normalize_sql(sql: &str) -> String {
+    let mut normalized = String::with_capacity(sql.len());
+    for word in sql.split_whitespace() {
+        let joined = normalized.ends_with('(') || word.starts_with(')') || word.starts_with(';');
+        if !normalized.is_empty() && !joined {
+            normalized.push(' ');
+        }
+        normalized.push_str(word);
+    }
+    normalized
+}
+
+fn snapshot_path(dir: &Path, name: &str, dialect: DialectKind) -> PathBuf {
+    dir.join(format!("{}.{}.sql", name, dialect.name()))
+}
+
+fn render_snapshot(query: &Query) -> String {
+    let mut snapshot = String::with_capacity(query.sql().len() + 32);
+    snapshot.push_str(query.sql());
+    snapshot.push('\n');
+    for (i, param) in query.params().iter().enumerate() {
+        // Writing to a String never fails.
+        let _ = writeln!(snapshot, "-- ${}: {:?}", i + 1, param);
+    }
+    snapshot
+}
+
+fn write_snapshot(path: &Path, snapshot: &str) {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .unwrap_or_else(|e| panic!("failed to create {}: {}", dir.display(), e));
+    }
+    std::fs::write(path, snapshot)
+        .unwrap_or_else(|e| panic!("failed to write {}: {}", path.display(), e));
+}
+
+/// Both strings line by line, marking the first differing line and column.
+fn diff_message(expected: &str, actual: &str) -> String {
+    let mut message = String::new();
+    let mut reported = false;
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+    loop {
+        let (e, a) = (expected_lines.next(), actual_lines.next());
+        if e.is_none() && a.is_none() {
+            break;
+        }
+        let (e, a) = (e.unwrap_or(""), a.unwrap_or(""));
+        let _ = writeln!(message, "expected: {}", e);
+        let _ = writeln!(message, "  actual: {}", a);
+        if e != a && !reported {
+            let column = e.chars().zip(a.chars()).take_while(|(e, a)| e == a).count();
+            let _ = writeln!(message, "          {}^", " ".repeat(column));
+            reported = true;
+        }
+    }
+    message
+}
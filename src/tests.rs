@@ -594,3 +594,2034 @@ fn function_sum_any() {
     assert_eq!(query.sql(), "SELECT sum(t1.c1) FROM table1 as t1;");
     assert_eq!(query.params(), &[]);
 }
+
+#[test]
+fn size_hint_matches_built_sql() {
+    let builder = SelectBuilder::new();
+    let (builder, t1) = builder.source("table1");
+    let (builder, t2) =
+        builder.inner_join("table2", |t2| t2.column("id").eq(t1.column("table2_id")));
+    let (builder, t3) =
+        builder.left_outer_join("table3", |t3| t3.column("id").eq(t2.column("table3_id")));
+    let builder = builder
+        .filter(
+            t1.column("c2")
+                .any(vec![SqlInt::new(1), SqlInt::new(2)])
+                .and(not(t1.column("c3").like(SqlString::new("foo%")))),
+        )
+        .select((count(t1.column("c1")), t2.column("c1"), t3.column("c1")))
+        .group_by((t2.column("c1"), t3.column("c1")))
+        .order_by(Order::Desc(t2.column("c1")))
+        .limit((10, 15));
+    let mut buf = Vec::new();
+    let mut params = Vec::new();
+    builder
+        .build_sql(&mut buf, &mut params)
+        .expect("Success building SQL");
+
+    assert_eq!(builder.size_hint(), SizeHint::new(buf.len(), params.len()));
+}
+
+#[test]
+fn build_into_reuses_buffers() {
+    let mut sql = String::from("garbage");
+    let mut params = vec![Value::Null];
+    for id in 1..=3 {
+        let (builder, t1) = EmptySelectBuilder::new().source("table1");
+        builder
+            .filter(t1.column("id").eq(SqlInt::new(id)))
+            .select(t1.column("c1"))
+            .build_into(&mut sql, &mut params)
+            .expect("Success building SQL");
+        assert_eq!(sql, "SELECT t1.c1 FROM table1 as t1 WHERE t1.id = ?;");
+        assert_eq!(params, &[Value::Int(id)]);
+    }
+
+    let (builder, t1) = EmptySelectBuilder::new().source("table1");
+    let placeholders = builder
+        .filter(t1.column("id").eq(SqlInt::new(1)))
+        .select(t1.column("c1"))
+        .order_by(Order::Asc(t1.column("c1")).nulls_first())
+        .tag("app", "api")
+        .build_into_with(
+            &BuildOptions::new().dialect(crate::dialect::Postgres),
+            &mut sql,
+            &mut params,
+        )
+        .expect("Success building SQL");
+    assert_eq!(
+        sql,
+        "SELECT t1.c1 FROM table1 as t1 WHERE t1.id = ? ORDER BY t1.c1 ASC NULLS FIRST /* app='api' */;"
+    );
+    assert_eq!(placeholders, [sql.find('?').expect("A placeholder")]);
+
+    let capacity = sql.capacity();
+    let result = Query::build_into(&mut sql, &mut params, SizeHint::default(), |buf, params| {
+        buf.extend_from_slice(b"SELECT ?");
+        params.push(Value::Int(1));
+        Err(anyhow::anyhow!("failed after writing").into())
+    });
+    assert!(result.is_err());
+    assert_eq!(sql, "");
+    assert_eq!(sql.capacity(), capacity);
+    assert!(params.is_empty());
+}
+
+#[test]
+fn debug_sql_inlines_escaped_literals() {
+    use crate::dialect::{MySql, Postgres};
+
+    let (builder, t1) = EmptySelectBuilder::new().source("table1");
+    let query = builder
+        .filter(
+            t1.column("c1")
+                .eq(SqlString::new("it's a \\ test"))
+                .and(t1.column("c2").eq(SqlInt::new(-3)))
+                .and(t1.column("c3").eq(Value::Null)),
+        )
+        .select(t1.column("c1"))
+        .limit(10)
+        .build()
+        .expect("Success building SQL");
+
+    assert_eq!(
+        query.to_debug_sql(MySql),
+        "SELECT t1.c1 FROM table1 as t1 WHERE t1.c1 = 'it''s a \\\\ test' AND t1.c2 = -3 AND t1.c3 = NULL LIMIT 10;"
+    );
+    assert_eq!(
+        query.to_debug_sql(Postgres),
+        "SELECT t1.c1 FROM table1 as t1 WHERE t1.c1 = 'it''s a \\ test' AND t1.c2 = -3 AND t1.c3 = NULL LIMIT 10;"
+    );
+}
+
+#[test]
+fn display_select_builder() {
+    let (builder, t1) = EmptySelectBuilder::new().source("table1");
+    let builder = builder
+        .filter(t1.column("c1").eq(SqlString::new("root")))
+        .select(t1.column("c2"));
+    assert_eq!(
+        builder.to_string(),
+        "SELECT t1.c2 FROM table1 as t1 WHERE t1.c1 = 'root';"
+    );
+}
+
+#[test]
+fn debug_sql_follows_tracked_placeholders() {
+    use crate::dialect::Postgres;
+
+    let (builder, t1) = EmptySelectBuilder::new()
+        .with_scope(TenantScope(7i64))
+        .source("table1");
+    let builder = builder
+        .optimizer_hint(OptimizerHint::raw("QB_NAME(q?)"))
+        .filter(
+            t1.column("c1")
+                .eq(SqlString::new("a?"))
+                .or(t1.column("c2").eq(SqlInt::new(2))),
+        )
+        .select(t1.column("c2"));
+    assert_eq!(
+        builder.to_string(),
+        "SELECT /*+ QB_NAME(q?) */ t1.c2 FROM table1 as t1 WHERE (t1.c1 = 'a?' OR t1.c2 = 2) AND t1.tenant_id = 7;"
+    );
+    let query = builder.clone().build().expect("Success building SQL");
+    assert_eq!(query.placeholders().map(<[usize]>::len), Some(3));
+    assert_eq!(
+        builder
+            .debug_sql(BuildOptions::new().dialect(Postgres))
+            .expect("Success building SQL"),
+        "SELECT /*+ QB_NAME(q?) */ t1.c2 FROM table1 as t1 WHERE (t1.c1 = 'a?' OR t1.c2 = 2) AND t1.tenant_id = 7;"
+    );
+}
+
+#[test]
+fn pretty_format_puts_each_clause_on_its_own_line() {
+    let (sub_builder, sub_t1) = EmptySelectBuilder::new().source("table4");
+    let (mut sub_builder, sub_t2) =
+        sub_builder.inner_join("table5", |t| t.column("id").eq(sub_t1.column("table5_id")));
+    sub_builder.change_sources_alias_name("u");
+
+    let builder = SelectBuilder::new();
+    let (builder, t1) = builder.source("table1");
+    let (builder, t2) =
+        builder.inner_join("table2", |t2| t2.column("id").eq(t1.column("table2_id")));
+    let (builder, t3) =
+        builder.left_outer_join("table3", |t3| t3.column("id").eq(t2.column("table3_id")));
+    let query = builder
+        .filter(
+            t1.column("c2").eq(SqlInt::new(1)).and(
+                t1.column("c3").eq(sub_builder
+                    .filter(sub_t2.column("c1").eq(t1.column("c1")))
+                    .select(sub_t1.column("c3"))),
+            ),
+        )
+        .select((t1.column("c1"), t2.column("c1"), count(t3.column("c1"))))
+        .group_by((t1.column("c1"), t2.column("c1")))
+        .order_by(Order::Asc(t1.column("c1")))
+        .limit(10)
+        .build_with(BuildOptions::new().pretty())
+        .expect("Success building SQL");
+
+    assert_eq!(
+        query.sql(),
+        "SELECT t1.c1, t2.c1, count(t3.c1)
+FROM table1 as t1
+JOIN table2 as t2 ON t2.id = t1.table2_id
+LEFT OUTER JOIN table3 as t3 ON t3.id = t2.table3_id
+WHERE t1.c2 = ? AND t1.c3 = (
+  SELECT u1.c3
+  FROM table4 as u1
+  JOIN table5 as u2 ON u2.id = u1.table5_id
+  WHERE u2.c1 = t1.c1
+)
+GROUP BY t1.c1, t2.c1
+ORDER BY t1.c1 ASC
+LIMIT ?;"
+    );
+    assert_eq!(query.params(), &[Value::Int(1), Value::Int(10)]);
+}
+
+#[test]
+fn pretty_format_indents_source_subquery() {
+    let (sub_builder, sub_t1) = EmptySelectBuilder::new().source("table1");
+    let (builder, t1) = EmptySelectBuilder::new().source(
+        sub_builder
+            .filter(sub_t1.column("c2").eq(SqlInt::new(1)))
+            .select(sub_t1.column("c1")),
+    );
+    let query = SelectForUpdate::for_update(builder.select(t1.column("c1")))
+        .build_with(BuildOptions::new().pretty())
+        .expect("Success building SQL");
+
+    assert_eq!(
+        query.sql(),
+        "SELECT t1.c1
+FROM (
+  SELECT t1.c1
+  FROM table1 as t1
+  WHERE t1.c2 = ?
+) as t1
+FOR UPDATE;"
+    );
+}
+
+#[test]
+fn assert_sql_normalizes_whitespace() {
+    let (builder, t1) = EmptySelectBuilder::new().source("table1");
+    let builder = builder
+        .filter(t1.column("c2").eq(SqlInt::new(1)))
+        .select(t1.column("c1"));
+
+    crate::assert_sql!(
+        builder.clone(),
+        "SELECT t1.c1
+         FROM table1 as t1
+         WHERE t1.c2 = ?;",
+        [1i64]
+    );
+    crate::assert_sql!(
+        builder.build_with(BuildOptions::new().pretty()).unwrap(),
+        "SELECT t1.c1 FROM table1 as t1 WHERE t1.c2 = ?;"
+    );
+}
+
+#[test]
+#[should_panic(expected = "SQL mismatch")]
+fn assert_sql_reports_mismatch() {
+    let (builder, t1) = EmptySelectBuilder::new().source("table1");
+    crate::assert_sql!(
+        builder.select(t1.column("c1")),
+        "SELECT t1.c2 FROM table1 as t1;"
+    );
+}
+
+#[test]
+fn sql_snapshots_per_dialect() {
+    use crate::dialect::DialectKind;
+    use crate::testing::{assert_snapshot, update_snapshots};
+
+    let dir = std::env::temp_dir().join(format!("aqueryon-snapshots-{}", std::process::id()));
+    let (builder, t1) = EmptySelectBuilder::new().source("table1");
+    let builder = builder
+        .filter(t1.column("c2").eq(SqlString::new("x")))
+        .select(t1.column("c1"));
+
+    // A missing golden file fails instead of being written.
+    let missing = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        assert_snapshot(&dir, "select_c1", builder.clone(), &DialectKind::ALL)
+    }));
+    assert!(missing.is_err());
+    assert!(!dir.join("select_c1.postgres.sql").exists());
+
+    update_snapshots(&dir, "select_c1", builder.clone(), &DialectKind::ALL);
+    assert_snapshot(&dir, "select_c1", builder, &DialectKind::ALL);
+
+    let postgres = std::fs::read_to_string(dir.join("select_c1.postgres.sql")).unwrap();
+    assert_eq!(
+        postgres,
+        "SELECT t1.c1\nFROM table1 as t1\nWHERE t1.c2 = ?;\n-- $1: String(\"x\")\n"
+    );
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn raw_sql_fragment_with_bound_values() {
+    let (builder, t1) = EmptySelectBuilder::new().source("table1");
+    let query = builder
+        .filter(
+            t1.column("c1")
+                .eq(SqlInt::new(1))
+                .and(crate::raw_sql!(SqlTypeBool; "t1.c2 = ? OR t1.c3 = ?", 2i64, "x")),
+        )
+        .select((t1.column("c1"), crate::raw_sql!("coalesce(t1.c4, ?)", 0i64)))
+        .build()
+        .expect("Success building SQL");
+    assert_eq!(
+        query.sql(),
+        "SELECT t1.c1, coalesce(t1.c4, ?) FROM table1 as t1 WHERE t1.c1 = ? AND (t1.c2 = ? OR t1.c3 = ?);"
+    );
+    assert_eq!(
+        query.params(),
+        &[
+            Value::Int(0),
+            Value::Int(1),
+            Value::Int(2),
+            Value::String("x".to_string())
+        ]
+    );
+}
+
+#[test]
+fn raw_sql_rejects_placeholder_count_mismatch() {
+    let (builder, t1) = EmptySelectBuilder::new().source("table1");
+    let result = builder
+        .filter(crate::raw_sql!(SqlTypeBool; "t1.c1 = ? AND t1.c2 = ?", 1i64))
+        .select(t1.column("c1"))
+        .build();
+    assert!(result.is_err());
+}
+
+#[test]
+fn raw_sql_skips_quoted_question_marks() {
+    let (builder, t1) = EmptySelectBuilder::new().source("table1");
+    let builder = builder
+        .filter(crate::raw_sql!(SqlTypeBool; "t1.c1 = 'why?' AND t1.c2 = ?", 1i64))
+        .select(t1.column("c1"));
+    assert_eq!(
+        builder.to_string(),
+        "SELECT t1.c1 FROM table1 as t1 WHERE t1.c1 = 'why?' AND t1.c2 = 1;"
+    );
+}
+
+#[test]
+fn raw_sql_skips_backslash_escaped_quotes_on_mysql() {
+    use crate::dialect::Postgres;
+
+    let raw = crate::raw_sql!(SqlTypeBool; r"t1.c1 = 'it\'s ?' AND t1.c2 = ?", 1i64);
+    let build = |options: BuildOptions| {
+        let (builder, t1) = EmptySelectBuilder::new().source("table1");
+        builder
+            .filter(raw.clone())
+            .select(t1.column("c1"))
+            .build_with(options)
+            .expect("Success building SQL")
+    };
+    let query = build(BuildOptions::new());
+    assert_eq!(
+        query.placeholders(),
+        Some(&[query.sql().rfind('?').unwrap_or(0)][..])
+    );
+
+    // Postgres strings have no backslash escapes, so the quote closes the literal.
+    let query = build(BuildOptions::new().dialect(Postgres));
+    assert_eq!(
+        query.placeholders(),
+        Some(&[query.sql().find('?').unwrap_or(0)][..])
+    );
+}
+
+#[test]
+fn raw_sql_skips_comments() {
+    use crate::dialect::Postgres;
+
+    for options in [BuildOptions::new(), BuildOptions::new().dialect(Postgres)] {
+        let (builder, t1) = EmptySelectBuilder::new().source("table1");
+        let query = builder
+            .filter(crate::raw_sql!(
+                SqlTypeBool;
+                "t1.c1 = ? /* why? */ AND t1.c2 = ? -- or ?\n",
+                1i64,
+                2i64
+            ))
+            .select(t1.column("c1"))
+            .build_with(options)
+            .expect("Success building SQL");
+        assert_eq!(query.placeholders().map(<[usize]>::len), Some(2));
+    }
+
+    // `--` without a space is a double minus on MySQL.
+    let (builder, t1) = EmptySelectBuilder::new().source("table1");
+    let query = builder
+        .filter(crate::raw_sql!(SqlTypeBool; "t1.c1 = 1--? # why?\n", 1i64))
+        .select(t1.column("c1"))
+        .build()
+        .expect("Success building SQL");
+    assert_eq!(query.placeholders().map(<[usize]>::len), Some(1));
+}
+
+#[test]
+fn like_escape_escapes_wildcards() {
+    let (builder, t1) = EmptySelectBuilder::new().source("table1");
+    let query = builder
+        .filter(
+            t1.column("c1")
+                .like(like_escape("50%_off!").contains())
+                .and(t1.column("c2").not_like(like_escape("a_b").starts_with())),
+        )
+        .select(t1.column("c1"))
+        .build()
+        .expect("Success building SQL");
+    assert_eq!(
+        query.sql(),
+        "SELECT t1.c1 FROM table1 as t1 WHERE t1.c1 LIKE ? ESCAPE '!' AND t1.c2 NOT LIKE ? ESCAPE '!';"
+    );
+    assert_eq!(
+        query.params(),
+        &[
+            Value::String("%50!%!_off!!%".to_string()),
+            Value::String("a!_b%".to_string())
+        ]
+    );
+}
+
+#[test]
+fn codegen_parses_create_table_ddl() {
+    use crate::codegen::{ColumnSchema, Schema, SchemaFormat};
+
+    let ddl = r#"
+        -- users
+        CREATE TABLE IF NOT EXISTS `shop`.`users` (
+          `id` int(10) unsigned NOT NULL AUTO_INCREMENT,
+          `name` varchar(255) NOT NULL DEFAULT 'a, b',
+          `type` tinyint(1) DEFAULT NULL, /* kind */
+          PRIMARY KEY (`id`),
+          KEY `idx_name` (`name`)
+        ) ENGINE=InnoDB;
+        CREATE TABLE posts (user_id INT NOT NULL, body TEXT, PRIMARY KEY (user_id));
+    "#;
+    let schema = Schema::parse(ddl, SchemaFormat::Ddl).expect("Success parsing DDL");
+    assert_eq!(schema.tables.len(), 2);
+    assert_eq!(schema.tables[0].name, "users");
+    assert_eq!(
+        schema.tables[0].columns,
+        vec![
+            ColumnSchema {
+                name: "id".to_string(),
+                column_type: "int(10) unsigned".to_string(),
+                nullable: false,
+            },
+            ColumnSchema {
+                name: "name".to_string(),
+                column_type: "varchar(255)".to_string(),
+                nullable: false,
+            },
+            ColumnSchema {
+                name: "type".to_string(),
+                column_type: "tinyint(1)".to_string(),
+                nullable: true,
+            },
+        ]
+    );
+    assert!(!schema.tables[1].columns[0].nullable);
+
+    let code = schema.generate("Shop").expect("Success generating code");
+    assert!(code.contains("::aqueryon::impl_joinable!(Shop);"));
+    assert!(code.contains("pub type Table = TypedTable<Marker>;"));
+    assert!(code.contains(
+        "pub fn id<S>(source: &QuerySourceRef<S>) -> Column<NullableIf<S, SqlTypeUint>>"
+    ));
+    assert!(code.contains(
+        "pub fn r#type<S>(source: &QuerySourceRef<S>) -> Column<NullableIf<S, Nullable<SqlTypeInt>>>"
+    ));
+    assert!(code
+        .contains("S: QuerySource<Database = super::Shop> + SourceNullability + OfTable<Marker>,"));
+    assert!(code.contains("source.typed_column::<Nullable<SqlTypeString>>(\"body\")"));
+}
+
+#[test]
+fn codegen_generates_pg_dump_fixture() {
+    use crate::codegen::{ColumnSchema, Schema, SchemaFormat};
+
+    let ddl = include_str!("../tests/fixtures/codegen/pg_dump.sql");
+    let schema = Schema::parse(ddl, SchemaFormat::Ddl).expect("Success parsing DDL");
+    let names: Vec<_> = schema.tables.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, ["import_batches", "users", "posts"]);
+    assert_eq!(
+        schema.tables[1].columns[1],
+        ColumnSchema {
+            name: "name".to_string(),
+            column_type: "character varying(255)".to_string(),
+            nullable: false,
+        }
+    );
+    assert_eq!(
+        schema.tables[1].columns[1].sql_type_marker(),
+        "SqlTypeString"
+    );
+    // The fixture is compiled by the `codegen_generated` integration test.
+    assert_eq!(
+        schema.generate("AppDb").expect("Success generating code"),
+        include_str!("../tests/fixtures/codegen/pg_dump.rs")
+    );
+
+    // `#>>` is an operator of Postgres, not a MySQL comment.
+    let ddl = "CREATE TABLE t (a jsonb, b text DEFAULT ('{}'::jsonb #>> '{x}') NOT NULL);";
+    let schema = Schema::parse(ddl, SchemaFormat::Ddl).expect("Success parsing DDL");
+    assert_eq!(schema.tables[0].columns.len(), 2);
+    assert!(!schema.tables[0].columns[1].nullable);
+    let ddl = "CREATE TABLE `t` (\n  `a` int, # note\n  `b` int NOT NULL\n) ENGINE=InnoDB;";
+    let schema = Schema::parse(ddl, SchemaFormat::Ddl).expect("Success parsing DDL");
+    assert_eq!(schema.tables[0].columns.len(), 2);
+
+    let ddl = "CREATE TABLE t (c varchar(10) CHARACTER SET utf8mb4 NOT NULL);";
+    let schema = Schema::parse(ddl, SchemaFormat::Ddl).expect("Success parsing DDL");
+    assert_eq!(schema.tables[0].columns[0].column_type, "varchar(10)");
+    assert!(!schema.tables[0].columns[0].nullable);
+}
+
+#[test]
+fn codegen_rejects_identifier_collisions() {
+    use crate::codegen::{Schema, SchemaFormat};
+
+    for (ddl, message) in [
+        (
+            "CREATE TABLE t (\"user-id\" int, user_id int);",
+            "column of `t` `user-id` and `user_id` are both generated as `user_id`",
+        ),
+        (
+            "CREATE TABLE t (\"table\" int, table_ int);",
+            "column of `t` `table` and `table_` are both generated as `table_`",
+        ),
+        (
+            "CREATE TABLE t (\"self\" int, self_ int);",
+            "column of `t` `self` and `self_` are both generated as `self_`",
+        ),
+        (
+            "CREATE TABLE \"Users\" (id int); CREATE TABLE users (id int);",
+            "table `Users` and `users` are both generated as `users`",
+        ),
+    ] {
+        let schema = Schema::parse(ddl, SchemaFormat::Ddl).expect("Success parsing DDL");
+        let error = schema.generate("Db").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("CodegenError::NameCollision: {}", message)
+        );
+    }
+}
+
+#[test]
+fn codegen_parses_information_schema_exports() {
+    use crate::codegen::{Schema, SchemaFormat};
+
+    let csv = "TABLE_NAME,COLUMN_NAME,COLUMN_TYPE,IS_NULLABLE,ORDINAL_POSITION\n\
+               users,name,\"varchar(255)\",YES,2\n\
+               users,id,bigint(20) unsigned,NO,1\n\
+               posts,id,int(11),NO,1\n";
+    let json = r#"[
+        {"TABLE_NAME": "users", "COLUMN_NAME": "name", "DATA_TYPE": "varchar", "IS_NULLABLE": "YES", "ORDINAL_POSITION": 2},
+        {"TABLE_NAME": "users", "COLUMN_NAME": "id", "DATA_TYPE": "bigint", "IS_NULLABLE": "NO", "ORDINAL_POSITION": 1},
+        {"TABLE_NAME": "posts", "COLUMN_NAME": "id", "DATA_TYPE": "int", "IS_NULLABLE": "NO", "ORDINAL_POSITION": 1}
+    ]"#;
+
+    for schema in [
+        Schema::parse(csv, SchemaFormat::Csv).expect("Success parsing CSV"),
+        Schema::parse(json, SchemaFormat::Json).expect("Success parsing JSON"),
+    ] {
+        let names: Vec<_> = schema.tables.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["users", "posts"]);
+        let columns: Vec<_> = schema.tables[0]
+            .columns
+            .iter()
+            .map(|c| (c.name.as_str(), c.nullable))
+            .collect();
+        assert_eq!(columns, [("id", false), ("name", true)]);
+    }
+
+    let error = Schema::parse("TABLE_NAME,COLUMN_NAME\nusers\n", SchemaFormat::Csv).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "CodegenError::ParseError: line 2: expected 2 fields, found 1"
+    );
+}
+
+#[test]
+fn ddl_create_table_per_dialect() {
+    use crate::ddl::*;
+    use crate::dialect::{Postgres, Sqlite};
+
+    #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+    struct Shop;
+    crate::impl_joinable!(Shop);
+
+    let posts = CreateTable::new(TableName::<Shop>::new("posts"))
+        .if_not_exists()
+        .column(
+            ColumnDef::new::<SqlTypeInt>("id")
+                .not_null()
+                .primary_key()
+                .auto_increment(),
+        )
+        .column(ColumnDef::new::<SqlTypeUint>("user_id").not_null())
+        .column(ColumnDef::new::<SqlTypeString>("title").default("it's"))
+        .column(ColumnDef::new::<SqlTypeBool>("draft").not_null())
+        .column(ColumnDef::raw("created_at", "TIMESTAMP").default_raw("CURRENT_TIMESTAMP"))
+        .unique("uq_posts_title", &["user_id", "title"])
+        .foreign_key(
+            ForeignKey::new(&["user_id"], TableName::<Shop>::new("users"), &["id"])
+                .on_delete(ReferentialAction::Cascade),
+        );
+
+    assert_eq!(
+        posts.build().expect("Success building SQL").sql(),
+        "CREATE TABLE IF NOT EXISTS posts (id BIGINT NOT NULL AUTO_INCREMENT PRIMARY KEY, \
+         user_id BIGINT UNSIGNED NOT NULL, title VARCHAR(255) DEFAULT 'it''s', draft BOOLEAN NOT NULL, \
+         created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP, CONSTRAINT uq_posts_title UNIQUE (user_id, title), \
+         FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE);"
+    );
+    assert_eq!(
+        posts
+            .build_with(BuildOptions::new().dialect(Postgres).pretty())
+            .expect("Success building SQL")
+            .sql(),
+        "CREATE TABLE IF NOT EXISTS posts (
+  id BIGINT NOT NULL GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+  user_id BIGINT NOT NULL,
+  title TEXT DEFAULT 'it''s',
+  draft BOOLEAN NOT NULL,
+  created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+  CONSTRAINT uq_posts_title UNIQUE (user_id, title),
+  FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);"
+    );
+    assert!(posts
+        .build_with(BuildOptions::new().dialect(Sqlite))
+        .expect("Success building SQL")
+        .sql()
+        .starts_with(
+            "CREATE TABLE IF NOT EXISTS posts (id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT, "
+        ));
+}
+
+#[test]
+fn ddl_alter_drop_and_index() {
+    use crate::ddl::*;
+    use crate::dialect::Sqlite;
+
+    let users = TableName::<AnyDatabase>::new("users");
+    let alter = AlterTable::new(users.clone())
+        .add_column(
+            ColumnDef::new::<SqlTypeString>("email")
+                .not_null()
+                .default(""),
+        )
+        .drop_column("nickname");
+    assert_eq!(
+        alter.build().expect("Success building SQL").sql(),
+        "ALTER TABLE users ADD COLUMN email VARCHAR(255) NOT NULL DEFAULT '', DROP COLUMN nickname;"
+    );
+    assert!(alter
+        .build_with(BuildOptions::new().dialect(Sqlite))
+        .is_err());
+
+    assert_eq!(
+        CreateIndex::new("idx_users_email", users.clone(), &["email"])
+            .unique()
+            .build()
+            .expect("Success building SQL")
+            .sql(),
+        "CREATE UNIQUE INDEX idx_users_email ON users (email);"
+    );
+    assert_eq!(
+        DropTable::new(users)
+            .if_exists()
+            .build()
+            .expect("Success building SQL")
+            .sql(),
+        "DROP TABLE IF EXISTS users;"
+    );
+}
+
+#[test]
+fn migrate_dry_run_lists_pending_migrations() {
+    use crate::ddl::*;
+    use crate::dialect::Sqlite;
+    use crate::migrate::*;
+
+    fn create_users(options: BuildOptions) -> Result<Vec<Query>, QueryBuildError> {
+        Ok(vec![CreateTable::new(TableName::<AnyDatabase>::new(
+            "users",
+        ))
+        .column(ColumnDef::new::<SqlTypeInt>("id").not_null().primary_key())
+        .build_with(options)?])
+    }
+
+    let migrator = Migrator::new(BuildOptions::new().dialect(Sqlite))
+        .migration(Migration::sql(
+            2,
+            "add_email",
+            "-- email; unique later\nALTER TABLE users ADD COLUMN email TEXT DEFAULT ';';\n\nCREATE INDEX idx_email ON users (email);\n",
+        ))
+        .and_then(|m| m.migration(Migration::new(1, "create_users", create_users)))
+        .expect("Success adding migrations");
+
+    assert_eq!(
+        migrator.setup_query().expect("Success building SQL").sql(),
+        "CREATE TABLE IF NOT EXISTS aqueryon_migrations (version INTEGER NOT NULL PRIMARY KEY, name TEXT NOT NULL);"
+    );
+    assert_eq!(
+        migrator
+            .applied_versions_query()
+            .expect("Success building SQL")
+            .sql(),
+        "SELECT t1.version FROM aqueryon_migrations as t1 ORDER BY t1.version ASC;"
+    );
+    assert_eq!(
+        migrator.dry_run(&[1]).expect("Success planning"),
+        "-- 2 add_email
+-- email; unique later
+ALTER TABLE users ADD COLUMN email TEXT DEFAULT ';';
+CREATE INDEX idx_email ON users (email);
+INSERT INTO aqueryon_migrations (version, name) VALUES (?, ?);
+-- $1: Int(2)
+-- $2: String(\"add_email\")
+"
+    );
+    assert_eq!(migrator.plan(&[]).expect("Success planning").len(), 2);
+    assert!(matches!(
+        migrator.migration(Migration::sql(1, "again", "SELECT 1;")),
+        Err(MigrateError::DuplicateVersion(1))
+    ));
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn migrate_sqlite_in_memory() {
+    use crate::dialect::Sqlite;
+    use crate::migrate::*;
+    use sqlx::{Connection, SqliteConnection};
+
+    let migrator = Migrator::new(BuildOptions::new().dialect(Sqlite))
+        .migrations(vec![
+            Migration::sql(
+                1,
+                "create_users",
+                "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT);",
+            ),
+            Migration::sql(
+                2,
+                "seed_users",
+                "INSERT INTO users (name) VALUES ('a'); INSERT INTO users (name) VALUES ('b');",
+            ),
+        ])
+        .expect("Success adding migrations");
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("Success building runtime");
+    runtime.block_on(async {
+        let mut conn = SqliteConnection::connect("sqlite::memory:")
+            .await
+            .expect("Success connecting");
+        assert_eq!(
+            migrator
+                .run_sqlite(&mut conn)
+                .await
+                .expect("Success migrating"),
+            [1, 2]
+        );
+        assert_eq!(
+            migrator
+                .run_sqlite(&mut conn)
+                .await
+                .expect("Success migrating"),
+            Vec::<Version>::new()
+        );
+        let (count,): (i64,) = sqlx::query_as("SELECT count(*) FROM users;")
+            .fetch_one(&mut conn)
+            .await
+            .expect("Success counting");
+        assert_eq!(count, 2);
+    });
+}
+
+#[test]
+fn group_by_rejects_ungrouped_columns() {
+    let (builder, t1) = EmptySelectBuilder::new().source("table1");
+    let builder = builder
+        .select((t1.column("c1"), t1.column("c2"), count(t1.column("c3"))))
+        .group_by(t1.column("c1"));
+    let error = builder.clone().build().unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("`t1.c2` is neither in GROUP BY nor aggregated"));
+    assert_eq!(
+        builder
+            .build_with(BuildOptions::new().allow_partial_group_by())
+            .expect("Success building SQL")
+            .sql(),
+        "SELECT t1.c1, t1.c2, count(t1.c3) FROM table1 as t1 GROUP BY t1.c1;"
+    );
+
+    // ORDER BY is checked too.
+    let (builder, t1) = EmptySelectBuilder::new().source("table1");
+    assert!(builder
+        .select(count(t1.column("c1")))
+        .group_by(t1.column("c2"))
+        .order_by(Order::Asc(t1.column("c3")))
+        .build()
+        .is_err());
+}
+
+#[test]
+fn group_by_checks_each_subquery_separately() {
+    let (sub_builder, sub_t1) = EmptySelectBuilder::new().source("table2");
+    let sub_query = sub_builder
+        .select(count(sub_t1.column("c1")))
+        .group_by(sub_t1.column("c2"));
+    let (builder, t1) = EmptySelectBuilder::new().source("table1");
+    let query = builder
+        .filter(t1.column("c3").eq(sub_query))
+        .select((t1.column("c1"), date(t1.column("c2"))))
+        .group_by((t1.column("c1"), date(t1.column("c2"))))
+        .build()
+        .expect("Success building SQL");
+    assert_eq!(
+        query.sql(),
+        "SELECT t1.c1, date(t1.c2) FROM table1 as t1 WHERE t1.c3 = (SELECT count(t1.c1) FROM table2 as t1 GROUP BY t1.c2) GROUP BY t1.c1, date(t1.c2);"
+    );
+}
+
+#[test]
+fn group_by_matches_columns_used_in_expressions() {
+    // A column used by a grouped expression counts as grouped, though it isn't grouped itself.
+    let (builder, t1) = EmptySelectBuilder::new().source("table1");
+    let query = builder
+        .select((t1.column("c2"), count(t1.column("c1"))))
+        .group_by(date(t1.column("c2")))
+        .build()
+        .expect("Success building SQL");
+    assert_eq!(
+        query.sql(),
+        "SELECT t1.c2, count(t1.c1) FROM table1 as t1 GROUP BY date(t1.c2);"
+    );
+
+    // Expressions of grouped columns are accepted, as the databases do.
+    let (builder, t1) = EmptySelectBuilder::new().source("table1");
+    assert!(builder
+        .select((date(t1.column("c2")), count(t1.column("c1"))))
+        .group_by(t1.column("c2"))
+        .build()
+        .is_ok());
+}
+
+#[test]
+fn outer_joined_columns_are_nullable() {
+    let (builder, t1) = EmptySelectBuilder::new().source("users");
+    let (builder, t2) = builder.left_outer_join("posts", |t2| {
+        t2.typed_column::<SqlTypeInt>("user_id")
+            .eq(t1.typed_column::<SqlTypeInt>("id"))
+    });
+    let id: Column<SqlTypeInt> = t1.typed_column("id");
+    let body: Column<Nullable<SqlTypeString>> = t2.typed_column::<SqlTypeString>("body");
+    let query = builder
+        .filter(body.clone().is_null())
+        .select((id, left(body, Value::Int(3))));
+    assert_eq!(
+        query.to_string(),
+        "SELECT t1.id, left(t2.body, 3) FROM users as t1 LEFT OUTER JOIN posts as t2 ON t2.user_id = t1.id WHERE t2.body IS NULL;"
+    );
+
+    let row: (i64, Option<String>) = query
+        .decode_row(vec![Value::Int(1), Value::Null])
+        .expect("Success decoding row");
+    assert_eq!(row, (1, None));
+    assert!(query.decode_row(vec![Value::Null, Value::Null]).is_err());
+    assert!(query.decode_row(vec![Value::Int(1)]).is_err());
+}
+
+#[test]
+fn aggregates_are_nullable_except_count() {
+    let (builder, t1) = EmptySelectBuilder::new().source("orders");
+    let query = builder.select((
+        count(t1.column("id")),
+        sum(t1.typed_column::<SqlTypeInt>("amount")),
+        max(t1.typed_column::<SqlTypeString>("name")),
+        min(t1.typed_column::<SqlTypeInt>("amount")),
+        avg(t1.typed_column::<SqlTypeInt>("amount")),
+    ));
+    assert_eq!(
+        query.to_string(),
+        "SELECT count(t1.id), sum(t1.amount), max(t1.name), min(t1.amount), avg(t1.amount) FROM orders as t1;"
+    );
+
+    // Aggregates over no rows are NULL, except count.
+    let row: (i64, Option<i64>, Option<String>, Option<i64>, Value) = query
+        .decode_row(vec![
+            Value::Int(0),
+            Value::Null,
+            Value::Null,
+            Value::Null,
+            Value::Null,
+        ])
+        .expect("Success decoding row");
+    assert_eq!(row, (0, None, None, None, Value::Null));
+    let _ = sum(t1.typed_column::<SqlTypeInt>("amount")).is_null();
+}
+
+#[test]
+fn full_outer_natural_and_using_joins() {
+    use crate::dialect::{MySql, Postgres};
+
+    let (builder, _t1) = EmptySelectBuilder::new().source("users");
+    let (builder, t1, t2) =
+        builder.full_outer_join(Postgres, "accounts", |_| using(["id", "org_id"]));
+    let (builder, _t3) = builder.natural_join("profiles");
+    // The left side is nullable too.
+    let name: Column<Nullable<SqlTypeString>> = t1.typed_column::<SqlTypeString>("name");
+    let email: Column<Nullable<SqlTypeString>> = t2.typed_column::<SqlTypeString>("email");
+    let query = builder.select((name, email));
+    assert_eq!(
+        query.build_with(BuildOptions::new().dialect(Postgres))
+            .expect("Success building SQL")
+            .sql(),
+        "SELECT t1.name, t2.email FROM users as t1 FULL OUTER JOIN accounts as t2 USING (id, org_id) NATURAL JOIN profiles as t3;"
+    );
+
+    // `full_outer_join(MySql, ...)` does not compile; a MySQL build is rejected as well.
+    let (builder, t1) = EmptySelectBuilder::new().source("users");
+    let (builder, _t1, t2) = builder.full_outer_join(Postgres, "accounts", |t2| {
+        t2.column("user_id").eq(t1.column("id"))
+    });
+    let error = builder
+        .select(t2.column("id"))
+        .build_with(BuildOptions::new().dialect(MySql))
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("FULL OUTER JOIN is not supported by mysql"));
+}
+
+#[test]
+fn lateral_joins_and_table_functions() {
+    use crate::dialect::{Postgres, Sqlite};
+
+    let (builder, t1) = EmptySelectBuilder::new().source("users");
+    let (builder, t2) = builder.inner_join(
+        lateral(|b| {
+            let (b, l1) = b.source("posts");
+            b.filter(l1.column("user_id").eq(t1.column("id")))
+                .select(l1.column("title"))
+                .order_by(Order::Desc(l1.column("created_at")))
+                .limit(3)
+        }),
+        |_| True,
+    );
+    let (builder, t3) = builder.cross_join(table_function(
+        "generate_series",
+        (Value::Int(1), Value::Int(2)),
+    ));
+    let query = builder
+        .select((t1.column("name"), t2.column("title"), t3.column("t3")))
+        .build_with(BuildOptions::new().dialect(Postgres))
+        .expect("Success building SQL");
+    assert_eq!(
+        query.sql(),
+        "SELECT t1.name, t2.title, t3.t3 FROM users as t1 JOIN LATERAL (SELECT l1.title FROM posts as l1 WHERE l1.user_id = t1.id ORDER BY l1.created_at DESC LIMIT ?) as t2 ON TRUE CROSS JOIN generate_series(?, ?) as t3;"
+    );
+    assert_eq!(
+        query.params(),
+        &[Value::Int(3), Value::Int(1), Value::Int(2)]
+    );
+
+    let (builder, t1) = EmptySelectBuilder::new().source("users");
+    let (builder, t2) = builder.cross_join(lateral(|b| {
+        let (b, l1) = b.source("posts");
+        b.filter(l1.column("user_id").eq(t1.column("id")))
+            .select(l1.column("title"))
+    }));
+    assert!(builder
+        .select(t2.column("title"))
+        .build_with(BuildOptions::new().dialect(Sqlite))
+        .is_err());
+}
+
+#[test]
+fn values_list_as_source() {
+    use crate::dialect::{Postgres, Sqlite};
+
+    let build = |options: BuildOptions| {
+        let (builder, t1) = EmptySelectBuilder::new().source("users");
+        let (builder, t2) = builder.left_outer_join(
+            values_source(["id", "label"], vec![(1i64, "a"), (2, "b")]),
+            |t2| {
+                t2.value_column::<0>()
+                    .eq(t1.typed_column::<SqlTypeInt>("id"))
+            },
+        );
+        let label: Column<Nullable<SqlTypeString>> = t2.value_column::<1>();
+        builder
+            .select((t1.column("name"), label))
+            .build_with(options)
+            .expect("Success building SQL")
+    };
+
+    let query = build(BuildOptions::new());
+    assert_eq!(
+        query.sql(),
+        "SELECT t1.name, t2.label FROM users as t1 LEFT OUTER JOIN (VALUES ROW(?, ?), ROW(?, ?)) as t2(id, label) ON t2.id = t1.id;"
+    );
+    assert_eq!(
+        query.params(),
+        &[
+            Value::Int(1),
+            Value::String("a".to_string()),
+            Value::Int(2),
+            Value::String("b".to_string())
+        ]
+    );
+    assert_eq!(
+        build(BuildOptions::new().dialect(Postgres)).sql(),
+        "SELECT t1.name, t2.label FROM users as t1 LEFT OUTER JOIN (VALUES (?, ?), (?, ?)) as t2(id, label) ON t2.id = t1.id;"
+    );
+    assert_eq!(
+        build(BuildOptions::new().dialect(Sqlite)).sql(),
+        "SELECT t1.name, t2.label FROM users as t1 LEFT OUTER JOIN (SELECT ? AS id, ? AS label UNION ALL SELECT ?, ?) as t2 ON t2.id = t1.id;"
+    );
+}
+
+#[test]
+fn values_list_columns_by_name_with_bool_and_i32() {
+    use crate::dialect::Postgres;
+
+    let (builder, t1) = EmptySelectBuilder::new().source(values_source(
+        ["id", "active"],
+        vec![(1i32, true), (2, false)],
+    ));
+    let active: Column<SqlTypeBool> = t1.value_column::<1>();
+    assert!(t1.value_column_named("missing").is_none());
+    let query = builder
+        .filter(active)
+        .select(t1.value_column_named("id").expect("Declared column"))
+        .build()
+        .expect("Success building SQL");
+    assert_eq!(
+        query.sql(),
+        "SELECT t1.id FROM (VALUES ROW(?, ?), ROW(?, ?)) as t1(id, active) WHERE t1.active;"
+    );
+    assert_eq!(
+        query.params(),
+        &[
+            Value::Int(1),
+            Value::Bool(true),
+            Value::Int(2),
+            Value::Bool(false)
+        ]
+    );
+    assert_eq!(
+        query.to_debug_sql(Postgres),
+        "SELECT t1.id FROM (VALUES ROW(1, TRUE), ROW(2, FALSE)) as t1(id, active) WHERE t1.active;"
+    );
+}
+
+#[test]
+fn explicit_source_aliases_and_clashes() {
+    let (builder, u) = EmptySelectBuilder::new().source(source_as("user", "u"));
+    let (builder, p) = builder.inner_join("post", |p| p.column("user_id").eq(u.column("id")));
+    assert_eq!(
+        builder
+            .select((u.column("name"), p.column("title")))
+            .to_string(),
+        "SELECT u.name, t2.title FROM user as u JOIN post as t2 ON t2.user_id = u.id;"
+    );
+
+    let (builder, _t1) = EmptySelectBuilder::new().source(source_as("user", "t2"));
+    let (builder, t2) = builder.cross_join("post");
+    let error = builder.select(t2.column("id")).build().unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("source alias `t2` is used by more than one source"));
+
+    // The subquery's own `t1` would capture the reference to the outer `t1`.
+    let (builder, t1) = EmptySelectBuilder::new().source("table1");
+    let (sub_builder, sub_t1) = EmptySelectBuilder::new().source("table2");
+    let sub_query = sub_builder
+        .filter(sub_t1.column("c1").eq(t1.column("c1")))
+        .select(sub_t1.column("c2"));
+    let error = builder
+        .filter(t1.column("c2").eq(sub_query))
+        .select(t1.column("c3"))
+        .build()
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("source alias `t1` of an outer query is shadowed"));
+}
+
+#[test]
+fn order_by_nulls_alias_and_runtime_columns() {
+    use crate::dialect::Postgres;
+
+    let build = |options: BuildOptions| {
+        let (builder, t1) = EmptySelectBuilder::new().source("table1");
+        let total = ColumnAlias::new(t1.column("c2"), "total");
+        let order = (
+            Order::Desc(total.alias_ref()),
+            Order::Asc(t1.column("c3")).nulls_last(),
+            Order::Asc(ColumnPosition(1)),
+        );
+        builder
+            .select((t1.column("c1"), total))
+            .order_by(order)
+            .build_with(options)
+            .expect("Success building SQL")
+    };
+    assert_eq!(
+        build(BuildOptions::new().dialect(Postgres)).sql(),
+        "SELECT t1.c1, t1.c2 as total FROM table1 as t1 ORDER BY total DESC, t1.c3 ASC NULLS LAST, 1 ASC;"
+    );
+    assert_eq!(
+        build(BuildOptions::new()).sql(),
+        "SELECT t1.c1, t1.c2 as total FROM table1 as t1 ORDER BY total DESC, t1.c3 IS NULL ASC, t1.c3 ASC, 1 ASC;"
+    );
+
+    let (builder, t1) = EmptySelectBuilder::new().source("table1");
+    let sortable = SortableColumns::new()
+        .column("name", t1.typed_column::<SqlTypeString>("name"))
+        .column("created", t1.typed_column::<SqlTypeInt>("created_at"));
+    let orders = sortable.parse("-created, name").expect("Known columns");
+    assert_eq!(
+        builder.select(t1.column("id")).order_by(orders).to_string(),
+        "SELECT t1.id FROM table1 as t1 ORDER BY t1.created_at DESC, t1.name ASC;"
+    );
+    assert!(matches!(
+        sortable.parse("name,password"),
+        Err(OrderError::UnknownColumn(key)) if key == "password"
+    ));
+
+    let (builder, t1) = EmptySelectBuilder::new().source("table1");
+    let orders = sortable.parse("").expect("No columns");
+    assert_eq!(
+        builder.select(t1.column("id")).order_by(orders).to_string(),
+        "SELECT t1.id FROM table1 as t1;"
+    );
+
+    // NULLS FIRST/LAST at runtime.
+    let (builder, t1) = EmptySelectBuilder::new().source("table1");
+    let sortable = SortableColumns::new()
+        .column("name", t1.typed_column::<SqlTypeString>("name"))
+        .column("created", t1.typed_column::<SqlTypeInt>("created_at"));
+    let mut orders = sortable
+        .parse("-created NULLS LAST, name nulls first")
+        .expect("Known columns");
+    orders.push(sortable.order("name", true).expect("Known column"));
+    assert_eq!(
+        builder
+            .select(t1.column("id"))
+            .order_by(orders)
+            .build_with(BuildOptions::new().dialect(Postgres))
+            .expect("Success building SQL")
+            .sql(),
+        "SELECT t1.id FROM table1 as t1 ORDER BY t1.created_at DESC NULLS LAST, t1.name ASC NULLS FIRST, t1.name DESC;"
+    );
+}
+
+#[test]
+fn order_by_position_and_alias_must_be_in_the_select_list() {
+    let build = |order: Order<ColumnPosition>| {
+        let (builder, t1) = EmptySelectBuilder::new().source("table1");
+        builder
+            .select((t1.column("c1"), t1.column("c2")))
+            .order_by(order)
+            .build()
+    };
+    assert!(build(Order::Asc(ColumnPosition(2))).is_ok());
+    let error = build(Order::Asc(ColumnPosition(0))).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("column position 0 is out of the select list of 2 columns"));
+    let error = build(Order::Desc(ColumnPosition(3))).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("column position 3 is out of the select list of 2 columns"));
+
+    let (builder, t1) = EmptySelectBuilder::new().source("table1");
+    let total = ColumnAlias::new(t1.column("c2"), "total");
+    let other = ColumnAlias::new(t1.column("c3"), "other");
+    let error = builder
+        .select((t1.column("c1"), total))
+        .order_by(Order::Asc(other.alias_ref()))
+        .build()
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("`other` is not an alias in the select list"));
+
+    // A subquery checks its own select list.
+    let (mut sub_builder, sub_t1) = EmptySelectBuilder::new().source("table2");
+    sub_builder.change_sources_alias_name("s");
+    let sub_query = sub_builder
+        .select(sub_t1.column("c1"))
+        .order_by(Order::Asc(ColumnPosition(2)))
+        .limit(1);
+    let (builder, t1) = EmptySelectBuilder::new().source("table1");
+    let error = builder
+        .filter(t1.column("c1").eq(sub_query))
+        .select((t1.column("c1"), t1.column("c2")))
+        .build()
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("column position 2 is out of the select list of 1 columns"));
+}
+
+#[test]
+fn group_by_rollup_cube_and_grouping_sets() {
+    use crate::dialect::{Postgres, Sqlite};
+
+    let build = |options: BuildOptions| {
+        let (builder, t1) = EmptySelectBuilder::new().source("sales");
+        builder
+            .select((
+                t1.column("region"),
+                t1.column("product"),
+                grouping(t1.column("product")),
+                sum(t1.typed_column::<SqlTypeInt>("amount")),
+            ))
+            .group_by(rollup((t1.column("region"), t1.column("product"))))
+            .having(grouping(t1.column("region")).eq(Value::Int(0)))
+            .build_with(options)
+    };
+    assert_eq!(
+        build(BuildOptions::new()).expect("Success building SQL").sql(),
+        "SELECT t1.region, t1.product, grouping(t1.product), sum(t1.amount) FROM sales as t1 GROUP BY t1.region, t1.product WITH ROLLUP HAVING grouping(t1.region) = ?;"
+    );
+    assert_eq!(
+        build(BuildOptions::new().dialect(Postgres))
+            .expect("Success building SQL")
+            .sql(),
+        "SELECT t1.region, t1.product, grouping(t1.product), sum(t1.amount) FROM sales as t1 GROUP BY ROLLUP (t1.region, t1.product) HAVING grouping(t1.region) = ?;"
+    );
+    assert!(build(BuildOptions::new().dialect(Sqlite)).is_err());
+
+    let (builder, t1) = EmptySelectBuilder::new().source("sales");
+    let query = builder
+        .select((t1.column("region"), count(t1.column("id"))))
+        .group_by(grouping_sets((
+            grouping_set((t1.column("region"), t1.column("product"))),
+            grouping_set(t1.column("region")),
+            EmptyGroupingSet,
+        )))
+        .build_with(BuildOptions::new().dialect(Postgres))
+        .expect("Success building SQL");
+    assert_eq!(
+        query.sql(),
+        "SELECT t1.region, count(t1.id) FROM sales as t1 GROUP BY GROUPING SETS ((t1.region, t1.product), (t1.region), ());"
+    );
+}
+
+#[test]
+fn rolled_up_columns_are_nullable_and_grouping_args_grouped() {
+    use crate::dialect::Postgres;
+
+    // Subtotal rows have NULL for the rolled up columns.
+    let (builder, t1) = EmptySelectBuilder::new().source("sales");
+    let region: Column<SqlTypeString> = t1.typed_column("region");
+    let error = builder
+        .select((region.clone(), sum(t1.typed_column::<SqlTypeInt>("amount"))))
+        .group_by(rollup(region))
+        .build()
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("`t1.region` is rolled up into NULL in subtotal rows"));
+
+    let (builder, t1) = EmptySelectBuilder::new().source("sales");
+    let region: Column<Nullable<SqlTypeString>> = t1.typed_column("region");
+    let query = builder
+        .select((region.clone(), sum(t1.typed_column::<SqlTypeInt>("amount"))))
+        .group_by(cube(region));
+    let row: (Option<String>, Option<i64>) = query
+        .decode_row(vec![Value::Null, Value::Int(10)])
+        .expect("Success decoding row");
+    assert_eq!(row, (None, Some(10)));
+    assert!(query
+        .build_with(BuildOptions::new().dialect(Postgres))
+        .is_ok());
+
+    // Grouped outside of ROLLUP too, the column is never NULL.
+    let (builder, t1) = EmptySelectBuilder::new().source("sales");
+    let region: Column<SqlTypeString> = t1.typed_column("region");
+    assert!(builder
+        .select((region.clone(), count(t1.column("id"))))
+        .group_by((region.clone(), rollup(region)))
+        .build()
+        .is_ok());
+
+    // grouping() is an aggregate, but its argument must be grouped.
+    let (builder, t1) = EmptySelectBuilder::new().source("sales");
+    let error = builder
+        .select((t1.column("region"), grouping(t1.column("product"))))
+        .group_by(rollup(t1.column("region")))
+        .build()
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("`t1.product` is an argument of grouping() but not in GROUP BY"));
+}
+
+#[test]
+fn distinct_on_and_aggregate_filter() {
+    use crate::dialect::Postgres;
+
+    let (builder, t1) = EmptySelectBuilder::new().source("posts");
+    let query = builder
+        .select(DistinctOn::new(
+            t1.column("user_id"),
+            (t1.column("user_id"), t1.column("title")),
+        ))
+        .order_by((
+            Order::Asc(t1.column("user_id")),
+            Order::Desc(t1.column("created_at")),
+        ));
+    assert_eq!(
+        query
+            .clone()
+            .build_with(BuildOptions::new().dialect(Postgres))
+            .expect("Success building SQL")
+            .sql(),
+        "SELECT DISTINCT ON (t1.user_id) t1.user_id, t1.title FROM posts as t1 ORDER BY t1.user_id ASC, t1.created_at DESC;"
+    );
+    assert!(query.build().is_err());
+
+    let (builder, t1) = EmptySelectBuilder::new().source("posts");
+    let error = builder
+        .select(DistinctOn::new(t1.column("user_id"), t1.column("title")))
+        .order_by(Order::Desc(t1.column("created_at")))
+        .build_with(BuildOptions::new().dialect(Postgres))
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("ORDER BY must start with the DISTINCT ON expressions (t1.user_id)"));
+
+    let build = |options: BuildOptions| {
+        let (builder, t1) = EmptySelectBuilder::new().source("orders");
+        builder
+            .select((
+                t1.column("user_id"),
+                count(t1.column("id")).filter(t1.column("status").eq(Value::from("paid"))),
+                sum(t1.typed_column::<SqlTypeInt>("amount"))
+                    .filter(t1.column("status").eq(Value::from("paid"))),
+            ))
+            .group_by(t1.column("user_id"))
+            .build_with(options)
+            .expect("Success building SQL")
+    };
+    assert_eq!(
+        build(BuildOptions::new().dialect(Postgres)).sql(),
+        "SELECT t1.user_id, count(t1.id) FILTER (WHERE t1.status = ?), sum(t1.amount) FILTER (WHERE t1.status = ?) FROM orders as t1 GROUP BY t1.user_id;"
+    );
+    assert_eq!(
+        build(BuildOptions::new()).sql(),
+        "SELECT t1.user_id, count(CASE WHEN t1.status = ? THEN t1.id END), sum(CASE WHEN t1.status = ? THEN t1.amount END) FROM orders as t1 GROUP BY t1.user_id;"
+    );
+}
+
+#[test]
+fn json_operators_and_aggregates() {
+    use crate::dialect::{Postgres, Sqlite};
+
+    let build = |options: BuildOptions| {
+        let (builder, t1) = EmptySelectBuilder::new().source("events");
+        let payload = t1.typed_column::<SqlTypeJson>("payload");
+        builder
+            .filter(
+                payload
+                    .clone()
+                    .json_get_text("kind")
+                    .eq(Value::from("click"))
+                    .and(
+                        payload
+                            .clone()
+                            .json_contains(json_value(r#"{"tags":["a"]}"#)),
+                    ),
+            )
+            .select((
+                payload.clone().json_get("user"),
+                payload.json_path_text(&["user", "name"]),
+            ))
+            .build_with(options)
+    };
+    let query = build(BuildOptions::new().dialect(Postgres)).expect("Success building SQL");
+    assert_eq!(
+        query.sql(),
+        "SELECT t1.payload -> ?, t1.payload #>> CAST(? AS text[]) FROM events as t1 WHERE t1.payload ->> ? = ? AND t1.payload @> CAST(? AS jsonb);"
+    );
+    assert_eq!(
+        query.params()[..2],
+        [Value::from("user"), Value::from(r#"{"user","name"}"#)]
+    );
+    let query = build(BuildOptions::new()).expect("Success building SQL");
+    assert_eq!(
+        query.sql(),
+        "SELECT JSON_EXTRACT(t1.payload, ?), JSON_UNQUOTE(JSON_EXTRACT(t1.payload, ?)) FROM events as t1 WHERE JSON_UNQUOTE(JSON_EXTRACT(t1.payload, ?)) = ? AND JSON_CONTAINS(t1.payload, CAST(? AS JSON));"
+    );
+    assert_eq!(query.params()[1], Value::from(r#"$."user"."name""#));
+    assert!(build(BuildOptions::new().dialect(Sqlite)).is_err());
+
+    let (builder, t1) = EmptySelectBuilder::new().source("events");
+    let payload = t1.typed_column::<SqlTypeJson>("payload");
+    assert_eq!(
+        builder
+            .select((
+                payload.clone().json_get("user"),
+                payload.json_path_text(&["user", "name"]),
+            ))
+            .build_with(BuildOptions::new().dialect(Sqlite))
+            .expect("Success building SQL")
+            .sql(),
+        "SELECT t1.payload -> ?, t1.payload ->> ? FROM events as t1;"
+    );
+
+    let build = |options: BuildOptions| {
+        let (builder, t1) = EmptySelectBuilder::new().source("users");
+        let query = builder.select((
+            json_agg(t1.column("id")),
+            json_object_agg(t1.typed_column::<SqlTypeString>("name"), t1.column("age")),
+        ));
+        let row: (Option<String>, Option<String>) = query
+            .decode_row(vec![Value::Null, Value::Null])
+            .expect("Success decoding row");
+        assert_eq!(row, (None, None));
+        query.build_with(options).expect("Success building SQL")
+    };
+    assert_eq!(
+        build(BuildOptions::new().dialect(Postgres)).sql(),
+        "SELECT json_agg(t1.id), json_object_agg(t1.name, t1.age) FROM users as t1;"
+    );
+    assert_eq!(
+        build(BuildOptions::new()).sql(),
+        "SELECT JSON_ARRAYAGG(t1.id), JSON_OBJECTAGG(t1.name, t1.age) FROM users as t1;"
+    );
+    assert_eq!(
+        build(BuildOptions::new().dialect(Sqlite)).sql(),
+        "SELECT json_group_array(t1.id), json_group_object(t1.name, t1.age) FROM users as t1;"
+    );
+}
+
+#[test]
+fn full_text_search() {
+    use crate::dialect::{Postgres, Sqlite};
+
+    let build = |options: BuildOptions, mode: MatchMode| {
+        let (builder, t1) = EmptySelectBuilder::new().source("products");
+        let search = match_against(
+            (
+                t1.typed_column::<SqlTypeString>("name"),
+                t1.typed_column::<SqlTypeString>("description"),
+            ),
+            "red shoes",
+            mode,
+        );
+        builder
+            .filter(search.clone())
+            .select((t1.column("id"), search.score()))
+            .order_by(Order::Desc(search.score()))
+            .build_with(options)
+    };
+    let query =
+        build(BuildOptions::new(), MatchMode::NaturalLanguage).expect("Success building SQL");
+    assert_eq!(
+        query.sql(),
+        "SELECT t1.id, MATCH (t1.name, t1.description) AGAINST (? IN NATURAL LANGUAGE MODE) FROM products as t1 WHERE MATCH (t1.name, t1.description) AGAINST (? IN NATURAL LANGUAGE MODE) ORDER BY MATCH (t1.name, t1.description) AGAINST (? IN NATURAL LANGUAGE MODE) DESC;"
+    );
+    assert_eq!(query.params()[0], Value::from("red shoes"));
+    assert_eq!(
+        build(BuildOptions::new().dialect(Postgres), MatchMode::Boolean)
+            .expect("Success building SQL")
+            .sql(),
+        "SELECT t1.id, ts_rank(to_tsvector(concat_ws(' ', t1.name, t1.description)), websearch_to_tsquery(?)) FROM products as t1 WHERE to_tsvector(concat_ws(' ', t1.name, t1.description)) @@ websearch_to_tsquery(?) ORDER BY ts_rank(to_tsvector(concat_ws(' ', t1.name, t1.description)), websearch_to_tsquery(?)) DESC;"
+    );
+    assert!(build(BuildOptions::new().dialect(Sqlite), MatchMode::Boolean).is_err());
+
+    let (builder, t1) = EmptySelectBuilder::new().source("products");
+    let search = to_tsvector(t1.typed_column::<SqlTypeString>("name"))
+        .config("english")
+        .matches(plainto_tsquery("shoe").config("english"));
+    let query = builder
+        .filter(search.clone())
+        .select(search.rank())
+        .build_with(BuildOptions::new().dialect(Postgres))
+        .expect("Success building SQL");
+    assert_eq!(
+        query.sql(),
+        "SELECT ts_rank(to_tsvector('english', t1.name), plainto_tsquery('english', ?)) FROM products as t1 WHERE to_tsvector('english', t1.name) @@ plainto_tsquery('english', ?);"
+    );
+    assert_eq!(query.params(), &[Value::from("shoe"), Value::from("shoe")]);
+
+    let (builder, t1) = EmptySelectBuilder::new().source("products");
+    let query = builder
+        .filter(
+            match_against(
+                t1.typed_column::<SqlTypeString>("name"),
+                "shoe",
+                MatchMode::NaturalLanguage,
+            )
+            .config("pg_catalog.english"),
+        )
+        .select(t1.column("id"))
+        .build_with(BuildOptions::new().dialect(Postgres))
+        .expect("Success building SQL");
+    assert_eq!(
+        query.sql(),
+        "SELECT t1.id FROM products as t1 WHERE to_tsvector('pg_catalog.english', t1.name) @@ plainto_tsquery('pg_catalog.english', ?);"
+    );
+
+    let (builder, t1) = EmptySelectBuilder::new().source("products");
+    let error = builder
+        .filter(
+            to_tsvector(t1.typed_column::<SqlTypeString>("name"))
+                .config("english'); DROP TABLE products; --")
+                .matches(plainto_tsquery("shoe")),
+        )
+        .select(t1.column("id"))
+        .build_with(BuildOptions::new().dialect(Postgres))
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("invalid text search configuration"));
+}
+
+#[test]
+fn index_and_optimizer_hints() {
+    use crate::dialect::Postgres;
+
+    let (builder, t1) = EmptySelectBuilder::new().source("users".force_index(&["idx_email"]));
+    let (mut builder, t2) = builder.inner_join(
+        "posts"
+            .use_index(&["idx_user_id"])
+            .ignore_index(&["idx_created_at", "idx_title"]),
+        |t2| t2.column("user_id").eq(t1.column("id")),
+    );
+    builder.change_sources_alias_name("s");
+    let query = builder
+        .optimizer_hint(OptimizerHint::max_execution_time(1000))
+        .optimizer_hint(OptimizerHint::index(&t1, &["idx_email"]))
+        .filter(t1.column("email").eq(Value::from("a@example.com")))
+        .select((t1.column("id"), t2.column("title")));
+    assert_eq!(
+        query.to_string(),
+        "SELECT /*+ MAX_EXECUTION_TIME(1000) INDEX(s1 `idx_email`) */ s1.id, s2.title FROM users as s1 FORCE INDEX (`idx_email`) JOIN posts as s2 USE INDEX (`idx_user_id`) IGNORE INDEX (`idx_created_at`, `idx_title`) ON s2.user_id = s1.id WHERE s1.email = 'a@example.com';"
+    );
+    assert!(query
+        .build_with(BuildOptions::new().dialect(Postgres))
+        .is_err());
+
+    let (builder, t1) = EmptySelectBuilder::new().source("users");
+    let error = builder
+        .optimizer_hint(OptimizerHint::raw("SET_VAR(x = 1) */ DROP"))
+        .select(t1.column("id"))
+        .build()
+        .unwrap_err();
+    assert!(error.to_string().contains("must not contain `*/`"));
+
+    // Index names are identifiers, not SQL.
+    let (builder, t1) = EmptySelectBuilder::new().source("users".use_index(&["a`) b"]));
+    assert_eq!(
+        builder
+            .optimizer_hint(OptimizerHint::no_index(&t1, &["x`y"]))
+            .select(t1.column("id"))
+            .to_string(),
+        "SELECT /*+ NO_INDEX(t1 `x``y`) */ t1.id FROM users as t1 USE INDEX (`a``) b`);"
+    );
+
+    let (builder, t1) = EmptySelectBuilder::new().source("users".force_index(&[]));
+    let error = builder.select(t1.column("id")).build().unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("FORCE INDEX needs at least one index"));
+    let (builder, t1) = EmptySelectBuilder::new().source("users".use_index(&[]));
+    assert_eq!(
+        builder.select(t1.column("id")).to_string(),
+        "SELECT t1.id FROM users as t1 USE INDEX ();"
+    );
+}
+
+#[test]
+fn sqlcommenter_tags() {
+    let (builder, t1) = EmptySelectBuilder::new().source("users");
+    let query = builder
+        .tag("route", "/users/{id}")
+        .tag("app", "it's */ here")
+        .select(t1.column("id"))
+        .build()
+        .expect("Success building SQL");
+    assert_eq!(
+        query.sql(),
+        "SELECT t1.id FROM users as t1 /* app='it%27s%20%2A%2F%20here',route='%2Fusers%2F%7Bid%7D' */;"
+    );
+
+    let (builder, t1) = EmptySelectBuilder::new().source("users");
+    let line = line!() + 4;
+    let query = builder
+        .tag("app", "api")
+        .select(t1.column("id"))
+        .build_with(BuildOptions::new().tag_hook(caller_location_tags))
+        .expect("Success building SQL");
+    assert_eq!(
+        query.sql(),
+        format!(
+            "SELECT t1.id FROM users as t1 /* app='api',location='src%2Ftests.rs%3A{}' */;",
+            line
+        )
+    );
+
+    let (builder, t1) = DeleteBuilder::new("users");
+    let options =
+        BuildOptions::new().tag_hook(|location: &'static std::panic::Location<'static>| {
+            vec![("line".to_string(), location.line().to_string())]
+        });
+    let line = line!() + 6;
+    let query = crate::explain::explain(
+        builder
+            .filter(t1.column("id").eq(Value::from(1i64)))
+            .tag("app", "api"),
+    )
+    .build_query(options)
+    .expect("Success building SQL");
+    assert_eq!(
+        query.sql(),
+        format!(
+            "EXPLAIN DELETE FROM users WHERE users.id = ? /* app='api',line='{}' */;",
+            line
+        )
+    );
+}
+
+#[test]
+fn explain_statements_and_sqlite_plans() {
+    use crate::dialect::{Postgres, Sqlite};
+    use crate::explain::*;
+
+    let select = || {
+        let (builder, t1) = EmptySelectBuilder::new().source("users");
+        builder
+            .filter(t1.column("email").eq(Value::from("a@example.com")))
+            .select(t1.column("id"))
+    };
+    assert_eq!(
+        explain(select())
+            .format_json()
+            .build_query(BuildOptions::new())
+            .expect("Success building SQL")
+            .sql(),
+        "EXPLAIN FORMAT=JSON SELECT t1.id FROM users as t1 WHERE t1.email = ?;"
+    );
+    let query = explain_analyze(select())
+        .format_json()
+        .build_query(BuildOptions::new().dialect(Postgres))
+        .expect("Success building SQL");
+    assert_eq!(
+        query.sql(),
+        "EXPLAIN (ANALYZE, FORMAT JSON) SELECT t1.id FROM users as t1 WHERE t1.email = ?;"
+    );
+    assert_eq!(query.params(), &[Value::from("a@example.com")]);
+    assert_eq!(
+        explain(select())
+            .build_query(BuildOptions::new().dialect(Sqlite))
+            .expect("Success building SQL")
+            .sql(),
+        "EXPLAIN QUERY PLAN SELECT t1.id FROM users as t1 WHERE t1.email = ?;"
+    );
+    assert!(explain_analyze(select())
+        .build_query(BuildOptions::new().dialect(Sqlite))
+        .is_err());
+
+    let plan = QueryPlan::from_sqlite_rows(vec![
+        (
+            2,
+            0,
+            "SEARCH users USING INDEX idx_users_email (email=?)".to_string(),
+        ),
+        (5, 0, "SCAN TABLE posts".to_string()),
+        (9, 5, "USE TEMP B-TREE FOR ORDER BY".to_string()),
+    ]);
+    assert!(plan.uses_index("idx_users_email"));
+    assert!(plan.scans_table("posts"));
+    assert!(!plan.scans_table("users"));
+    assert_eq!(plan.roots[1].children[0].operation, "USE");
+
+    // SQLite 3.36+ prints the aliases.
+    let mut plan = QueryPlan::from_sqlite_rows(vec![
+        (3, 0, "SCAN t1".to_string()),
+        (
+            5,
+            0,
+            "SEARCH t2 USING INTEGER PRIMARY KEY (rowid=?)".to_string(),
+        ),
+    ]);
+    assert!(!plan.scans_table("users"));
+    plan.resolve_aliases(&[
+        (Some("users".to_string()), "t1".to_string()),
+        (Some("posts".to_string()), "t2".to_string()),
+    ]);
+    assert!(plan.scans_table("users"));
+    assert!(!plan.scans_table("posts"));
+    assert_eq!(plan.roots[0].alias.as_deref(), Some("t1"));
+
+    let plan = QueryPlan::from_sqlite_rows(vec![(2, 0, "SCAN TABLE users AS t1".to_string())]);
+    assert_eq!(plan.roots[0].table.as_deref(), Some("users"));
+    assert_eq!(plan.roots[0].alias.as_deref(), Some("t1"));
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn explain_query_plan_sqlite_in_memory() {
+    use crate::explain::*;
+    use sqlx::{Connection, SqliteConnection};
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("Success building runtime");
+    runtime.block_on(async {
+        let mut conn = SqliteConnection::connect("sqlite::memory:")
+            .await
+            .expect("Success connecting");
+        sqlx::query("CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT);")
+            .execute(&mut conn)
+            .await
+            .expect("Success creating table");
+        sqlx::query("CREATE INDEX idx_users_email ON users (email);")
+            .execute(&mut conn)
+            .await
+            .expect("Success creating index");
+
+        let (builder, t1) = EmptySelectBuilder::new().source("users");
+        let plan = explain(
+            builder
+                .filter(t1.column("email").eq(Value::from("a@example.com")))
+                .select(t1.column("id")),
+        )
+        .query_plan_sqlite(&mut conn)
+        .await
+        .expect("Success explaining");
+        assert!(plan.uses_index("idx_users_email"));
+        assert!(!plan.scans_table("users"));
+
+        let (builder, t1) = EmptySelectBuilder::new().source("users");
+        let plan = explain(
+            builder
+                .filter(t1.column("id").eq(Value::from(1i64)))
+                .select(t1.column("email")),
+        )
+        .query_plan_sqlite(&mut conn)
+        .await
+        .expect("Success explaining");
+        assert!(!plan.scans_table("users"));
+
+        let (builder, t1) = EmptySelectBuilder::new().source("users");
+        let plan = explain(builder.select(t1.column("email")))
+            .query_plan_sqlite(&mut conn)
+            .await
+            .expect("Success explaining");
+        assert!(plan.scans_table("users"));
+    });
+}
+
+#[test]
+fn visit_query_tree() {
+    let (builder, t1) = EmptySelectBuilder::new().source("users");
+    let (builder, t2) =
+        builder.left_outer_join("posts", |t2| t2.column("user_id").eq(t1.column("id")));
+    let (sub, s1) = EmptySelectBuilder::new().source("banned_users");
+    let mut sub = sub.select(s1.column("user_id"));
+    sub.change_sources_alias_name("s");
+    let query = builder
+        .filter(
+            t2.column("status")
+                .eq(Value::from("published"))
+                .and(t1.column("id").not_eq_all(sub)),
+        )
+        .select((t1.column("name"), t2.column("title")))
+        .limit(10);
+
+    let references = QueryReferences::of(&query).expect("Success visiting");
+    assert_eq!(references.tables(), ["users", "posts", "banned_users"]);
+    assert_eq!(
+        references.sources[2],
+        (Some("banned_users".to_string()), "s1".to_string())
+    );
+    assert_eq!(
+        references.columns[..2],
+        [
+            ("t1".to_string(), "name".to_string()),
+            ("t2".to_string(), "title".to_string())
+        ]
+    );
+    assert!(references
+        .columns
+        .contains(&("s1".to_string(), "user_id".to_string())));
+    assert_eq!(
+        references.params,
+        [Value::from("published"), Value::Int(10)]
+    );
+    assert_eq!(references.subqueries, 1);
+
+    let column_references = QueryReferences::of(&t1.column("id")).expect("Success visiting");
+    assert_eq!(
+        column_references.columns,
+        [("t1".to_string(), "id".to_string())]
+    );
+    assert_eq!(column_references.subqueries, 0);
+
+    // DISTINCT ON fails to build on the default dialect, MySQL, but is visited.
+    let (builder, t1) = EmptySelectBuilder::new().source("posts");
+    let query = builder
+        .select(DistinctOn::new(t1.column("user_id"), t1.column("title")))
+        .order_by(Order::Asc(t1.column("user_id")).nulls_first());
+    assert!(query.clone().build().is_err());
+    let references = QueryReferences::of(&query).expect("Success visiting");
+    assert_eq!(references.columns.len(), 3);
+
+    // NULLS FIRST is emulated by repeating the expression on MySQL.
+    let (builder, t1) = EmptySelectBuilder::new().source("posts");
+    let query = builder
+        .select(t1.column("title"))
+        .order_by(Order::Asc(t1.column("user_id")).nulls_first());
+    let references = QueryReferences::of(&query).expect("Success visiting");
+    assert_eq!(
+        references.columns,
+        [
+            ("t1".to_string(), "title".to_string()),
+            ("t1".to_string(), "user_id".to_string())
+        ]
+    );
+}
+
+#[test]
+fn visit_rejected_queries_in_tree_order() {
+    #[derive(Default)]
+    struct Log(Vec<String>);
+
+    impl Visitor for Log {
+        fn visit_column(&mut self, alias: &str, column: &str) {
+            self.0.push(format!("{}.{}", alias, column));
+        }
+
+        fn visit_operator(&mut self, operator: &str) {
+            self.0.push(operator.to_string());
+        }
+
+        fn visit_function(&mut self, name: &str) {
+            self.0.push(format!("{}()", name));
+        }
+
+        fn visit_param(&mut self, param: &Value) {
+            self.0.push(format!("{:?}", param));
+        }
+    }
+
+    // `t1.c2` is neither grouped nor aggregated.
+    let (builder, t1) = EmptySelectBuilder::new().source("table1");
+    let query = builder
+        .filter(
+            t1.column("c1")
+                .eq(Value::Int(1))
+                .and(t1.column("c3").is_null()),
+        )
+        .select((t1.column("c2"), count(t1.column("c4"))))
+        .group_by(t1.column("c1"))
+        .order_by(Order::Asc(ColumnPosition(3)))
+        .limit(5);
+    assert!(query.clone().build().is_err());
+    let mut log = Log::default();
+    query.visit(&mut log).expect("Success visiting");
+    assert_eq!(
+        log.0,
+        [
+            "t1.c2", "count()", "t1.c4", "AND", "=", "t1.c1", "Int(1)", "IS NULL", "t1.c3",
+            "t1.c1", "Int(5)"
+        ]
+    );
+
+    let references = QueryReferences::of(&query).expect("Success visiting");
+    assert_eq!(references.operators, ["AND", "=", "IS NULL"]);
+    assert_eq!(references.functions, ["count"]);
+    assert_eq!(references.params, [Value::Int(1), Value::Int(5)]);
+}
+
+#[test]
+fn tenant_scope_on_every_source() {
+    let (builder, t1) = EmptySelectBuilder::new()
+        .with_scope(TenantScope(7i64))
+        .source("users");
+    let (builder, t2) = builder.inner_join("teams", |t2| t2.column("id").eq(t1.column("team_id")));
+    let (builder, t3) =
+        builder.left_outer_join("posts", |t3| t3.column("user_id").eq(t1.column("id")));
+    let (builder, t4) = builder.inner_join(unscoped("countries"), |t4| {
+        t4.column("code").eq(t1.column("country"))
+    });
+    let (sub, s1) = EmptySelectBuilder::new().source("banned_users");
+    let mut sub = sub.select(s1.column("user_id"));
+    sub.change_sources_alias_name("s");
+    let query = builder
+        .filter(
+            t1.column("name")
+                .eq(Value::from("a"))
+                .or(t1.column("id").not_eq_all(sub)),
+        )
+        .select((t2.column("name"), t3.column("title"), t4.column("name")))
+        .build()
+        .expect("Success building SQL");
+    assert_eq!(
+        query.sql(),
+        "SELECT t2.name, t3.title, t4.name FROM users as t1 JOIN teams as t2 ON t2.id = t1.team_id LEFT OUTER JOIN posts as t3 ON (t3.user_id = t1.id) AND t3.tenant_id = ? JOIN countries as t4 ON t4.code = t1.country WHERE (t1.name = ? OR t1.id != ALL (SELECT s1.user_id FROM banned_users as s1 WHERE s1.tenant_id = ?)) AND t1.tenant_id = ? AND t2.tenant_id = ?;"
+    );
+    assert_eq!(
+        query.params(),
+        &[
+            Value::Int(7),
+            Value::from("a"),
+            Value::Int(7),
+            Value::Int(7),
+            Value::Int(7)
+        ]
+    );
+
+    let (builder, t1) = EmptySelectBuilder::new()
+        .with_scope(TenantScope(7i64))
+        .source("users");
+    assert_eq!(
+        builder.select(t1.column("id")).to_string(),
+        "SELECT t1.id FROM users as t1 WHERE t1.tenant_id = 7;"
+    );
+}
+
+#[test]
+fn tenant_scope_on_subqueries_of_every_clause() {
+    let subquery = |name: &'static str| {
+        let (sub, s1) = EmptySelectBuilder::new().source("posts");
+        let mut sub = sub.select(count(s1.column("id")));
+        sub.change_sources_alias_name(name);
+        sub
+    };
+    let (builder, t1) = EmptySelectBuilder::new()
+        .with_scope(TenantScope(7i64))
+        .source("users");
+    let query = builder
+        .select((t1.column("team_id"), subquery("a")))
+        .group_by(t1.column("team_id"))
+        .having(count(t1.column("id")).gt(subquery("b")))
+        .order_by(Order::Desc(subquery("c")))
+        .build()
+        .expect("Success building SQL");
+    assert_eq!(
+        query.sql(),
+        "SELECT t1.team_id, (SELECT count(a1.id) FROM posts as a1 WHERE a1.tenant_id = ?) FROM users as t1 WHERE t1.tenant_id = ? GROUP BY t1.team_id HAVING count(t1.id) > (SELECT count(b1.id) FROM posts as b1 WHERE b1.tenant_id = ?) ORDER BY (SELECT count(c1.id) FROM posts as c1 WHERE c1.tenant_id = ?) DESC;"
+    );
+    assert_eq!(query.params(), vec![Value::Int(7); 4]);
+}
+
+#[test]
+fn soft_delete_sources() {
+    let (builder, t1) = EmptySelectBuilder::new().source(soft_delete("users"));
+    let (builder, t2) = builder.left_outer_join(soft_delete("posts").column("removed_at"), |t2| {
+        t2.column("user_id").eq(t1.column("id"))
+    });
+    let query = builder
+        .filter(t1.column("name").eq(Value::from("a")))
+        .select((t1.column("id"), t2.column("title")))
+        .build()
+        .expect("Success building SQL");
+    assert_eq!(
+        query.sql(),
+        "SELECT t1.id, t2.title FROM users as t1 LEFT OUTER JOIN posts as t2 ON (t2.user_id = t1.id) AND t2.removed_at IS NULL WHERE (t1.name = ?) AND t1.deleted_at IS NULL;"
+    );
+
+    let (builder, t1) = EmptySelectBuilder::new().source(soft_delete("users").with_deleted());
+    assert_eq!(
+        builder.select(t1.column("id")).to_string(),
+        "SELECT t1.id FROM users as t1;"
+    );
+    let (builder, t1) = EmptySelectBuilder::new().source(soft_delete("users").only_deleted());
+    assert_eq!(
+        builder.select(t1.column("id")).to_string(),
+        "SELECT t1.id FROM users as t1 WHERE t1.deleted_at IS NOT NULL;"
+    );
+
+    let (builder, t1) = DeleteBuilder::new(soft_delete(TableName::<AnyDatabase>::new("users")));
+    let builder = builder.filter(t1.column("id").eq(Value::from(1i64)));
+    let query = builder.clone().build().expect("Success building SQL");
+    assert_eq!(
+        query.sql(),
+        "UPDATE users SET deleted_at = now() WHERE (users.id = ?) AND users.deleted_at IS NULL;"
+    );
+    assert_eq!(query.params(), &[Value::Int(1)]);
+    let query = builder
+        .clone()
+        .build_with(BuildOptions::new().dialect(crate::dialect::Sqlite))
+        .expect("Success building SQL");
+    assert_eq!(
+        query.sql(),
+        "UPDATE users SET deleted_at = CURRENT_TIMESTAMP WHERE (users.id = ?) AND users.deleted_at IS NULL;"
+    );
+    let query = builder
+        .force_delete()
+        .build()
+        .expect("Success building SQL");
+    assert_eq!(query.sql(), "DELETE FROM users WHERE users.id = ?;");
+
+    let (builder, t1) = DeleteBuilder::new("users");
+    let query = builder
+        .filter(t1.column("id").eq(Value::from(1i64)))
+        .build()
+        .expect("Success building SQL");
+    assert_eq!(query.sql(), "DELETE FROM users WHERE users.id = ?;");
+}
+
+#[test]
+fn delete_with_scope() {
+    let (builder, t1) = DeleteBuilder::new(soft_delete("users"));
+    // The table of DELETE has no alias, so a subquery may use `t1`.
+    let (sub, s1) = EmptySelectBuilder::new().source("banned_users");
+    let sub = sub
+        .filter(
+            s1.column("reason")
+                .eq(Value::from("WHERE spam"))
+                .and(s1.column("user_id").eq(t1.column("id"))),
+        )
+        .select(s1.column("user_id"));
+    let query = builder
+        .with_scope(TenantScope(7i64))
+        .filter(t1.column("id").eq_any(sub))
+        .force_delete()
+        .build()
+        .expect("Success building SQL");
+    assert_eq!(
+        query.sql(),
+        "DELETE FROM users WHERE (users.id = ANY (SELECT t1.user_id FROM banned_users as t1 WHERE (t1.reason = ? AND t1.user_id = users.id) AND t1.tenant_id = ?)) AND users.tenant_id = ?;"
+    );
+    assert_eq!(
+        query.params(),
+        &[Value::from("WHERE spam"), Value::Int(7), Value::Int(7)]
+    );
+
+    let (builder, _) = DeleteBuilder::new("users");
+    assert_eq!(
+        builder.with_scope(TenantScope(7i64)).to_string(),
+        "DELETE FROM users WHERE users.tenant_id = 7;"
+    );
+}
@@ -0,0 +1,26 @@
+//! The module generated from `tests/fixtures/codegen/pg_dump.sql` compiles and builds queries.
+//!
+//! The fixture is checked against the generator by `codegen_generates_pg_dump_fixture` of the unit tests.
+
+mod schema {
+    include!("fixtures/codegen/pg_dump.rs");
+}
+
+use aqueryon::query_builder::*;
+use schema::{posts, users};
+
+#[test]
+fn generated_module_builds_queries() {
+    let (builder, u) = EmptySelectBuilder::new().source(users::table());
+    let (builder, p) =
+        builder.left_outer_join(posts::table(), |p| posts::user_id(&p).eq(users::id(&u)));
+    let query = builder
+        .filter(users::name(&u).eq(SqlString::new("a")))
+        .select((users::email(&u), posts::title(&p), posts::score(&p)))
+        .build()
+        .expect("Success building SQL");
+    assert_eq!(
+        query.sql(),
+        "SELECT t1.email, t2.title, t2.score FROM users as t1 LEFT OUTER JOIN posts as t2 ON t2.user_id = t1.id WHERE t1.name = ?;"
+    );
+}
@@ -0,0 +1,198 @@
+// Generated by aqueryon-codegen. Do not edit.
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub struct AppDb;
+
+::aqueryon::impl_joinable!(AppDb);
+
+pub mod import_batches {
+    #![allow(dead_code)]
+    use ::aqueryon::query_builder::*;
+
+    pub const NAME: &str = "import_batches";
+
+    #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+    pub struct Marker;
+
+    impl TableMarker for Marker {
+        type Database = super::AppDb;
+        const NAME: &'static str = NAME;
+    }
+
+    pub type Table = TypedTable<Marker>;
+    pub type Source = QuerySourceRef<Table>;
+
+    pub fn table() -> Table {
+        TypedTable::new()
+    }
+
+    /// `id` integer NOT NULL
+    pub fn id<S>(source: &QuerySourceRef<S>) -> Column<NullableIf<S, SqlTypeInt>>
+    where
+        S: QuerySource<Database = super::AppDb> + SourceNullability + OfTable<Marker>,
+        S::Nullability: ApplyNullability<SqlTypeInt>,
+    {
+        source.typed_column::<SqlTypeInt>("id")
+    }
+}
+
+pub mod users {
+    #![allow(dead_code)]
+    use ::aqueryon::query_builder::*;
+
+    pub const NAME: &str = "users";
+
+    #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+    pub struct Marker;
+
+    impl TableMarker for Marker {
+        type Database = super::AppDb;
+        const NAME: &'static str = NAME;
+    }
+
+    pub type Table = TypedTable<Marker>;
+    pub type Source = QuerySourceRef<Table>;
+
+    pub fn table() -> Table {
+        TypedTable::new()
+    }
+
+    /// `id` bigint NOT NULL
+    pub fn id<S>(source: &QuerySourceRef<S>) -> Column<NullableIf<S, SqlTypeInt>>
+    where
+        S: QuerySource<Database = super::AppDb> + SourceNullability + OfTable<Marker>,
+        S::Nullability: ApplyNullability<SqlTypeInt>,
+    {
+        source.typed_column::<SqlTypeInt>("id")
+    }
+
+    /// `name` character varying(255) NOT NULL
+    pub fn name<S>(source: &QuerySourceRef<S>) -> Column<NullableIf<S, SqlTypeString>>
+    where
+        S: QuerySource<Database = super::AppDb> + SourceNullability + OfTable<Marker>,
+        S::Nullability: ApplyNullability<SqlTypeString>,
+    {
+        source.typed_column::<SqlTypeString>("name")
+    }
+
+    /// `email` character varying(255)
+    pub fn email<S>(source: &QuerySourceRef<S>) -> Column<NullableIf<S, Nullable<SqlTypeString>>>
+    where
+        S: QuerySource<Database = super::AppDb> + SourceNullability + OfTable<Marker>,
+        S::Nullability: ApplyNullability<Nullable<SqlTypeString>>,
+    {
+        source.typed_column::<Nullable<SqlTypeString>>("email")
+    }
+
+    /// `country` character(2) NOT NULL
+    pub fn country<S>(source: &QuerySourceRef<S>) -> Column<NullableIf<S, SqlTypeString>>
+    where
+        S: QuerySource<Database = super::AppDb> + SourceNullability + OfTable<Marker>,
+        S::Nullability: ApplyNullability<SqlTypeString>,
+    {
+        source.typed_column::<SqlTypeString>("country")
+    }
+
+    /// `is_admin` boolean NOT NULL
+    pub fn is_admin<S>(source: &QuerySourceRef<S>) -> Column<NullableIf<S, SqlTypeBool>>
+    where
+        S: QuerySource<Database = super::AppDb> + SourceNullability + OfTable<Marker>,
+        S::Nullability: ApplyNullability<SqlTypeBool>,
+    {
+        source.typed_column::<SqlTypeBool>("is_admin")
+    }
+
+    /// `profile` jsonb
+    pub fn profile<S>(source: &QuerySourceRef<S>) -> Column<NullableIf<S, Nullable<SqlTypeJson>>>
+    where
+        S: QuerySource<Database = super::AppDb> + SourceNullability + OfTable<Marker>,
+        S::Nullability: ApplyNullability<Nullable<SqlTypeJson>>,
+    {
+        source.typed_column::<Nullable<SqlTypeJson>>("profile")
+    }
+
+    /// `created_at` timestamp without time zone NOT NULL
+    pub fn created_at<S>(source: &QuerySourceRef<S>) -> Column<NullableIf<S, SqlTypeString>>
+    where
+        S: QuerySource<Database = super::AppDb> + SourceNullability + OfTable<Marker>,
+        S::Nullability: ApplyNullability<SqlTypeString>,
+    {
+        source.typed_column::<SqlTypeString>("created_at")
+    }
+
+    /// `deleted_at` timestamp with time zone
+    pub fn deleted_at<S>(source: &QuerySourceRef<S>) -> Column<NullableIf<S, Nullable<SqlTypeString>>>
+    where
+        S: QuerySource<Database = super::AppDb> + SourceNullability + OfTable<Marker>,
+        S::Nullability: ApplyNullability<Nullable<SqlTypeString>>,
+    {
+        source.typed_column::<Nullable<SqlTypeString>>("deleted_at")
+    }
+}
+
+pub mod posts {
+    #![allow(dead_code)]
+    use ::aqueryon::query_builder::*;
+
+    pub const NAME: &str = "posts";
+
+    #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+    pub struct Marker;
+
+    impl TableMarker for Marker {
+        type Database = super::AppDb;
+        const NAME: &'static str = NAME;
+    }
+
+    pub type Table = TypedTable<Marker>;
+    pub type Source = QuerySourceRef<Table>;
+
+    pub fn table() -> Table {
+        TypedTable::new()
+    }
+
+    /// `id` integer NOT NULL
+    pub fn id<S>(source: &QuerySourceRef<S>) -> Column<NullableIf<S, SqlTypeInt>>
+    where
+        S: QuerySource<Database = super::AppDb> + SourceNullability + OfTable<Marker>,
+        S::Nullability: ApplyNullability<SqlTypeInt>,
+    {
+        source.typed_column::<SqlTypeInt>("id")
+    }
+
+    /// `user_id` bigint NOT NULL
+    pub fn user_id<S>(source: &QuerySourceRef<S>) -> Column<NullableIf<S, SqlTypeInt>>
+    where
+        S: QuerySource<Database = super::AppDb> + SourceNullability + OfTable<Marker>,
+        S::Nullability: ApplyNullability<SqlTypeInt>,
+    {
+        source.typed_column::<SqlTypeInt>("user_id")
+    }
+
+    /// `title` text NOT NULL
+    pub fn title<S>(source: &QuerySourceRef<S>) -> Column<NullableIf<S, SqlTypeString>>
+    where
+        S: QuerySource<Database = super::AppDb> + SourceNullability + OfTable<Marker>,
+        S::Nullability: ApplyNullability<SqlTypeString>,
+    {
+        source.typed_column::<SqlTypeString>("title")
+    }
+
+    /// `body` text
+    pub fn body<S>(source: &QuerySourceRef<S>) -> Column<NullableIf<S, Nullable<SqlTypeString>>>
+    where
+        S: QuerySource<Database = super::AppDb> + SourceNullability + OfTable<Marker>,
+        S::Nullability: ApplyNullability<Nullable<SqlTypeString>>,
+    {
+        source.typed_column::<Nullable<SqlTypeString>>("body")
+    }
+
+    /// `score` double precision
+    pub fn score<S>(source: &QuerySourceRef<S>) -> Column<NullableIf<S, Nullable<SqlTypeAny>>>
+    where
+        S: QuerySource<Database = super::AppDb> + SourceNullability + OfTable<Marker>,
+        S::Nullability: ApplyNullability<Nullable<SqlTypeAny>>,
+    {
+        source.typed_column::<Nullable<SqlTypeAny>>("score")
+    }
+}
@@ -0,0 +1,52 @@
+--
+-- PostgreSQL database dump
+--
+
+SET statement_timeout = 0;
+SET client_encoding = 'UTF8';
+SET default_tablespace = '';
+SET default_table_access_method = heap;
+
+CREATE TEMPORARY TABLE import_batches (
+    id integer NOT NULL
+);
+
+--
+-- Name: users; Type: TABLE; Schema: public; Owner: app
+--
+
+CREATE TABLE public.users (
+    id bigint NOT NULL,
+    name character varying(255) NOT NULL,
+    email character varying(255) COLLATE pg_catalog."default",
+    country character(2) DEFAULT 'JP'::bpchar NOT NULL,
+    is_admin boolean DEFAULT false NOT NULL,
+    profile jsonb,
+    created_at timestamp without time zone DEFAULT now() NOT NULL,
+    deleted_at timestamp with time zone
+);
+
+
+ALTER TABLE public.users OWNER TO app;
+
+CREATE SEQUENCE public.users_id_seq
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+--
+-- Name: posts; Type: TABLE; Schema: public; Owner: app
+--
+
+CREATE TABLE public.posts (
+    id integer NOT NULL,
+    user_id bigint NOT NULL,
+    title text NOT NULL,
+    body text,
+    score double precision
+);
+
+ALTER TABLE ONLY public.users
+    ADD CONSTRAINT users_pkey PRIMARY KEY (id);